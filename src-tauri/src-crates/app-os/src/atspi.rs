@@ -0,0 +1,214 @@
+use snow_shot_app_shared::ElementRect;
+use thiserror::Error;
+use zbus::blocking::Connection;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+const A11Y_BUS_NAME: &str = "org.a11y.Bus";
+const A11Y_BUS_OBJECT_PATH: &str = "/org/a11y/bus";
+const A11Y_BUS_INTERFACE: &str = "org.a11y.Bus";
+const A11Y_STATUS_INTERFACE: &str = "org.a11y.Status";
+const REGISTRY_BUS_NAME: &str = "org.a11y.atspi.Registry";
+const ROOT_OBJECT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NULL_OBJECT_PATH: &str = "/org/a11y/atspi/null";
+const ACCESSIBLE_INTERFACE: &str = "org.a11y.atspi.Accessible";
+const COMPONENT_INTERFACE: &str = "org.a11y.atspi.Component";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+/// ATSPI_COORD_TYPE_SCREEN，以屏幕左上角为原点
+const COORD_TYPE_SCREEN: u32 = 0;
+
+#[derive(Error, Debug)]
+pub enum AtspiError {
+    #[error("AT-SPI D-Bus error")]
+    DBus(#[from] zbus::Error),
+    #[error("AT-SPI invalid value")]
+    Value(#[from] zbus::zvariant::Error),
+}
+
+/**
+ * AT-SPI 状态，取值与 AtspiStateType 一致
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtspiState {
+    Active = 1,
+    Iconified = 15,
+    Showing = 25,
+    Visible = 30,
+}
+
+/// GetState 返回的状态位集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AtspiStateSet(u64);
+
+impl AtspiStateSet {
+    pub fn from_bits(bits: &[u32]) -> Self {
+        let low = bits.first().copied().unwrap_or(0) as u64;
+        let high = bits.get(1).copied().unwrap_or(0) as u64;
+
+        Self(low | (high << 32))
+    }
+
+    pub fn contains(&self, state: AtspiState) -> bool {
+        self.0 & (1 << state as u64) != 0
+    }
+}
+
+/**
+ * AT-SPI 中的可访问对象，由所在应用的总线名称和对象路径确定
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AtspiElement {
+    pub bus_name: String,
+    pub path: OwnedObjectPath,
+}
+
+/**
+ * 读取 org.a11y.Status 的 IsEnabled，即桌面是否开启了辅助功能支持
+ */
+pub fn is_accessibility_enabled() -> Result<bool, AtspiError> {
+    let session = Connection::session()?;
+    let is_enabled: OwnedValue = session
+        .call_method(
+            Some(A11Y_BUS_NAME),
+            A11Y_BUS_OBJECT_PATH,
+            Some(PROPERTIES_INTERFACE),
+            "Get",
+            &(A11Y_STATUS_INTERFACE, "IsEnabled"),
+        )?
+        .body()
+        .deserialize()?;
+
+    Ok(bool::try_from(is_enabled)?)
+}
+
+/**
+ * 设置 org.a11y.Status 的 IsEnabled，开启时通知应用导出辅助功能元素树
+ * GTK 默认导出，Qt、Chromium 等应用只在开启后导出
+ */
+pub fn set_accessibility_enabled(is_enabled: bool) -> Result<(), AtspiError> {
+    let session = Connection::session()?;
+    session.call_method(
+        Some(A11Y_BUS_NAME),
+        A11Y_BUS_OBJECT_PATH,
+        Some(PROPERTIES_INTERFACE),
+        "Set",
+        &(A11Y_STATUS_INTERFACE, "IsEnabled", Value::from(is_enabled)),
+    )?;
+
+    Ok(())
+}
+
+pub struct AtspiConnection {
+    connection: Connection,
+}
+
+impl AtspiConnection {
+    /**
+     * 通过会话总线上的 org.a11y.Bus 获取辅助功能总线地址并连接
+     */
+    pub fn new() -> Result<Self, AtspiError> {
+        let session = Connection::session()?;
+        let address: String = session
+            .call_method(
+                Some(A11Y_BUS_NAME),
+                A11Y_BUS_OBJECT_PATH,
+                Some(A11Y_BUS_INTERFACE),
+                "GetAddress",
+                &(),
+            )?
+            .body()
+            .deserialize()?;
+
+        let connection = zbus::blocking::connection::Builder::address(address.as_str())?.build()?;
+
+        Ok(Self { connection })
+    }
+
+    /**
+     * 桌面根节点，子节点为所有注册了辅助功能的应用
+     */
+    pub fn root(&self) -> AtspiElement {
+        AtspiElement {
+            bus_name: REGISTRY_BUS_NAME.to_string(),
+            path: OwnedObjectPath::try_from(ROOT_OBJECT_PATH).unwrap(),
+        }
+    }
+
+    /**
+     * 获取子节点，跳过空对象
+     */
+    pub fn get_children(&self, element: &AtspiElement) -> Result<Vec<AtspiElement>, AtspiError> {
+        let children: Vec<(String, OwnedObjectPath)> = self
+            .connection
+            .call_method(
+                Some(element.bus_name.as_str()),
+                element.path.as_str(),
+                Some(ACCESSIBLE_INTERFACE),
+                "GetChildren",
+                &(),
+            )?
+            .body()
+            .deserialize()?;
+
+        Ok(children
+            .into_iter()
+            .filter(|(bus_name, path)| !bus_name.is_empty() && path.as_str() != NULL_OBJECT_PATH)
+            .map(|(bus_name, path)| AtspiElement { bus_name, path })
+            .collect())
+    }
+
+    pub fn get_state(&self, element: &AtspiElement) -> Result<AtspiStateSet, AtspiError> {
+        let bits: Vec<u32> = self
+            .connection
+            .call_method(
+                Some(element.bus_name.as_str()),
+                element.path.as_str(),
+                Some(ACCESSIBLE_INTERFACE),
+                "GetState",
+                &(),
+            )?
+            .body()
+            .deserialize()?;
+
+        Ok(AtspiStateSet::from_bits(&bits))
+    }
+
+    pub fn get_name(&self, element: &AtspiElement) -> Result<String, AtspiError> {
+        let name: OwnedValue = self
+            .connection
+            .call_method(
+                Some(element.bus_name.as_str()),
+                element.path.as_str(),
+                Some(PROPERTIES_INTERFACE),
+                "Get",
+                &(ACCESSIBLE_INTERFACE, "Name"),
+            )?
+            .body()
+            .deserialize()?;
+
+        Ok(String::try_from(name)?)
+    }
+
+    /**
+     * 获取元素在屏幕上的区域，未实现 Component 接口的元素返回错误
+     */
+    pub fn get_extents(&self, element: &AtspiElement) -> Result<ElementRect, AtspiError> {
+        let (x, y, width, height): (i32, i32, i32, i32) = self
+            .connection
+            .call_method(
+                Some(element.bus_name.as_str()),
+                element.path.as_str(),
+                Some(COMPONENT_INTERFACE),
+                "GetExtents",
+                &COORD_TYPE_SCREEN,
+            )?
+            .body()
+            .deserialize()?;
+
+        Ok(ElementRect {
+            min_x: x,
+            min_y: y,
+            max_x: x + width,
+            max_y: y + height,
+        })
+    }
+}
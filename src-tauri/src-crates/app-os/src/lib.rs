@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[cfg(target_os = "windows")]
@@ -34,6 +35,12 @@ pub mod utils;
 #[path = "./utils/macos.rs"]
 pub mod utils;
 
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+#[cfg(target_os = "linux")]
+pub mod atspi;
+
 // #[cfg(target_os = "linux")]
 // #[path = "./linux.rs"]
 // pub mod ui_automation;
@@ -112,6 +119,35 @@ impl Ord for ElementLevel {
     }
 }
 
+/**
+ * 通知按钮
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationAction {
+    /**
+     * 按钮 ID，点击后随事件返回
+     */
+    pub id: String,
+    /**
+     * 按钮文字
+     */
+    pub label: String,
+}
+
+/**
+ * 通知内容
+ */
+#[derive(Debug, Clone)]
+pub struct NotificationOptions {
+    pub title: String,
+    pub body: String,
+    /**
+     * 通知中显示的图片，如截图的缩略图
+     */
+    pub image: Option<image::DynamicImage>,
+    pub action_list: Vec<NotificationAction>,
+}
+
 #[derive(Error, Debug)]
 pub enum UIAutomationError {
     #[error("Capture error")]
@@ -123,4 +159,8 @@ pub enum UIAutomationError {
     #[cfg(target_os = "windows")]
     #[error("UIAutomation error")]
     UIAError(#[from] uiautomation::errors::Error),
+
+    #[cfg(target_os = "linux")]
+    #[error("AT-SPI error")]
+    Atspi(#[from] atspi::AtspiError),
 }
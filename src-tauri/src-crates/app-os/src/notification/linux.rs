@@ -1,3 +1,472 @@
-pub fn send_new_version_notification(title: String, body: String) {
-    log::warn!("[notification::send_new_version_notification] not implemented");
-}
\ No newline at end of file
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use zbus::MatchRule;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::Value;
+
+use crate::{NotificationAction, NotificationOptions};
+
+const NOTIFICATIONS_BUS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+const APP_NAME: &str = "Snow Shot";
+/// 通知图片的最大边长，图片通过 D-Bus 传输，不需要原图
+const NOTIFICATION_IMAGE_SIZE: u32 = 256;
+/// 点击通知本身时触发的动作
+pub const DEFAULT_ACTION_ID: &str = "default";
+/// 部分通知服务会把通知留在通知中心而不发送 NotificationClosed，超时后移除回调
+const ACTION_CALLBACK_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+/// 最多保留的回调数量，超出时移除最早登记的回调
+const MAX_ACTION_CALLBACK_COUNT: usize = 64;
+
+type ActionCallback = Arc<dyn Fn(u32, String) + Send + Sync>;
+type ActionCallbackMap = Arc<Mutex<HashMap<u32, ActionCallbackEntry>>>;
+
+struct ActionCallbackEntry {
+    action_callback: ActionCallback,
+    create_time: Instant,
+}
+
+/**
+ * 移除超时的回调，并在数量达到上限时移除最早登记的回调，为新回调留出位置
+ */
+fn evict_action_callback_map(action_callback_map: &mut HashMap<u32, ActionCallbackEntry>) {
+    action_callback_map.retain(|_, entry| entry.create_time.elapsed() < ACTION_CALLBACK_TIMEOUT);
+
+    while action_callback_map.len() >= MAX_ACTION_CALLBACK_COUNT {
+        let oldest_notification_id = match action_callback_map
+            .iter()
+            .min_by_key(|(_, entry)| entry.create_time)
+        {
+            Some((notification_id, _)) => *notification_id,
+            None => break,
+        };
+        action_callback_map.remove(&oldest_notification_id);
+    }
+}
+
+/// 所有通知共用的连接，首次发送通知时创建
+static NOTIFICATION_CONNECTION: Mutex<Option<NotificationConnection>> = Mutex::new(None);
+
+/**
+ * 通知服务的会话总线连接
+ * 后台线程监听 ActionInvoked 和 NotificationClosed 信号，按通知 ID 分发给对应的回调
+ */
+struct NotificationConnection {
+    connection: Connection,
+    action_callback_map: ActionCallbackMap,
+}
+
+impl NotificationConnection {
+    fn new() -> Result<Self, String> {
+        let connection = Connection::session().map_err(|e| {
+            format!(
+                "[NotificationConnection::new] Failed to connect session bus: {}",
+                e
+            )
+        })?;
+
+        let match_rule = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .sender(NOTIFICATIONS_BUS_NAME)
+            .and_then(|builder| builder.path(NOTIFICATIONS_OBJECT_PATH))
+            .and_then(|builder| builder.interface(NOTIFICATIONS_INTERFACE))
+            .map(|builder| builder.build())
+            .map_err(|e| {
+                format!(
+                    "[NotificationConnection::new] Failed to create match rule: {}",
+                    e
+                )
+            })?;
+        let signal_iterator = MessageIterator::for_match_rule(match_rule, &connection, None)
+            .map_err(|e| {
+                format!(
+                    "[NotificationConnection::new] Failed to subscribe signals: {}",
+                    e
+                )
+            })?;
+
+        let action_callback_map: ActionCallbackMap = Arc::new(Mutex::new(HashMap::new()));
+        let listener_callback_map = action_callback_map.clone();
+        std::thread::spawn(move || {
+            for message in signal_iterator {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(_) => break,
+                };
+
+                let header = message.header();
+                match header.member().map(|member| member.as_str()) {
+                    Some("ActionInvoked") => {
+                        let (notification_id, action_id) =
+                            match message.body().deserialize::<(u32, String)>() {
+                                Ok(body) => body,
+                                Err(_) => continue,
+                            };
+
+                        // 在锁外调用回调，回调中可以继续发送通知
+                        let action_callback = listener_callback_map
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .get(&notification_id)
+                            .map(|entry| entry.action_callback.clone());
+                        if let Some(action_callback) = action_callback {
+                            action_callback(notification_id, action_id);
+                        }
+                    }
+                    Some("NotificationClosed") => {
+                        if let Ok((notification_id, _)) = message.body().deserialize::<(u32, u32)>()
+                        {
+                            listener_callback_map
+                                .lock()
+                                .unwrap_or_else(|e| e.into_inner())
+                                .remove(&notification_id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(Self {
+            connection,
+            action_callback_map,
+        })
+    }
+
+    fn notify(
+        &self,
+        options: NotificationOptions,
+        action_callback: ActionCallback,
+    ) -> Result<u32, String> {
+        let proxy = Proxy::new(
+            &self.connection,
+            NOTIFICATIONS_BUS_NAME,
+            NOTIFICATIONS_OBJECT_PATH,
+            NOTIFICATIONS_INTERFACE,
+        )
+        .map_err(|e| {
+            format!(
+                "[NotificationConnection::notify] Failed to create proxy: {}",
+                e
+            )
+        })?;
+
+        // 按钮按 [ID, 文字, ID, 文字, ...] 排列
+        let action_list: Vec<&str> = options
+            .action_list
+            .iter()
+            .flat_map(|action| [action.id.as_str(), action.label.as_str()])
+            .collect();
+
+        let mut hints: HashMap<&str, Value> = HashMap::new();
+        if let Some(image) = &options.image {
+            let image = image
+                .thumbnail(NOTIFICATION_IMAGE_SIZE, NOTIFICATION_IMAGE_SIZE)
+                .to_rgba8();
+
+            // image-data 的签名为 (iiibiiay)：宽、高、行字节数、是否有透明通道、位深、通道数、像素数据
+            hints.insert(
+                "image-data",
+                Value::from((
+                    image.width() as i32,
+                    image.height() as i32,
+                    image.width() as i32 * 4,
+                    true,
+                    8i32,
+                    4i32,
+                    image.into_raw(),
+                )),
+            );
+        }
+
+        // 登记回调前一直持有锁，避免通知刚显示就被点击时监听线程找不到回调
+        let mut action_callback_map = self
+            .action_callback_map
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let notification_id: u32 = proxy
+            .call(
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    "",
+                    options.title.as_str(),
+                    options.body.as_str(),
+                    action_list,
+                    hints,
+                    -1i32,
+                ),
+            )
+            .map_err(|e| {
+                format!(
+                    "[NotificationConnection::notify] Failed to call Notify: {}",
+                    e
+                )
+            })?;
+
+        // 通知 ID 可能被通知服务复用，先移除旧通知的回调
+        action_callback_map.remove(&notification_id);
+        if !options.action_list.is_empty() {
+            evict_action_callback_map(&mut action_callback_map);
+            action_callback_map.insert(
+                notification_id,
+                ActionCallbackEntry {
+                    action_callback,
+                    create_time: Instant::now(),
+                },
+            );
+        }
+
+        Ok(notification_id)
+    }
+}
+
+/**
+ * 通过 org.freedesktop.Notifications 发送通知，返回通知 ID
+ * 所有通知共用一个连接和监听线程，通知关闭、超时或 ID 被复用时移除对应的回调
+ *
+ * @param on_action 点击按钮时调用，参数为通知 ID 和按钮 ID
+ */
+pub fn send_notification<F>(options: NotificationOptions, on_action: F) -> Result<u32, String>
+where
+    F: Fn(u32, String) + Send + Sync + 'static,
+{
+    let mut notification_connection = NOTIFICATION_CONNECTION
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    let result = match notification_connection.as_ref() {
+        Some(connection) => connection.notify(options, Arc::new(on_action)),
+        None => {
+            let connection = NotificationConnection::new()?;
+            let result = connection.notify(options, Arc::new(on_action));
+            *notification_connection = Some(connection);
+            result
+        }
+    };
+
+    // 连接可能已经断开，下次发送时重新连接
+    if result.is_err() {
+        *notification_connection = None;
+    }
+
+    result
+}
+
+/**
+ * 发送新版本通知，点击按钮或通知本身时打开官网
+ *
+ * @param action_label 按钮文字，由前端按当前语言传入
+ */
+pub fn send_new_version_notification(title: String, body: String, action_label: String) {
+    let result = send_notification(
+        NotificationOptions {
+            title,
+            body,
+            image: None,
+            action_list: vec![NotificationAction {
+                id: DEFAULT_ACTION_ID.to_string(),
+                label: action_label,
+            }],
+        },
+        |_, _| {
+            if let Err(e) = std::process::Command::new("xdg-open")
+                .arg("https://snowshot.top/")
+                .spawn()
+            {
+                log::error!(
+                    "[notification::send_new_version_notification] Failed to open website: {:?}",
+                    e
+                );
+            }
+        },
+    );
+
+    if let Err(e) = result {
+        log::warn!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex, mpsc};
+    use zbus::object_server::SignalEmitter;
+    use zbus::zvariant::OwnedValue;
+
+    /// (标题, 按钮, 是否有图片)
+    type MockNotification = (String, Vec<String>, bool);
+
+    #[derive(Default)]
+    struct MockNotifications {
+        notification_list: Arc<Mutex<Vec<MockNotification>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl MockNotifications {
+        #[allow(clippy::too_many_arguments)]
+        async fn notify(
+            &self,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+            _app_name: &str,
+            _replaces_id: u32,
+            _app_icon: &str,
+            summary: &str,
+            _body: &str,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let notification_id = 7;
+            let has_image = hints.contains_key("image-data");
+            self.notification_list.lock().unwrap().push((
+                summary.to_string(),
+                actions.clone(),
+                has_image,
+            ));
+
+            // 模拟用户点击第一个按钮后关闭通知
+            if let Some(action_id) = actions.first() {
+                Self::action_invoked(&emitter, notification_id, action_id)
+                    .await
+                    .unwrap();
+                Self::notification_closed(&emitter, notification_id, 2)
+                    .await
+                    .unwrap();
+            }
+
+            notification_id
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(
+            emitter: &SignalEmitter<'_>,
+            id: u32,
+            action_key: &str,
+        ) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn notification_closed(
+            emitter: &SignalEmitter<'_>,
+            id: u32,
+            reason: u32,
+        ) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn test_evict_action_callback_map() {
+        let action_callback: ActionCallback = Arc::new(|_, _| {});
+        let mut action_callback_map = HashMap::new();
+        let now = Instant::now();
+
+        action_callback_map.insert(
+            1,
+            ActionCallbackEntry {
+                action_callback: action_callback.clone(),
+                create_time: now - ACTION_CALLBACK_TIMEOUT - Duration::from_secs(1),
+            },
+        );
+        for notification_id in 2..=MAX_ACTION_CALLBACK_COUNT as u32 + 1 {
+            action_callback_map.insert(
+                notification_id,
+                ActionCallbackEntry {
+                    action_callback: action_callback.clone(),
+                    create_time: now + Duration::from_millis(notification_id as u64),
+                },
+            );
+        }
+
+        evict_action_callback_map(&mut action_callback_map);
+
+        // 超时的回调被移除，剩余回调达到上限时再移除最早登记的一个
+        assert_eq!(action_callback_map.len(), MAX_ACTION_CALLBACK_COUNT - 1);
+        assert!(!action_callback_map.contains_key(&1));
+        assert!(!action_callback_map.contains_key(&2));
+        assert!(action_callback_map.contains_key(&3));
+    }
+
+    /// 由测试代替通知服务，总线上已有通知服务时失败
+    #[test]
+    #[ignore = "需要独立的会话总线，使用 dbus-run-session cargo test -- --ignored 运行"]
+    fn test_send_notification() {
+        let mock_notifications = MockNotifications::default();
+        let notification_list = mock_notifications.notification_list.clone();
+        let _connection = zbus::blocking::connection::Builder::session()
+            .and_then(|builder| builder.name(NOTIFICATIONS_BUS_NAME))
+            .and_then(|builder| builder.serve_at(NOTIFICATIONS_OBJECT_PATH, mock_notifications))
+            .and_then(|builder| builder.build())
+            .expect("Mock notification service is not available");
+
+        let (sender, receiver) = mpsc::channel();
+        let notification_id = send_notification(
+            NotificationOptions {
+                title: "截图已保存".to_string(),
+                body: "/tmp/screenshot.png".to_string(),
+                image: Some(image::DynamicImage::new_rgba8(640, 320)),
+                action_list: vec![
+                    NotificationAction {
+                        id: "open-folder".to_string(),
+                        label: "打开文件夹".to_string(),
+                    },
+                    NotificationAction {
+                        id: "copy".to_string(),
+                        label: "复制".to_string(),
+                    },
+                ],
+            },
+            move |notification_id, action_id| {
+                sender.send((notification_id, action_id)).unwrap();
+            },
+        )
+        .unwrap();
+        assert_eq!(notification_id, 7);
+
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            (7, "open-folder".to_string())
+        );
+
+        // 通知关闭后移除回调
+        let start_time = Instant::now();
+        while !NOTIFICATION_CONNECTION
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .action_callback_map
+            .lock()
+            .unwrap()
+            .is_empty()
+        {
+            assert!(start_time.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // 之后的通知共用同一个连接
+        send_notification(
+            NotificationOptions {
+                title: "录制已保存".to_string(),
+                body: "/tmp/video.mp4".to_string(),
+                image: None,
+                action_list: vec![],
+            },
+            |_, _| {},
+        )
+        .unwrap();
+
+        let notification_list = notification_list.lock().unwrap();
+        assert_eq!(notification_list.len(), 2);
+        assert_eq!(notification_list[0].0, "截图已保存");
+        assert_eq!(
+            notification_list[0].1,
+            vec!["open-folder", "打开文件夹", "copy", "复制"]
+        );
+        assert!(notification_list[0].2);
+    }
+}
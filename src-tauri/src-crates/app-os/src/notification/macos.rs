@@ -1,4 +1,17 @@
 #[allow(unused)]
-pub fn send_new_version_notification(title: String, body: String) {
+pub fn send_new_version_notification(title: String, body: String, action_label: String) {
     log::warn!("[notification::send_new_version_notification] not implemented");
-}
\ No newline at end of file
+}
+
+#[allow(unused)]
+pub fn send_notification<F>(
+    options: crate::NotificationOptions,
+    on_action: F,
+) -> Result<u32, String>
+where
+    F: Fn(u32, String) + Send + Sync + 'static,
+{
+    Err(String::from(
+        "[notification::send_notification] not implemented",
+    ))
+}
@@ -3,7 +3,14 @@ use windows::{
     core::*,
 };
 
-pub fn send_new_version_notification(title: String, body: String) {
+/**
+ * Toast 通知不带按钮，点击通知本身打开官网，action_label 暂未使用
+ */
+pub fn send_new_version_notification(
+    title: String,
+    body: String,
+    #[allow(unused_variables)] action_label: String,
+) {
     // 首先尝试 Toast 通知，失败时使用备用方案
     if let Err(e) = send_toast_notification(&title, &body, "https://snowshot.top/") {
         log::warn!("Toast 通知发送失败: {}，使用备用方案", e);
@@ -11,6 +18,25 @@ pub fn send_new_version_notification(title: String, body: String) {
     }
 }
 
+/**
+ * 发送通知，Toast 暂不支持图片和按钮，返回的通知 ID 固定为 0
+ */
+pub fn send_notification<F>(
+    options: crate::NotificationOptions,
+    #[allow(unused_variables)] on_action: F,
+) -> std::result::Result<u32, String>
+where
+    F: Fn(u32, String) + Send + Sync + 'static,
+{
+    if options.image.is_some() || !options.action_list.is_empty() {
+        log::warn!("[notification::send_notification] image and actions are not supported");
+    }
+
+    send_notification_with_fallback(&options.title, &options.body);
+
+    Ok(0)
+}
+
 fn send_toast_notification(title: &str, body: &str, url: &str) -> Result<()> {
     unsafe {
         // 初始化 COM
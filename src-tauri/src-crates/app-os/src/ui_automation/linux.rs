@@ -1,41 +1,751 @@
-use crate::os::ElementRect;
-use crate::os::TryGetElementByFocus;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::mem;
 
-pub struct UIElements {}
+use atree::Arena;
+use atree::Token;
+use rtree_rs::{RTree, Rect};
+
+use snow_shot_app_shared::ElementRect;
+use snow_shot_app_utils::monitor_info::MonitorList;
+
+use super::ElementLevel;
+use super::UIAutomationError;
+use crate::atspi::{AtspiConnection, AtspiElement, AtspiState};
+
+enum ElementChildrenNextSiblingCacheItem {
+    Element(AtspiElement, ElementLevel),
+    /**
+     * 叶子节点
+     */
+    Leaf,
+    /**
+     * 没有下一个兄弟节点
+     */
+    NoNext,
+}
+
+pub struct UIElements {
+    atspi: Option<AtspiConnection>,
+    root_element: Option<AtspiElement>,
+    element_cache: RTree<2, i32, ElementLevel>,
+    element_level_map: HashMap<ElementLevel, (AtspiElement, Token)>,
+    element_rect_tree: Arena<ElementRect>,
+    element_children_next_sibling_cache: HashMap<ElementLevel, ElementChildrenNextSiblingCacheItem>,
+    /**
+     * AT-SPI 只能按索引获取兄弟节点，缓存父元素的子节点列表
+     */
+    element_children_map: HashMap<ElementLevel, Vec<AtspiElement>>,
+    window_rect_map: HashMap<ElementLevel, ElementRect>,
+    window_index_level_map: HashMap<i32, ElementLevel>,
+    /**
+     * 辅助功能支持是否由当前进程开启，释放时恢复为关闭
+     */
+    accessibility_enabled_by_self: bool,
+}
 
 impl UIElements {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            atspi: None,
+            root_element: None,
+            element_rect_tree: Arena::new(),
+            element_cache: RTree::new(),
+            element_level_map: HashMap::new(),
+            element_children_next_sibling_cache: HashMap::new(),
+            element_children_map: HashMap::new(),
+            window_rect_map: HashMap::new(),
+            window_index_level_map: HashMap::new(),
+            accessibility_enabled_by_self: false,
+        }
     }
 
-    pub fn init(&mut self) -> Result<(), ()> {
-        log::warn!("[os::ui_automation::linux::init] not implemented");
+    /**
+     * 开启桌面的辅助功能支持，Qt、Chromium 等应用只在开启后才导出元素树
+     * 原本关闭时由 restore_accessibility 或释放时恢复
+     */
+    pub fn init(&mut self) -> Result<(), UIAutomationError> {
+        if self.accessibility_enabled_by_self {
+            return Ok(());
+        }
+
+        match crate::atspi::is_accessibility_enabled() {
+            Ok(true) => return Ok(()),
+            Ok(false) => (),
+            Err(e) => {
+                // 读取失败时无法恢复原值，不修改用户的设置
+                log::warn!(
+                    "[os::ui_automation::linux::init] Failed to read accessibility status: {:?}",
+                    e
+                );
+                return Ok(());
+            }
+        }
+
+        match crate::atspi::set_accessibility_enabled(true) {
+            Ok(_) => self.accessibility_enabled_by_self = true,
+            Err(e) => {
+                log::warn!(
+                    "[os::ui_automation::linux::init] Failed to enable accessibility: {:?}",
+                    e
+                );
+            }
+        }
+
         Ok(())
     }
 
-    pub fn init_cache(
-        &mut self,
-        monitor_rect: ElementRect,
-        try_get_element_by_focus: TryGetElementByFocus,
-    ) -> Result<(), ()> {
-        log::warn!("[os::ui_automation::linux::init_cache] not implemented");
+    /**
+     * 关闭由当前进程开启的辅助功能支持，应用退出时调用
+     */
+    pub fn restore_accessibility(&mut self) {
+        if !self.accessibility_enabled_by_self {
+            return;
+        }
+
+        self.accessibility_enabled_by_self = false;
+        if let Err(e) = crate::atspi::set_accessibility_enabled(false) {
+            log::warn!(
+                "[os::ui_automation::linux::restore_accessibility] Failed to disable accessibility: {:?}",
+                e
+            );
+        }
+    }
+
+    pub fn convert_element_rect_to_rtree_rect(rect: ElementRect) -> Rect<2, i32> {
+        Rect::new([rect.min_x, rect.min_y], [rect.max_x, rect.max_y])
+    }
+
+    fn normalize_rect(rect: ElementRect) -> ElementRect {
+        // 当前矩形的数据不可信，做个纠正
+        let mut rect = rect;
+
+        if rect.min_x > rect.max_x {
+            mem::swap(&mut rect.min_x, &mut rect.max_x);
+        }
+
+        if rect.min_y > rect.max_y {
+            mem::swap(&mut rect.min_y, &mut rect.max_y);
+        }
+
+        rect
+    }
+
+    fn beyond_rect(rect: ElementRect, parent_rect: ElementRect) -> bool {
+        rect.min_x < parent_rect.min_x
+            || rect.max_x > parent_rect.max_x
+            || rect.min_y < parent_rect.min_y
+            || rect.max_y > parent_rect.max_y
+    }
+
+    fn get_intersection_area(rect: ElementRect, other: ElementRect) -> i64 {
+        let intersection = rect.clip_rect(&other);
+        if intersection.min_x >= intersection.max_x || intersection.min_y >= intersection.max_y {
+            return 0;
+        }
+
+        (intersection.max_x - intersection.min_x) as i64
+            * (intersection.max_y - intersection.min_y) as i64
+    }
+
+    /**
+     * 获取所有可见的顶层窗口，按层级从高到低排列
+     * AT-SPI 不提供窗口层级，X11 下按 _NET_CLIENT_LIST_STACKING 的顺序匹配，
+     * 匹配不到的窗口（如 Wayland）排在后面，活动窗口优先
+     */
+    fn get_window_element_list(
+        atspi: &AtspiConnection,
+        root_element: &AtspiElement,
+    ) -> Vec<(AtspiElement, ElementRect)> {
+        let mut frame_list: Vec<(AtspiElement, ElementRect, bool)> = Vec::new();
+        for application in atspi.get_children(root_element).unwrap_or_default() {
+            for frame in atspi.get_children(&application).unwrap_or_default() {
+                let state = match atspi.get_state(&frame) {
+                    Ok(state) => state,
+                    Err(_) => continue,
+                };
+                if !state.contains(AtspiState::Showing) || state.contains(AtspiState::Iconified) {
+                    continue;
+                }
+
+                match atspi.get_name(&frame) {
+                    Ok(name) => {
+                        if name.eq("Snow Shot - Draw") {
+                            continue;
+                        }
+                    }
+                    Err(_) => continue,
+                }
+
+                let frame_rect = match atspi.get_extents(&frame) {
+                    Ok(rect) => Self::normalize_rect(rect),
+                    Err(_) => continue,
+                };
+                if frame_rect.min_x == frame_rect.max_x || frame_rect.min_y == frame_rect.max_y {
+                    continue;
+                }
+
+                frame_list.push((frame, frame_rect, state.contains(AtspiState::Active)));
+            }
+        }
+
+        let mut window_element_list = Vec::with_capacity(frame_list.len());
+        for window in crate::utils::get_window_list() {
+            // 窗口区域包含窗口管理器的边框，选择重叠面积最大且大部分位于窗口内的元素
+            let mut best_match: Option<(usize, i64)> = None;
+            for (index, (_, frame_rect, _)) in frame_list.iter().enumerate() {
+                let area = Self::get_intersection_area(*frame_rect, window.rect);
+                let frame_area = (frame_rect.max_x - frame_rect.min_x) as i64
+                    * (frame_rect.max_y - frame_rect.min_y) as i64;
+                if area * 2 < frame_area {
+                    continue;
+                }
+
+                if best_match.is_none_or(|(_, best_area)| area > best_area) {
+                    best_match = Some((index, area));
+                }
+            }
+
+            if let Some((index, _)) = best_match {
+                let (frame, frame_rect, _) = frame_list.remove(index);
+                window_element_list.push((frame, frame_rect));
+            }
+        }
+
+        frame_list.sort_by_key(|(_, _, is_active)| !*is_active);
+        window_element_list.extend(
+            frame_list
+                .into_iter()
+                .map(|(frame, frame_rect, _)| (frame, frame_rect)),
+        );
+
+        window_element_list
+    }
+
+    /**
+     * 初始化窗口元素缓存
+     */
+    pub fn init_cache(&mut self) -> Result<(), UIAutomationError> {
+        let atspi = AtspiConnection::new()?;
+        let root_element = atspi.root();
+
+        self.element_cache = RTree::new();
+        self.element_level_map.clear();
+        self.element_rect_tree = Arena::new();
+        self.element_children_next_sibling_cache.clear();
+        self.element_children_map.clear();
+        self.window_rect_map.clear();
+        self.window_index_level_map.clear();
+
+        // 桌面的窗口索引应该是最高，因为其优先级最低
+        let mut current_level = ElementLevel::root();
+        let root_element_rect = MonitorList::all().get_monitors_bounding_box();
+
+        let mut root_tree_token = self.element_rect_tree.new_node(root_element_rect);
+        let (_, mut parent_tree_token) = self.insert_element_cache(
+            &mut root_tree_token,
+            root_element.clone(),
+            root_element_rect,
+            current_level,
+        );
+
+        // 遍历所有窗口
+        let children_list = Self::get_window_element_list(&atspi, &root_element);
+
+        self.atspi = Some(atspi);
+        self.root_element = Some(root_element);
+
+        // 窗口层级
+        current_level.window_index = 0;
+        current_level.next_level();
+
+        for (current_child, current_child_rect) in children_list {
+            current_level.window_index += 1;
+            current_level.next_element();
+
+            let (current_child_rect, _) = self.insert_element_cache(
+                &mut parent_tree_token,
+                current_child,
+                current_child_rect,
+                current_level,
+            );
+
+            self.window_rect_map
+                .insert(current_level, current_child_rect);
+            self.window_index_level_map
+                .insert(current_level.window_index, current_level);
+        }
+
         Ok(())
     }
 
-    pub fn recovery_window_z_order(&self) {
-        log::warn!("[os::ui_automation::linux::recovery_window_z_order] not implemented");
+    pub fn insert_element_cache(
+        &mut self,
+        parent_tree_token: &mut Token,
+        element: AtspiElement,
+        element_rect: ElementRect,
+        element_level: ElementLevel,
+    ) -> (ElementRect, Token) {
+        let mut element_rect = Self::normalize_rect(element_rect);
+
+        let window_rect = *self
+            .window_rect_map
+            .get(
+                self.window_index_level_map
+                    .get(&element_level.window_index)
+                    .unwrap_or(&element_level),
+            )
+            .unwrap_or(&element_rect);
+
+        if Self::beyond_rect(element_rect, window_rect) {
+            element_rect = element_rect.clip_rect(&window_rect);
+        }
+
+        self.element_cache.insert(
+            Self::convert_element_rect_to_rtree_rect(element_rect),
+            element_level,
+        );
+
+        let current_node = self.element_rect_tree.new_node(element_rect);
+        parent_tree_token
+            .append_node(&mut self.element_rect_tree, current_node)
+            .unwrap();
+        self.element_level_map
+            .insert(element_level, (element, current_node));
+
+        (element_rect, current_node)
+    }
+
+    fn get_element_from_cache(
+        &self,
+        mouse_x: i32,
+        mouse_y: i32,
+    ) -> Option<(AtspiElement, ElementLevel, ElementRect, Token)> {
+        let element_rect = self
+            .element_cache
+            .search(Rect::new_point([mouse_x, mouse_y]));
+
+        // 获取层级最高的元素
+        let mut max_level = ElementLevel::root();
+        let mut max_level_rect = None;
+        for rect in element_rect {
+            if max_level.cmp(&rect.data) == Ordering::Less {
+                max_level = rect.data.clone();
+                max_level_rect = Some(rect.rect);
+            }
+        }
+        let element_rtree_rect = match max_level_rect {
+            Some(rect) => ElementRect {
+                min_x: rect.min[0],
+                min_y: rect.min[1],
+                max_x: rect.max[0],
+                max_y: rect.max[1],
+            },
+            None => return None,
+        };
+
+        self.element_level_map
+            .get(&max_level)
+            .map(|(element, token)| (element.clone(), max_level, element_rtree_rect, *token))
+    }
+
+    /**
+     * 获取第一个子节点，并缓存子节点列表用于获取兄弟节点
+     */
+    fn get_first_child(
+        &mut self,
+        element: &AtspiElement,
+        element_level: ElementLevel,
+    ) -> Option<AtspiElement> {
+        let children = self.atspi.as_ref()?.get_children(element).ok()?;
+        let first_child = children.first().cloned()?;
+
+        self.element_children_map.insert(element_level, children);
+
+        Some(first_child)
+    }
+
+    /**
+     * 根据当前元素的索引，从父元素的子节点列表中获取下一个兄弟节点
+     */
+    fn get_next_sibling(
+        &self,
+        parent_level: ElementLevel,
+        element_level: ElementLevel,
+    ) -> Option<AtspiElement> {
+        self.element_children_map
+            .get(&parent_level)?
+            .get(element_level.element_index as usize + 1)
+            .cloned()
+    }
+
+    /**
+     * 获取可见元素的区域，不可见或没有区域的元素返回 None
+     */
+    fn get_visible_element_rect(&self, element: &AtspiElement) -> Option<ElementRect> {
+        let atspi = self.atspi.as_ref()?;
+
+        let state = atspi.get_state(element).ok()?;
+        if !state.contains(AtspiState::Showing) {
+            return None;
+        }
+
+        let rect = atspi.get_extents(element).ok()?;
+        if rect.min_x == rect.max_x || rect.min_y == rect.max_y {
+            return None;
+        }
+
+        Some(rect)
     }
 
-    pub fn get_element_from_point_walker<F>(
+    /**
+     * 获取所有可选区域
+     */
+    pub fn get_element_from_point_walker(
         &mut self,
         mouse_x: i32,
         mouse_y: i32,
-        on_try_focus: &F,
-    ) -> Result<Vec<ElementRect>, ()>
-    where
-        F: Fn(),
-    {
-        log::warn!("[os::ui_automation::linux::get_element_from_point_walker] not implemented");
-        Ok(vec![])
+    ) -> Result<Vec<ElementRect>, UIAutomationError> {
+        let root_element = match self.root_element.clone() {
+            Some(root_element) => root_element,
+            None => return Ok(vec![]),
+        };
+
+        let (parent_element, mut parent_level, parent_rect, mut parent_tree_token) =
+            match self.get_element_from_cache(mouse_x, mouse_y) {
+                Some(element) => element,
+                None => {
+                    let root_rect = ElementRect {
+                        min_x: 0,
+                        min_y: 0,
+                        max_x: i32::MAX,
+                        max_y: i32::MAX,
+                    };
+
+                    (
+                        root_element,
+                        ElementLevel::root(),
+                        root_rect,
+                        self.element_rect_tree.new_node(root_rect),
+                    )
+                }
+            };
+
+        // 父元素必然命中了 mouse position，所以直接取第一个元素
+        let mut current_level = ElementLevel::root();
+
+        let mut queue = Option::<AtspiElement>::None;
+
+        let mut try_get_first_child = false;
+        match self.element_children_next_sibling_cache.get(&parent_level) {
+            Some(element) => match element {
+                ElementChildrenNextSiblingCacheItem::Element(element, level) => {
+                    queue = Some(element.clone());
+                    current_level = *level;
+                }
+                // 叶子节点说明直接命中了，不需要重新获取
+                ElementChildrenNextSiblingCacheItem::Leaf => {}
+                // 没有下一个节点说明遍历结束了
+                ElementChildrenNextSiblingCacheItem::NoNext => {}
+            },
+            None => {
+                try_get_first_child = true;
+            }
+        };
+
+        if try_get_first_child {
+            // 没有命中缓存，说明是第一次获取
+            match self.get_first_child(&parent_element, parent_level) {
+                Some(element) => {
+                    queue = Some(element.clone());
+                    current_level = parent_level;
+                    current_level.next_level();
+
+                    self.element_children_next_sibling_cache.insert(
+                        parent_level,
+                        ElementChildrenNextSiblingCacheItem::Element(element, current_level),
+                    );
+                }
+                None => {
+                    self.element_children_next_sibling_cache
+                        .insert(parent_level, ElementChildrenNextSiblingCacheItem::Leaf);
+                }
+            }
+        }
+
+        let mut result_token = parent_tree_token;
+        let mut result_rect = parent_rect;
+
+        while let Some(current_element) = queue.take() {
+            // 不可见或没有区域的元素不加入缓存，直接跳到下一个兄弟节点
+            if let Some(current_element_rect) = self.get_visible_element_rect(&current_element) {
+                let (clipped_element_rect, current_element_token) = self.insert_element_cache(
+                    &mut parent_tree_token,
+                    current_element.clone(),
+                    current_element_rect,
+                    current_level,
+                );
+
+                if current_element_rect.min_x <= mouse_x
+                    && current_element_rect.max_x >= mouse_x
+                    && current_element_rect.min_y <= mouse_y
+                    && current_element_rect.max_y >= mouse_y
+                {
+                    result_token = current_element_token;
+                    result_rect = clipped_element_rect;
+
+                    let mut child_level = current_level;
+                    child_level.next_level();
+                    if let Some(child) = self.get_first_child(&current_element, current_level) {
+                        queue = Some(child.clone());
+                        parent_tree_token = current_element_token;
+                        parent_level = current_level;
+                        current_level = child_level;
+
+                        self.element_children_next_sibling_cache.insert(
+                            parent_level,
+                            ElementChildrenNextSiblingCacheItem::Element(child, current_level),
+                        );
+
+                        continue;
+                    } else {
+                        self.element_children_next_sibling_cache
+                            .insert(current_level, ElementChildrenNextSiblingCacheItem::Leaf);
+                    }
+                }
+            }
+
+            match self.get_next_sibling(parent_level, current_level) {
+                Some(sibling) => {
+                    queue = Some(sibling.clone());
+                    current_level.next_element();
+
+                    self.element_children_next_sibling_cache.insert(
+                        parent_level,
+                        ElementChildrenNextSiblingCacheItem::Element(sibling, current_level),
+                    );
+                }
+                None => {
+                    // 如果当前层级遍历结束了，标记已经遍历结束
+                    self.element_children_next_sibling_cache
+                        .insert(parent_level, ElementChildrenNextSiblingCacheItem::NoNext);
+                }
+            }
+        }
+
+        let element_ancestors = result_token.ancestors(&self.element_rect_tree);
+        let mut result_rect_list = Vec::with_capacity(16);
+        let mut previous_rect = result_rect;
+        result_rect_list.push(previous_rect);
+        for node in element_ancestors {
+            let current_rect = node.data;
+            if current_rect == previous_rect {
+                continue;
+            }
+
+            if current_rect.min_x == previous_rect.max_x
+                || current_rect.min_y == previous_rect.max_y
+                || current_rect.min_x > previous_rect.max_x
+                || current_rect.min_y > previous_rect.max_y
+            {
+                continue;
+            }
+
+            result_rect_list.push(current_rect);
+            previous_rect = current_rect;
+        }
+
+        Ok(result_rect_list)
+    }
+}
+
+impl Drop for UIElements {
+    fn drop(&mut self) {
+        self.restore_accessibility();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::OwnedObjectPath;
+
+    struct MockA11yBus {
+        address: String,
+    }
+
+    #[zbus::interface(name = "org.a11y.Bus")]
+    impl MockA11yBus {
+        fn get_address(&self) -> String {
+            self.address.clone()
+        }
+    }
+
+    struct MockA11yStatus {
+        is_enabled: bool,
+    }
+
+    #[zbus::interface(name = "org.a11y.Status")]
+    impl MockA11yStatus {
+        #[zbus(property)]
+        fn is_enabled(&self) -> bool {
+            self.is_enabled
+        }
+
+        #[zbus(property)]
+        fn set_is_enabled(&mut self, is_enabled: bool) {
+            self.is_enabled = is_enabled;
+        }
+    }
+
+    struct MockAccessible {
+        name: String,
+        children: Vec<&'static str>,
+        is_showing: bool,
+    }
+
+    #[zbus::interface(name = "org.a11y.atspi.Accessible")]
+    impl MockAccessible {
+        fn get_children(
+            &self,
+            #[zbus(connection)] connection: &zbus::Connection,
+        ) -> Vec<(String, OwnedObjectPath)> {
+            let bus_name = connection.unique_name().unwrap().to_string();
+
+            self.children
+                .iter()
+                .map(|path| (bus_name.clone(), OwnedObjectPath::try_from(*path).unwrap()))
+                .collect()
+        }
+
+        fn get_state(&self) -> Vec<u32> {
+            if self.is_showing {
+                vec![1 << AtspiState::Showing as u32, 0]
+            } else {
+                vec![0, 0]
+            }
+        }
+
+        #[zbus(property)]
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+    }
+
+    struct MockComponent {
+        extents: (i32, i32, i32, i32),
+    }
+
+    #[zbus::interface(name = "org.a11y.atspi.Component")]
+    impl MockComponent {
+        fn get_extents(&self, _coord_type: u32) -> (i32, i32, i32, i32) {
+            self.extents
+        }
+    }
+
+    /// 模拟的辅助功能总线即会话总线本身，总线上已有辅助功能服务时失败
+    #[test]
+    #[ignore = "需要独立的会话总线，使用 dbus-run-session cargo test -- --ignored 运行"]
+    fn test_get_element_from_point_walker() {
+        let address =
+            std::env::var("DBUS_SESSION_BUS_ADDRESS").expect("Session bus is not available");
+
+        // (路径, 名称, 子节点, 是否可见, 区域)
+        let element_list = [
+            (
+                "/org/a11y/atspi/accessible/root",
+                "",
+                vec!["/app"],
+                true,
+                None,
+            ),
+            ("/app", "app", vec!["/app/frame"], true, None),
+            (
+                "/app/frame",
+                "frame",
+                vec!["/app/frame/header", "/app/frame/content"],
+                true,
+                Some((0, 0, 400, 300)),
+            ),
+            (
+                "/app/frame/header",
+                "header",
+                vec!["/app/frame/header/menu", "/app/frame/header/button"],
+                true,
+                Some((0, 0, 400, 40)),
+            ),
+            // 不可见的元素覆盖了按钮，需要跳过
+            (
+                "/app/frame/header/menu",
+                "menu",
+                vec![],
+                false,
+                Some((0, 0, 200, 200)),
+            ),
+            (
+                "/app/frame/header/button",
+                "button",
+                vec![],
+                true,
+                Some((10, 10, 60, 20)),
+            ),
+            (
+                "/app/frame/content",
+                "content",
+                vec![],
+                true,
+                Some((0, 40, 400, 260)),
+            ),
+        ];
+
+        let mut builder = zbus::blocking::connection::Builder::session()
+            .and_then(|builder| builder.name("org.a11y.Bus"))
+            .and_then(|builder| builder.name("org.a11y.atspi.Registry"))
+            .and_then(|builder| builder.serve_at("/org/a11y/bus", MockA11yBus { address }))
+            .and_then(|builder| {
+                builder.serve_at("/org/a11y/bus", MockA11yStatus { is_enabled: false })
+            })
+            .unwrap();
+        for (path, name, children, is_showing, extents) in element_list {
+            builder = builder
+                .serve_at(
+                    path,
+                    MockAccessible {
+                        name: name.to_string(),
+                        children,
+                        is_showing,
+                    },
+                )
+                .unwrap();
+            if let Some(extents) = extents {
+                builder = builder.serve_at(path, MockComponent { extents }).unwrap();
+            }
+        }
+        let _connection = builder
+            .build()
+            .expect("Mock accessibility bus is not available");
+
+        let mut ui_elements = UIElements::new();
+        ui_elements.init_cache().unwrap();
+
+        let rect_list = ui_elements.get_element_from_point_walker(20, 20).unwrap();
+        assert!(rect_list[0].equals(10, 10, 70, 30));
+        assert!(rect_list[1].equals(0, 0, 400, 40));
+        assert!(rect_list[2].equals(0, 0, 400, 300));
+
+        let rect_list = ui_elements.get_element_from_point_walker(200, 200).unwrap();
+        assert!(rect_list[0].equals(0, 40, 400, 300));
+        assert!(rect_list[1].equals(0, 0, 400, 300));
+
+        // 命中缓存
+        let rect_list = ui_elements.get_element_from_point_walker(20, 20).unwrap();
+        assert!(rect_list[0].equals(10, 10, 70, 30));
+        assert!(rect_list[1].equals(0, 0, 400, 40));
+
+        // 释放后恢复为开启前的状态
+        ui_elements.init().unwrap();
+        assert!(crate::atspi::is_accessibility_enabled().unwrap());
+        drop(ui_elements);
+        assert!(!crate::atspi::is_accessibility_enabled().unwrap());
     }
 }
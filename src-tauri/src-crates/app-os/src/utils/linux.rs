@@ -1,17 +1,81 @@
-pub fn get_focused_window() -> () {
-    log::warn!("[os::utils::linux::get_focused_window] not implemented");
+use crate::x11::{X11Connection, X11WindowInfo};
 
-    ()
+/**
+ * 获取所有可见的顶层窗口，按层级从高到低排列
+ */
+pub fn get_window_list() -> Vec<X11WindowInfo> {
+    let window_list = X11Connection::new().and_then(|x11| x11.get_window_list());
+
+    match window_list {
+        Ok(window_list) => window_list
+            .into_iter()
+            .filter(|window| !window.is_hidden && !window.is_desktop)
+            .collect(),
+        Err(e) => {
+            log::warn!(
+                "[os::utils::linux::get_window_list] Failed to get window list: {:?}",
+                e
+            );
+
+            Vec::new()
+        }
+    }
 }
 
-pub fn switch_always_on_top() -> () {
-    log::warn!("[os::utils::linux::switch_always_on_top] not implemented");
+/**
+ * 通过 _NET_ACTIVE_WINDOW 获取当前活动窗口
+ */
+pub fn get_focused_window() -> Option<X11WindowInfo> {
+    let x11 = match X11Connection::new() {
+        Ok(x11) => x11,
+        Err(e) => {
+            log::warn!(
+                "[os::utils::linux::get_focused_window] Failed to connect X server: {:?}",
+                e
+            );
+            return None;
+        }
+    };
 
-    ()
+    match x11
+        .get_active_window()
+        .and_then(|window| window.map(|window| x11.get_window_info(window)).transpose())
+    {
+        Ok(window_info) => window_info,
+        Err(e) => {
+            log::warn!(
+                "[os::utils::linux::get_focused_window] Failed to get active window: {:?}",
+                e
+            );
+            None
+        }
+    }
 }
 
-pub fn set_draw_window_style(window: tauri::Window) {
+/**
+ * 切换窗口置顶状态，返回切换后窗口是否置顶
+ */
+pub fn switch_always_on_top(window_id: u32) -> bool {
+    let result = X11Connection::new().and_then(|x11| {
+        let is_always_on_top = x11.is_always_on_top(window_id)?;
+        x11.set_always_on_top(window_id, !is_always_on_top)
+    });
+
+    match result {
+        Ok(is_always_on_top) => is_always_on_top,
+        Err(e) => {
+            log::warn!(
+                "[os::utils::linux::switch_always_on_top] Failed to switch always on top: {} {:?}",
+                window_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+pub fn set_draw_window_style(#[allow(unused_variables)] window: tauri::Window) {
     log::warn!("[os::utils::linux::set_draw_window_style] not implemented");
 
     ()
-}
\ No newline at end of file
+}
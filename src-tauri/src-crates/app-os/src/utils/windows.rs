@@ -7,6 +7,9 @@ use windows::Win32::UI::WindowsAndMessaging::{
     SetWindowPos, WS_EX_TOPMOST,
 };
 
+/**
+ * 切换窗口置顶状态，返回切换后窗口是否置顶
+ */
 pub fn switch_always_on_top(hwnd: *mut c_void) -> bool {
     let hwnd = HWND(hwnd);
 
@@ -33,7 +36,12 @@ pub fn switch_always_on_top(hwnd: *mut c_void) -> bool {
         )
     };
 
-    result.is_ok()
+    // 切换失败时保持原状态
+    if result.is_ok() {
+        !is_topmost
+    } else {
+        is_topmost
+    }
 }
 
 pub fn set_draw_window_style(#[allow(unused_variables)] window: tauri::Window) {
@@ -0,0 +1,493 @@
+use std::time::{Duration, Instant};
+
+use snow_shot_app_shared::ElementRect;
+use thiserror::Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, MapState, Window,
+};
+use x11rb::rust_connection::RustConnection;
+
+/// _NET_WM_STATE 客户端消息的操作类型
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+/// 来源标记为用户直接操作，窗口管理器不会因为焦点策略忽略请求
+const NET_WM_SOURCE_PAGER: u32 = 2;
+/// 等待窗口管理器更新 _NET_WM_STATE 的最长时间
+const NET_WM_STATE_TIMEOUT: Duration = Duration::from_millis(500);
+
+x11rb::atom_manager! {
+    pub X11Atoms: X11AtomsCookie {
+        UTF8_STRING,
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLIENT_LIST_STACKING,
+        _NET_FRAME_EXTENTS,
+        _NET_WM_NAME,
+        _NET_WM_STATE,
+        _NET_WM_STATE_ABOVE,
+        _NET_WM_STATE_HIDDEN,
+        _NET_WM_WINDOW_TYPE,
+        _NET_WM_WINDOW_TYPE_DESKTOP,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum X11Error {
+    #[error("X11 connect error")]
+    Connect(#[from] x11rb::errors::ConnectError),
+    #[error("X11 connection error")]
+    Connection(#[from] x11rb::errors::ConnectionError),
+    #[error("X11 reply error")]
+    Reply(#[from] x11rb::errors::ReplyError),
+}
+
+/// 顶层窗口信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct X11WindowInfo {
+    /// X11 窗口 ID，即客户端窗口的 ID
+    pub window_id: u32,
+    pub title: String,
+    /// 包含窗口管理器边框的窗口区域（物理像素）
+    pub rect: ElementRect,
+    /// 窗口已最小化或不在当前工作区
+    pub is_hidden: bool,
+    /// 桌面窗口，覆盖整个屏幕
+    pub is_desktop: bool,
+}
+
+pub struct X11Connection {
+    connection: RustConnection,
+    root: Window,
+    atoms: X11Atoms,
+}
+
+impl X11Connection {
+    /**
+     * 连接 DISPLAY 环境变量指定的 X 服务器
+     */
+    pub fn new() -> Result<Self, X11Error> {
+        let (connection, screen_num) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen_num].root;
+        let atoms = X11Atoms::new(&connection)?.reply()?;
+
+        Ok(Self {
+            connection,
+            root,
+            atoms,
+        })
+    }
+
+    pub fn connection(&self) -> &RustConnection {
+        &self.connection
+    }
+
+    pub fn root(&self) -> Window {
+        self.root
+    }
+
+    pub fn atoms(&self) -> &X11Atoms {
+        &self.atoms
+    }
+
+    fn get_property_u32_list(
+        &self,
+        window: Window,
+        property: u32,
+        property_type: AtomEnum,
+    ) -> Result<Vec<u32>, X11Error> {
+        let reply = self
+            .connection
+            .get_property(false, window, property, property_type, 0, u32::MAX)?
+            .reply()?;
+
+        Ok(reply
+            .value32()
+            .map(|value| value.collect())
+            .unwrap_or_default())
+    }
+
+    /**
+     * 获取窗口管理器记录的顶层窗口，按层级从高到低排列
+     * _NET_CLIENT_LIST_STACKING 按从低到高排列，窗口管理器不支持时使用 _NET_CLIENT_LIST
+     */
+    pub fn get_client_list(&self) -> Result<Vec<Window>, X11Error> {
+        let mut window_list = self.get_property_u32_list(
+            self.root,
+            self.atoms._NET_CLIENT_LIST_STACKING,
+            AtomEnum::WINDOW,
+        )?;
+        if window_list.is_empty() {
+            window_list = self.get_property_u32_list(
+                self.root,
+                self.atoms._NET_CLIENT_LIST,
+                AtomEnum::WINDOW,
+            )?;
+        }
+
+        window_list.reverse();
+        Ok(window_list)
+    }
+
+    /**
+     * 获取 _NET_ACTIVE_WINDOW，没有活动窗口时返回 None
+     */
+    pub fn get_active_window(&self) -> Result<Option<Window>, X11Error> {
+        let window_list =
+            self.get_property_u32_list(self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW)?;
+
+        Ok(window_list.first().copied().filter(|window| *window != 0))
+    }
+
+    fn get_window_title(&self, window: Window) -> Result<String, X11Error> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_NAME,
+                self.atoms.UTF8_STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+        if !reply.value.is_empty() {
+            return Ok(String::from_utf8_lossy(&reply.value).into_owned());
+        }
+
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_NAME,
+                AtomEnum::STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        Ok(String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    /**
+     * 获取窗口区域，加上 _NET_FRAME_EXTENTS 记录的边框
+     * 窗口被重新设置父窗口后，几何位置相对父窗口，需要转换到根窗口坐标
+     */
+    pub fn get_window_rect(&self, window: Window) -> Result<ElementRect, X11Error> {
+        let geometry = self.connection.get_geometry(window)?.reply()?;
+        let position = self
+            .connection
+            .translate_coordinates(window, self.root, 0, 0)?
+            .reply()?;
+
+        let frame_extents =
+            self.get_property_u32_list(window, self.atoms._NET_FRAME_EXTENTS, AtomEnum::CARDINAL)?;
+        let (left, right, top, bottom) = match frame_extents.as_slice() {
+            [left, right, top, bottom, ..] => {
+                (*left as i32, *right as i32, *top as i32, *bottom as i32)
+            }
+            _ => (0, 0, 0, 0),
+        };
+
+        let x = position.dst_x as i32;
+        let y = position.dst_y as i32;
+        Ok(ElementRect {
+            min_x: x - left,
+            min_y: y - top,
+            max_x: x + geometry.width as i32 + right,
+            max_y: y + geometry.height as i32 + bottom,
+        })
+    }
+
+    pub fn get_window_info(&self, window: Window) -> Result<X11WindowInfo, X11Error> {
+        let attributes = self.connection.get_window_attributes(window)?.reply()?;
+        let state_list =
+            self.get_property_u32_list(window, self.atoms._NET_WM_STATE, AtomEnum::ATOM)?;
+        let window_type_list =
+            self.get_property_u32_list(window, self.atoms._NET_WM_WINDOW_TYPE, AtomEnum::ATOM)?;
+
+        Ok(X11WindowInfo {
+            window_id: window,
+            title: self.get_window_title(window)?,
+            rect: self.get_window_rect(window)?,
+            is_hidden: attributes.map_state != MapState::VIEWABLE
+                || state_list.contains(&self.atoms._NET_WM_STATE_HIDDEN),
+            is_desktop: window_type_list.contains(&self.atoms._NET_WM_WINDOW_TYPE_DESKTOP),
+        })
+    }
+
+    /**
+     * 窗口的 _NET_WM_STATE 是否包含 _NET_WM_STATE_ABOVE
+     */
+    pub fn is_always_on_top(&self, window: Window) -> Result<bool, X11Error> {
+        let state_list =
+            self.get_property_u32_list(window, self.atoms._NET_WM_STATE, AtomEnum::ATOM)?;
+
+        Ok(state_list.contains(&self.atoms._NET_WM_STATE_ABOVE))
+    }
+
+    /**
+     * 向根窗口发送 _NET_WM_STATE 客户端消息，由窗口管理器设置或取消置顶
+     * 窗口管理器异步处理请求，等待 _NET_WM_STATE 更新后返回窗口当前的置顶状态
+     */
+    pub fn set_always_on_top(&self, window: Window, always_on_top: bool) -> Result<bool, X11Error> {
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            self.atoms._NET_WM_STATE,
+            [
+                if always_on_top {
+                    NET_WM_STATE_ADD
+                } else {
+                    NET_WM_STATE_REMOVE
+                },
+                self.atoms._NET_WM_STATE_ABOVE,
+                0,
+                NET_WM_SOURCE_PAGER,
+                0,
+            ],
+        );
+        self.connection.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        self.connection.flush()?;
+
+        let start_time = Instant::now();
+        loop {
+            let is_always_on_top = self.is_always_on_top(window)?;
+            if is_always_on_top == always_on_top || start_time.elapsed() > NET_WM_STATE_TIMEOUT {
+                return Ok(is_always_on_top);
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /**
+     * 获取所有顶层窗口的信息，按层级从高到低排列
+     * 获取过程中被关闭的窗口会被跳过
+     */
+    pub fn get_window_list(&self) -> Result<Vec<X11WindowInfo>, X11Error> {
+        let window_list = self.get_client_list()?;
+
+        let mut window_info_list = Vec::with_capacity(window_list.len());
+        for window in window_list {
+            match self.get_window_info(window) {
+                Ok(window_info) => window_info_list.push(window_info),
+                Err(X11Error::Reply(e)) => {
+                    log::warn!(
+                        "[X11Connection::get_window_list] Failed to get window info: {} {:?}",
+                        window,
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(window_info_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use x11rb::COPY_DEPTH_FROM_PARENT;
+    use x11rb::protocol::Event;
+    use x11rb::protocol::xproto::{
+        ChangeWindowAttributesAux, CreateWindowAux, PropMode, WindowClass,
+    };
+    use x11rb::wrapper::ConnectionExt as _;
+
+    /// 测试会代替窗口管理器修改根窗口，需要依次运行
+    static X11_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    /**
+     * 连接 X 服务器，并在另一个连接上选择根窗口的 SubstructureRedirect 代替窗口管理器
+     * 已有窗口管理器时测试会修改它管理的根窗口属性，直接失败
+     */
+    fn connect_as_window_manager() -> (X11Connection, X11Connection) {
+        let x11 = X11Connection::new().expect("X server is not available");
+        let window_manager = X11Connection::new().unwrap();
+        window_manager
+            .connection()
+            .change_window_attributes(
+                window_manager.root(),
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::SUBSTRUCTURE_REDIRECT),
+            )
+            .unwrap()
+            .check()
+            .expect("Window manager is running");
+
+        (x11, window_manager)
+    }
+
+    /// 由测试代替窗口管理器设置根窗口属性
+    #[test]
+    #[ignore = "需要没有窗口管理器的 X 服务器，使用 xvfb-run cargo test -- --ignored 运行"]
+    fn test_get_window_list() {
+        let _lock = X11_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (x11, _window_manager) = connect_as_window_manager();
+        let connection = x11.connection();
+        let atoms = *x11.atoms();
+
+        let create_window = |x: i16, y: i16, width: u16, height: u16, title: &str| -> Window {
+            let window = connection.generate_id().unwrap();
+            connection
+                .create_window(
+                    COPY_DEPTH_FROM_PARENT,
+                    window,
+                    x11.root(),
+                    x,
+                    y,
+                    width,
+                    height,
+                    0,
+                    WindowClass::INPUT_OUTPUT,
+                    0,
+                    &CreateWindowAux::new(),
+                )
+                .unwrap();
+            connection
+                .change_property8(
+                    PropMode::REPLACE,
+                    window,
+                    atoms._NET_WM_NAME,
+                    atoms.UTF8_STRING,
+                    title.as_bytes(),
+                )
+                .unwrap();
+            connection.map_window(window).unwrap();
+
+            window
+        };
+
+        let bottom_window = create_window(10, 20, 300, 200, "底层窗口");
+        let top_window = create_window(100, 120, 200, 100, "top");
+        connection
+            .change_property32(
+                PropMode::REPLACE,
+                top_window,
+                atoms._NET_FRAME_EXTENTS,
+                AtomEnum::CARDINAL,
+                &[2, 3, 24, 4],
+            )
+            .unwrap();
+        connection
+            .change_property32(
+                PropMode::REPLACE,
+                x11.root(),
+                atoms._NET_CLIENT_LIST_STACKING,
+                AtomEnum::WINDOW,
+                &[bottom_window, top_window],
+            )
+            .unwrap();
+        connection
+            .change_property32(
+                PropMode::REPLACE,
+                x11.root(),
+                atoms._NET_ACTIVE_WINDOW,
+                AtomEnum::WINDOW,
+                &[top_window],
+            )
+            .unwrap();
+        connection.sync().unwrap();
+
+        let window_list = x11.get_window_list().unwrap();
+        assert_eq!(window_list.len(), 2);
+
+        assert_eq!(window_list[0].window_id, top_window);
+        assert_eq!(window_list[0].title, "top");
+        assert!(window_list[0].rect.equals(98, 96, 303, 224));
+
+        assert_eq!(window_list[1].window_id, bottom_window);
+        assert_eq!(window_list[1].title, "底层窗口");
+        assert!(window_list[1].rect.equals(10, 20, 310, 220));
+        assert!(!window_list[1].is_hidden);
+
+        assert_eq!(x11.get_active_window().unwrap(), Some(top_window));
+
+        connection.destroy_window(top_window).unwrap();
+        connection.destroy_window(bottom_window).unwrap();
+        connection
+            .delete_property(x11.root(), atoms._NET_CLIENT_LIST_STACKING)
+            .unwrap();
+        connection
+            .delete_property(x11.root(), atoms._NET_ACTIVE_WINDOW)
+            .unwrap();
+        connection.sync().unwrap();
+    }
+
+    /// 由测试线程代替窗口管理器处理 _NET_WM_STATE 客户端消息
+    #[test]
+    #[ignore = "需要没有窗口管理器的 X 服务器，使用 xvfb-run cargo test -- --ignored 运行"]
+    fn test_set_always_on_top() {
+        let _lock = X11_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (x11, window_manager) = connect_as_window_manager();
+
+        let connection = x11.connection();
+        let window = connection.generate_id().unwrap();
+        connection
+            .create_window(
+                COPY_DEPTH_FROM_PARENT,
+                window,
+                x11.root(),
+                0,
+                0,
+                100,
+                100,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                0,
+                &CreateWindowAux::new(),
+            )
+            .unwrap();
+        connection.sync().unwrap();
+
+        let window_manager_thread = std::thread::spawn(move || {
+            let atoms = *window_manager.atoms();
+            for _ in 0..2 {
+                let event = loop {
+                    if let Event::ClientMessage(event) =
+                        window_manager.connection().wait_for_event().unwrap()
+                    {
+                        break event;
+                    }
+                };
+                let [action, property, ..] = event.data.as_data32();
+                assert_eq!(event.type_, atoms._NET_WM_STATE);
+                assert_eq!(property, atoms._NET_WM_STATE_ABOVE);
+
+                let state_list: &[u32] = if action == NET_WM_STATE_ADD {
+                    &[atoms._NET_WM_STATE_ABOVE]
+                } else {
+                    &[]
+                };
+                window_manager
+                    .connection()
+                    .change_property32(
+                        PropMode::REPLACE,
+                        event.window,
+                        atoms._NET_WM_STATE,
+                        AtomEnum::ATOM,
+                        state_list,
+                    )
+                    .unwrap();
+                window_manager.connection().sync().unwrap();
+            }
+        });
+
+        assert!(!x11.is_always_on_top(window).unwrap());
+        assert!(x11.set_always_on_top(window, true).unwrap());
+        assert!(!x11.set_always_on_top(window, false).unwrap());
+        window_manager_thread.join().unwrap();
+
+        connection.destroy_window(window).unwrap();
+        connection.sync().unwrap();
+    }
+}
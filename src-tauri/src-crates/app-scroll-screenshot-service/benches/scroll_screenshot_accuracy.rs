@@ -0,0 +1,114 @@
+//! 用合成的滚动序列比较不同拼接参数的准确度和耗时
+//!
+//! 用法：cargo bench -p snow-shot-app-scroll-screenshot-service --features benchmark --bench scroll_screenshot_accuracy
+
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_benchmark::{
+    ScrollAccuracyReport, SyntheticPageOptions, SyntheticScrollOptions, create_synthetic_sequence,
+    evaluate_scroll_accuracy,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_matcher::ScrollMatcherType;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_stitch_service::ScrollStitchOptions;
+
+const CORNER_THRESHOLD_LIST: [u8; 3] = [12, 24, 48];
+const DESCRIPTOR_PATCH_SIZE_LIST: [usize; 3] = [16, 28, 40];
+const SAMPLE_RATE_LIST: [f32; 2] = [0.5, 1.0];
+const MATCHER_TYPE_LIST: [ScrollMatcherType; 3] = [
+    ScrollMatcherType::Corner,
+    ScrollMatcherType::PhaseCorrelation,
+    ScrollMatcherType::RowHash,
+];
+
+fn print_report(name: &str, report: &Result<ScrollAccuracyReport, String>) {
+    match report {
+        Ok(report) => println!(
+            "{:<40} {:>4}/{:<4} {:>9} {:>8.2} {:>8} {:>6} {:>9.3} {:>10.4} {:>8}",
+            name,
+            report.frame_count - report.not_matched_count,
+            report.frame_count,
+            report.max_offset_error,
+            report.mean_offset_error,
+            report.width_error,
+            report.height_error,
+            report.mean_pixel_diff,
+            report.mismatched_pixel_ratio,
+            report.elapsed.as_millis(),
+        ),
+        Err(e) => println!("{:<40} {}", name, e),
+    }
+}
+
+fn main() {
+    // 无噪声、带吸顶导航栏、带噪声三种场景
+    let scenario_list = [
+        ("plain", 0, 0),
+        ("sticky header", 48, 0),
+        ("sticky header + noise", 48, 4),
+    ];
+
+    for (scenario_name, sticky_header_size, noise_amplitude) in scenario_list {
+        let sequence = match create_synthetic_sequence(
+            &SyntheticPageOptions {
+                sticky_header_size,
+                ..Default::default()
+            },
+            &SyntheticScrollOptions {
+                noise_amplitude,
+                ..Default::default()
+            },
+        ) {
+            Ok(sequence) => sequence,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+
+        println!();
+        println!("scenario: {}", scenario_name);
+        println!(
+            "{:<40} {:>9} {:>9} {:>8} {:>8} {:>6} {:>9} {:>10} {:>8}",
+            "options",
+            "matched",
+            "max err",
+            "mean err",
+            "width",
+            "height",
+            "pixel",
+            "mismatch",
+            "ms",
+        );
+
+        for corner_threshold in CORNER_THRESHOLD_LIST {
+            for descriptor_patch_size in DESCRIPTOR_PATCH_SIZE_LIST {
+                for sample_rate in SAMPLE_RATE_LIST {
+                    let options = ScrollStitchOptions {
+                        corner_threshold,
+                        descriptor_patch_size,
+                        sample_rate,
+                        ..Default::default()
+                    };
+
+                    print_report(
+                        &format!(
+                            "corner {} patch {} sample {}",
+                            corner_threshold, descriptor_patch_size, sample_rate
+                        ),
+                        &evaluate_scroll_accuracy(&sequence, &options),
+                    );
+                }
+            }
+        }
+
+        for matcher_type in MATCHER_TYPE_LIST {
+            let options = ScrollStitchOptions {
+                matcher_type,
+                ..Default::default()
+            };
+
+            print_report(
+                &format!("matcher {:?}", matcher_type),
+                &evaluate_scroll_accuracy(&sequence, &options),
+            );
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! 将已截取的帧序列离线拼接为长截图
+//!
+//! 用法：scroll_screenshot_stitch [--horizontal] [--top] [--no-rollback] -o <output> <frame>...
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_matcher::ScrollMatcherType;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_seam::ScrollSeamMode;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_service::{
+    ScrollDirection, ScrollImageList, ScrollScreenshotService,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_stitch_service::{
+    ScrollStitchFrameStatus, ScrollStitchOptions,
+};
+
+const USAGE: &str = "Usage: scroll_screenshot_stitch [--horizontal] [--top] [--no-rollback] [--corner-threshold <n>] [--descriptor-patch-size <n>] [--cross-axis-tolerance <n>] [--seam <overwrite|feather|min-difference>] [--matcher <corner|phase|row-hash>] -o <output> <frame>...";
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    match value.map(|value| value.parse::<T>()) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(format!("Invalid value for {}", name)),
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut options = ScrollStitchOptions::default();
+    let mut output_path: Option<PathBuf> = None;
+    let mut frame_paths: Vec<PathBuf> = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--horizontal" => options.direction = ScrollDirection::Horizontal,
+            "--top" => options.scroll_image_list = ScrollImageList::Top,
+            "--no-rollback" => options.try_rollback = false,
+            "--corner-threshold" => {
+                options.corner_threshold = parse_value(&arg, args.next())?;
+            }
+            "--descriptor-patch-size" => {
+                options.descriptor_patch_size = parse_value(&arg, args.next())?;
+            }
+            "--cross-axis-tolerance" => {
+                options.cross_axis_tolerance = parse_value(&arg, args.next())?;
+            }
+            "--seam" => {
+                options.seam_mode = match args.next().as_deref() {
+                    Some("overwrite") => ScrollSeamMode::Overwrite,
+                    Some("feather") => ScrollSeamMode::Feather,
+                    Some("min-difference") => ScrollSeamMode::MinDifference,
+                    _ => return Err(format!("Invalid value for {}", arg)),
+                };
+            }
+            "--matcher" => {
+                options.matcher_type = match args.next().as_deref() {
+                    Some("corner") => ScrollMatcherType::Corner,
+                    Some("phase") => ScrollMatcherType::PhaseCorrelation,
+                    Some("row-hash") => ScrollMatcherType::RowHash,
+                    _ => return Err(format!("Invalid value for {}", arg)),
+                };
+            }
+            "-o" | "--output" => {
+                output_path = Some(parse_value(&arg, args.next())?);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => frame_paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let output_path = match output_path {
+        Some(output_path) => output_path,
+        None => return Err(String::from(USAGE)),
+    };
+
+    let mut scroll_screenshot_service = ScrollScreenshotService::new();
+    let result = scroll_screenshot_service.stitch_files(&options, &frame_paths)?;
+
+    println!(
+        "frames: {}, appended: {}, unchanged: {}, not matched: {}",
+        result.frame_status_list.len(),
+        result.count(ScrollStitchFrameStatus::Appended),
+        result.count(ScrollStitchFrameStatus::Unchanged),
+        result.count(ScrollStitchFrameStatus::NotMatched),
+    );
+
+    result
+        .image
+        .save(&output_path)
+        .map_err(|e| format!("Failed to save image: {} {}", e, output_path.display()))?;
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
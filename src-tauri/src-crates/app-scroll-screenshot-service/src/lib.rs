@@ -1,3 +1,25 @@
+pub mod scroll_screenshot_area_detection;
+pub mod scroll_screenshot_auto_scroll_service;
+#[cfg(any(test, feature = "benchmark"))]
+pub mod scroll_screenshot_benchmark;
 pub mod scroll_screenshot_capture_service;
+pub mod scroll_screenshot_corner_matcher;
+pub mod scroll_screenshot_dynamic_mask;
+pub mod scroll_screenshot_export;
+pub mod scroll_screenshot_frame_store;
 pub mod scroll_screenshot_image_service;
-pub mod scroll_screenshot_service;
\ No newline at end of file
+pub mod scroll_screenshot_matcher;
+pub mod scroll_screenshot_panorama_service;
+pub mod scroll_screenshot_pdf;
+pub mod scroll_screenshot_phase_matcher;
+pub mod scroll_screenshot_row_hash_matcher;
+pub mod scroll_screenshot_scrollbar_band;
+pub mod scroll_screenshot_seam;
+pub mod scroll_screenshot_service;
+pub mod scroll_screenshot_session;
+pub mod scroll_screenshot_stitch_service;
+pub mod scroll_screenshot_sticky_band;
+#[cfg(test)]
+mod scroll_screenshot_test_utils;
+pub mod scroll_screenshot_video;
+pub mod scroll_screenshot_worker_service;
\ No newline at end of file
@@ -0,0 +1,321 @@
+use image::{DynamicImage, GrayImage};
+use serde::Serialize;
+use snow_shot_app_shared::ElementRect;
+
+use crate::scroll_screenshot_service::ScrollDirection;
+
+/// 像素差超过该值时认为像素发生了变化
+const PIXEL_DIFF_THRESHOLD: u8 = 16;
+/// 行（列）内对比度不超过该值时认为是留白，无法判断是否移动
+const FLAT_LINE_CONTRAST: u8 = 24;
+/// 行（列）内变化像素的比例不低于该值时认为发生了移动
+const MIN_MOVED_PIXEL_RATIO: f32 = 0.02;
+/// 滚动区域在各方向上的最小尺寸
+const MIN_AREA_SIZE: u32 = 32;
+/// 估计偏移时每行（列）采样的像素数
+const SIGNATURE_SAMPLE_COUNT: u32 = 64;
+/// 估计偏移时前后两帧至少重叠的比例
+const MIN_OVERLAP_RATIO: f32 = 0.25;
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub struct ScrollAreaDetection {
+    /// 滚动区域，坐标相对截图
+    pub rect: ElementRect,
+    /// 滚动方向
+    pub direction: ScrollDirection,
+    /// 滚动后内容在滚动方向上移动的像素，向前滚动时为正
+    pub offset: i32,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum LineState {
+    /// 内容发生了移动
+    Moved,
+    /// 有内容但没有变化，如侧边栏
+    Static,
+    /// 留白
+    Flat,
+}
+
+/**
+ * 按变化情况划分每一行（列）
+ * 只统计 cross_range 范围内的像素，is_row 为 false 时按列划分
+ */
+fn get_line_state_list(
+    before_image: &GrayImage,
+    after_image: &GrayImage,
+    is_row: bool,
+    cross_range: (u32, u32),
+) -> Vec<LineState> {
+    let line_count = if is_row {
+        before_image.height()
+    } else {
+        before_image.width()
+    };
+
+    (0..line_count)
+        .map(|line| {
+            let mut min_pixel = u8::MAX;
+            let mut max_pixel = u8::MIN;
+            let mut moved_pixel_count = 0;
+            for cross in cross_range.0..cross_range.1 {
+                let (x, y) = if is_row { (cross, line) } else { (line, cross) };
+                let before_pixel = before_image.get_pixel(x, y)[0];
+                let after_pixel = after_image.get_pixel(x, y)[0];
+
+                min_pixel = min_pixel.min(before_pixel).min(after_pixel);
+                max_pixel = max_pixel.max(before_pixel).max(after_pixel);
+                if before_pixel.abs_diff(after_pixel) > PIXEL_DIFF_THRESHOLD {
+                    moved_pixel_count += 1;
+                }
+            }
+
+            let cross_size = (cross_range.1 - cross_range.0).max(1);
+            if moved_pixel_count as f32 >= cross_size as f32 * MIN_MOVED_PIXEL_RATIO {
+                LineState::Moved
+            } else if max_pixel - min_pixel > FLAT_LINE_CONTRAST {
+                LineState::Static
+            } else {
+                LineState::Flat
+            }
+        })
+        .collect()
+}
+
+/**
+ * 以有内容但没有变化的行（列）为边界分段，返回包含移动行（列）最多的一段 [start, end)
+ * 段两端的留白会被包含在内
+ */
+fn get_moved_range(line_state_list: &[LineState]) -> Option<(u32, u32)> {
+    let mut best_range = None;
+    let mut best_moved_count = 0;
+
+    let mut segment_start = 0;
+    let mut moved_count = 0;
+    for (line, state) in line_state_list
+        .iter()
+        .chain(std::iter::once(&LineState::Static))
+        .enumerate()
+    {
+        match state {
+            LineState::Moved => moved_count += 1,
+            LineState::Flat => {}
+            LineState::Static => {
+                if moved_count > best_moved_count {
+                    best_moved_count = moved_count;
+                    best_range = Some((segment_start as u32, line as u32));
+                }
+
+                segment_start = line + 1;
+                moved_count = 0;
+            }
+        }
+    }
+
+    best_range
+}
+
+/**
+ * 估计区域内的内容沿指定方向的偏移，返回偏移和平均差异
+ */
+fn estimate_offset(
+    before_image: &GrayImage,
+    after_image: &GrayImage,
+    rect: &ElementRect,
+    direction: ScrollDirection,
+) -> Option<(i32, f32)> {
+    let is_vertical = direction == ScrollDirection::Vertical;
+    let (line_start, line_end, cross_start, cross_end) = if is_vertical {
+        (rect.min_y, rect.max_y, rect.min_x, rect.max_x)
+    } else {
+        (rect.min_x, rect.max_x, rect.min_y, rect.max_y)
+    };
+    let line_count = (line_end - line_start) as usize;
+    let cross_size = (cross_end - cross_start) as u32;
+    let sample_count = SIGNATURE_SAMPLE_COUNT.min(cross_size).max(1);
+
+    // 每行（列）等间隔采样的像素作为签名
+    let get_signature_list = |image: &GrayImage| -> Vec<Vec<u8>> {
+        (line_start..line_end)
+            .map(|line| {
+                (0..sample_count)
+                    .map(|sample| {
+                        let cross = cross_start as u32 + sample * cross_size / sample_count;
+                        if is_vertical {
+                            image.get_pixel(cross, line as u32)[0]
+                        } else {
+                            image.get_pixel(line as u32, cross)[0]
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    let before_signature_list = get_signature_list(before_image);
+    let after_signature_list = get_signature_list(after_image);
+
+    let min_overlap = ((line_count as f32 * MIN_OVERLAP_RATIO) as usize).max(1);
+    let max_offset = line_count.saturating_sub(min_overlap) as i32;
+
+    let mut best_offset: Option<(i32, f32)> = None;
+    for offset in -max_offset..=max_offset {
+        // 滚动后第 line 行的内容对应滚动前的第 line + offset 行
+        let overlap_start = (-offset).max(0) as usize;
+        let overlap_end = line_count.min((line_count as i32 - offset) as usize);
+
+        let diff_sum: u64 = (overlap_start..overlap_end)
+            .map(|line| {
+                before_signature_list[(line as i32 + offset) as usize]
+                    .iter()
+                    .zip(after_signature_list[line].iter())
+                    .map(|(a, b)| a.abs_diff(*b) as u64)
+                    .sum::<u64>()
+            })
+            .sum();
+        let diff = diff_sum as f32 / ((overlap_end - overlap_start) * sample_count as usize) as f32;
+
+        // 差异相同时优先选择偏移较小的位置
+        let is_better = match best_offset {
+            None => true,
+            Some((best, best_diff)) => {
+                diff < best_diff || (diff == best_diff && offset.abs() < best.abs())
+            }
+        };
+        if is_better {
+            best_offset = Some((offset, diff));
+        }
+    }
+
+    best_offset
+}
+
+/**
+ * 比较滚动前后的两张截图，找出实际发生滚动的区域和方向
+ * 没有变化、变化区域太小或无法确定偏移时返回 None
+ */
+pub fn detect_scroll_area(
+    before_image: &DynamicImage,
+    after_image: &DynamicImage,
+) -> Option<ScrollAreaDetection> {
+    if before_image.width() != after_image.width() || before_image.height() != after_image.height()
+    {
+        return None;
+    }
+
+    let before_image = before_image.to_luma8();
+    let after_image = after_image.to_luma8();
+    let (width, height) = before_image.dimensions();
+
+    // 先确定移动的列，再在这些列内确定移动的行
+    let (min_x, max_x) = get_moved_range(&get_line_state_list(
+        &before_image,
+        &after_image,
+        false,
+        (0, height),
+    ))?;
+    let (min_y, max_y) = get_moved_range(&get_line_state_list(
+        &before_image,
+        &after_image,
+        true,
+        (min_x, max_x),
+    ))?;
+    if max_x - min_x < MIN_AREA_SIZE || max_y - min_y < MIN_AREA_SIZE {
+        return None;
+    }
+
+    // 列内重新确定一次，排除只有在其他行中变化的列
+    let (min_x, max_x) = get_moved_range(&get_line_state_list(
+        &before_image,
+        &after_image,
+        false,
+        (min_y, max_y),
+    ))
+    .map(|(start, end)| (start.max(min_x), end.min(max_x)))
+    .unwrap_or((min_x, max_x));
+    if max_x <= min_x || max_x > width || max_y > height {
+        return None;
+    }
+
+    let rect = ElementRect {
+        min_x: min_x as i32,
+        min_y: min_y as i32,
+        max_x: max_x as i32,
+        max_y: max_y as i32,
+    };
+
+    let (direction, offset) = [ScrollDirection::Vertical, ScrollDirection::Horizontal]
+        .into_iter()
+        .filter_map(|direction| {
+            estimate_offset(&before_image, &after_image, &rect, direction)
+                .filter(|(offset, _)| *offset != 0)
+                .map(|(offset, diff)| (direction, offset, diff))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(direction, offset, _)| (direction, offset))?;
+
+    Some(ScrollAreaDetection {
+        rect,
+        direction,
+        offset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, create_window};
+    use image::{Rgb, RgbImage};
+
+    /// 左侧为固定的侧边栏，右侧为滚动到 scroll_position 的页面
+    fn create_sidebar_window(page: &RgbImage, scroll_position: u32) -> DynamicImage {
+        let sidebar = create_page(120, 400);
+        let content_rect = ElementRect {
+            min_x: 124,
+            min_y: 0,
+            max_x: 500,
+            max_y: 400,
+        };
+
+        DynamicImage::ImageRgb8(create_window(
+            page,
+            scroll_position,
+            500,
+            400,
+            content_rect,
+            |x, y| {
+                if x < 120 {
+                    *sidebar.get_pixel(x, y)
+                } else {
+                    Rgb([80, 80, 80])
+                }
+            },
+        ))
+    }
+
+    #[test]
+    fn test_detect_scroll_area() {
+        let page = create_page(376, 1200);
+
+        let detection = detect_scroll_area(
+            &create_sidebar_window(&page, 0),
+            &create_sidebar_window(&page, 90),
+        )
+        .expect("scroll area should be detected");
+        assert_eq!(detection.direction, ScrollDirection::Vertical);
+        assert_eq!(detection.offset, 90);
+        // 分隔线没有变化也没有对比度，可能被包含在区域内，但不会包含侧边栏
+        assert!((120..=124).contains(&detection.rect.min_x));
+        assert_eq!(detection.rect.max_x, 500);
+        assert_eq!(detection.rect.min_y, 0);
+        assert_eq!(detection.rect.max_y, 400);
+
+        // 没有滚动时无法检测
+        assert!(
+            detect_scroll_area(
+                &create_sidebar_window(&page, 0),
+                &create_sidebar_window(&page, 0)
+            )
+            .is_none()
+        );
+    }
+}
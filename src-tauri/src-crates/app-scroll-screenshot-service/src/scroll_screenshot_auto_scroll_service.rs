@@ -0,0 +1,278 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_stitch_service::ScrollStitchFrameStatus;
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ScrollAutoScrollOptions {
+    /// 每次发送的滚轮格数，正数向下（右）滚动
+    pub scroll_length: i32,
+    /// 滚动后等待页面稳定的时间（毫秒）
+    pub settle_delay: u64,
+    /// 连续多少帧没有变化时认为已经到达内容末尾
+    pub max_unchanged_count: u32,
+    /// 连续多少帧没有匹配时停止，通常是滚动过快或页面内容发生了变化
+    pub max_not_matched_count: u32,
+    /// 拼接结果在滚动方向上的最大尺寸，为 0 时不限制
+    pub max_size: i32,
+    /// 最长运行时间（毫秒），为 0 时不限制
+    pub max_duration: u64,
+}
+
+impl Default for ScrollAutoScrollOptions {
+    fn default() -> Self {
+        Self {
+            scroll_length: 3,
+            settle_delay: 200,
+            max_unchanged_count: 3,
+            max_not_matched_count: 3,
+            max_size: 0,
+            max_duration: 0,
+        }
+    }
+}
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub enum ScrollAutoScrollStopReason {
+    /// 连续多帧没有变化，已经滚动到内容末尾
+    EndOfContent,
+    /// 连续多帧没有匹配
+    NotMatched,
+    /// 达到最大尺寸
+    MaxSize,
+    /// 达到最长运行时间
+    MaxDuration,
+    /// 被用户停止
+    Stopped,
+}
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub struct ScrollAutoScrollProgress {
+    /// 已处理的帧数
+    pub step_count: u32,
+    /// 最近一帧的处理结果
+    pub frame_status: ScrollStitchFrameStatus,
+    /// 连续没有变化的帧数
+    pub unchanged_count: u32,
+    /// 已运行的时间（毫秒）
+    pub elapsed: u64,
+    pub top_image_size: i32,
+    pub bottom_image_size: i32,
+    /// 停止原因，仍在运行时为空
+    pub stop_reason: Option<ScrollAutoScrollStopReason>,
+}
+
+/**
+ * 自动滚动截图的运行状态和停止条件
+ * 滚动和截图由调用方完成，每处理一帧后通过 record_frame 判断是否需要停止
+ * 拼接和滚动不在同一线程时，滚动方通过 take_progress 获取最近一帧的进度
+ */
+pub struct ScrollAutoScrollService {
+    options: ScrollAutoScrollOptions,
+    running: bool,
+    stop_requested: bool,
+    start_time: Instant,
+    step_count: u32,
+    unchanged_count: u32,
+    not_matched_count: u32,
+    /// 尚未被取走的最近一帧进度
+    last_progress: Option<ScrollAutoScrollProgress>,
+}
+
+impl ScrollAutoScrollService {
+    pub fn new() -> Self {
+        Self {
+            options: ScrollAutoScrollOptions::default(),
+            running: false,
+            stop_requested: false,
+            start_time: Instant::now(),
+            step_count: 0,
+            unchanged_count: 0,
+            not_matched_count: 0,
+            last_progress: None,
+        }
+    }
+
+    pub fn start(&mut self, options: ScrollAutoScrollOptions) -> Result<(), String> {
+        if self.running {
+            return Err(String::from(
+                "[ScrollAutoScrollService::start] Auto scroll is already running",
+            ));
+        }
+
+        self.options = options;
+        self.running = true;
+        self.stop_requested = false;
+        self.start_time = Instant::now();
+        self.step_count = 0;
+        self.unchanged_count = 0;
+        self.not_matched_count = 0;
+        self.last_progress = None;
+
+        Ok(())
+    }
+
+    pub fn options(&self) -> &ScrollAutoScrollOptions {
+        &self.options
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /**
+     * 请求停止，在处理完当前帧后生效
+     */
+    pub fn request_stop(&mut self) {
+        if self.running {
+            self.stop_requested = true;
+        }
+    }
+
+    /**
+     * 在发送下一次滚动前检查是否需要停止
+     */
+    pub fn check_stop(&self) -> Option<ScrollAutoScrollStopReason> {
+        if self.stop_requested {
+            return Some(ScrollAutoScrollStopReason::Stopped);
+        }
+
+        if self.options.max_duration > 0
+            && self.start_time.elapsed() >= Duration::from_millis(self.options.max_duration)
+        {
+            return Some(ScrollAutoScrollStopReason::MaxDuration);
+        }
+
+        None
+    }
+
+    /**
+     * 记录一帧的处理结果并返回进度，需要停止时进度中包含停止原因
+     * 图片尺寸为处理该帧后上下图片列表的尺寸（方向边）
+     */
+    pub fn record_frame(
+        &mut self,
+        frame_status: ScrollStitchFrameStatus,
+        top_image_size: i32,
+        bottom_image_size: i32,
+    ) -> ScrollAutoScrollProgress {
+        self.step_count += 1;
+
+        match frame_status {
+            ScrollStitchFrameStatus::Appended => {
+                self.unchanged_count = 0;
+                self.not_matched_count = 0;
+            }
+            ScrollStitchFrameStatus::Unchanged => {
+                self.unchanged_count += 1;
+                self.not_matched_count = 0;
+            }
+            ScrollStitchFrameStatus::NotMatched => {
+                self.not_matched_count += 1;
+            }
+        }
+
+        let image_size = top_image_size + bottom_image_size;
+        let stop_reason = if self.unchanged_count >= self.options.max_unchanged_count.max(1) {
+            Some(ScrollAutoScrollStopReason::EndOfContent)
+        } else if self.not_matched_count >= self.options.max_not_matched_count.max(1) {
+            Some(ScrollAutoScrollStopReason::NotMatched)
+        } else if self.options.max_size > 0 && image_size >= self.options.max_size {
+            Some(ScrollAutoScrollStopReason::MaxSize)
+        } else {
+            self.check_stop()
+        };
+
+        let progress = ScrollAutoScrollProgress {
+            step_count: self.step_count,
+            frame_status,
+            unchanged_count: self.unchanged_count,
+            elapsed: self.start_time.elapsed().as_millis() as u64,
+            top_image_size,
+            bottom_image_size,
+            stop_reason,
+        };
+        self.last_progress = Some(progress);
+
+        progress
+    }
+
+    /**
+     * 取走最近一次 record_frame 的进度，之后没有新的帧时返回空
+     */
+    pub fn take_progress(&mut self) -> Option<ScrollAutoScrollProgress> {
+        self.last_progress.take()
+    }
+
+    pub fn finish(&mut self) {
+        self.running = false;
+        self.stop_requested = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_scroll_stop_reason() {
+        let mut service = ScrollAutoScrollService::new();
+        service.start(ScrollAutoScrollOptions::default()).unwrap();
+        assert!(service.start(ScrollAutoScrollOptions::default()).is_err());
+
+        // 未变化的帧被追加的帧打断时重新计数
+        for frame_status in [
+            ScrollStitchFrameStatus::Appended,
+            ScrollStitchFrameStatus::Unchanged,
+            ScrollStitchFrameStatus::Unchanged,
+            ScrollStitchFrameStatus::Appended,
+            ScrollStitchFrameStatus::Unchanged,
+            ScrollStitchFrameStatus::Unchanged,
+        ] {
+            assert_eq!(service.record_frame(frame_status, 0, 600).stop_reason, None);
+        }
+
+        let progress = service.record_frame(ScrollStitchFrameStatus::Unchanged, 0, 600);
+        assert_eq!(progress.step_count, 7);
+        assert_eq!(
+            progress.stop_reason,
+            Some(ScrollAutoScrollStopReason::EndOfContent)
+        );
+        // 进度只能取走一次
+        assert_eq!(service.take_progress(), Some(progress));
+        assert_eq!(service.take_progress(), None);
+        service.finish();
+
+        // 尺寸上限
+        service
+            .start(ScrollAutoScrollOptions {
+                max_size: 1000,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            service
+                .record_frame(ScrollStitchFrameStatus::Appended, 0, 800)
+                .stop_reason,
+            None
+        );
+        assert_eq!(
+            service
+                .record_frame(ScrollStitchFrameStatus::Appended, 100, 900)
+                .stop_reason,
+            Some(ScrollAutoScrollStopReason::MaxSize)
+        );
+        service.finish();
+
+        // 用户停止
+        service.start(ScrollAutoScrollOptions::default()).unwrap();
+        service.request_stop();
+        assert_eq!(
+            service.check_stop(),
+            Some(ScrollAutoScrollStopReason::Stopped)
+        );
+        service.finish();
+        assert!(!service.is_running());
+    }
+}
@@ -0,0 +1,540 @@
+use std::time::{Duration, Instant};
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::scroll_screenshot_service::ScrollScreenshotService;
+use crate::scroll_screenshot_stitch_service::{ScrollStitchFrameStatus, ScrollStitchOptions};
+
+/// 拼接结果与真实页面的像素差超过该值时认为像素不一致
+const MISMATCHED_PIXEL_THRESHOLD: u8 = 32;
+/// 页面左右两侧的留白
+const PAGE_MARGIN: u32 = 24;
+
+/// 合成页面参数
+#[derive(Debug, Clone, Copy)]
+pub struct SyntheticPageOptions {
+    /// 页面宽度
+    pub width: u32,
+    /// 页面高度
+    pub height: u32,
+    /// 吸顶导航栏高度，为 0 时不绘制
+    pub sticky_header_size: u32,
+    /// 随机种子，相同参数和种子生成的页面一致
+    pub seed: u32,
+}
+
+impl Default for SyntheticPageOptions {
+    fn default() -> Self {
+        Self {
+            width: 480,
+            height: 3200,
+            sticky_header_size: 48,
+            seed: 0x9e3779b9,
+        }
+    }
+}
+
+/// 合成滚动序列参数，只模拟垂直方向的正向滚动
+#[derive(Debug, Clone, Copy)]
+pub struct SyntheticScrollOptions {
+    /// 视口高度
+    pub viewport_size: u32,
+    /// 每次滚动的最小距离
+    pub min_step: u32,
+    /// 每次滚动的最大距离
+    pub max_step: u32,
+    /// 滚动内容每个通道叠加的随机噪声幅度，模拟缩放、抗锯齿带来的细微差异
+    pub noise_amplitude: u8,
+    /// 随机种子
+    pub seed: u32,
+}
+
+impl Default for SyntheticScrollOptions {
+    fn default() -> Self {
+        Self {
+            viewport_size: 400,
+            min_step: 60,
+            max_step: 220,
+            noise_amplitude: 0,
+            seed: 0x2545f491,
+        }
+    }
+}
+
+/// 合成的滚动序列
+pub struct SyntheticScrollSequence {
+    /// 理想的拼接结果，即带有吸顶导航栏的完整页面
+    pub page: RgbImage,
+    /// 按滚动顺序排列的帧
+    pub frame_list: Vec<DynamicImage>,
+    /// 每帧在页面上的真实滚动位置
+    pub frame_position_list: Vec<u32>,
+}
+
+/// 拼接准确度报告
+#[derive(Debug, Clone)]
+pub struct ScrollAccuracyReport {
+    /// 帧数
+    pub frame_count: usize,
+    /// 未匹配的帧数
+    pub not_matched_count: usize,
+    /// 得到位置的帧数，只有这些帧参与偏移误差统计
+    pub positioned_frame_count: usize,
+    /// 最大偏移误差（像素）
+    pub max_offset_error: u32,
+    /// 平均偏移误差（像素）
+    pub mean_offset_error: f32,
+    /// 拼接结果与真实页面的宽度差
+    pub width_error: i32,
+    /// 拼接结果与真实页面的高度差
+    pub height_error: i32,
+    /// 重叠区域内每个通道的平均像素差
+    pub mean_pixel_diff: f32,
+    /// 重叠区域内不一致像素的比例
+    pub mismatched_pixel_ratio: f32,
+    /// 拼接耗时
+    pub elapsed: Duration,
+}
+
+/// xorshift 随机数，保证不同平台上生成的页面一致
+struct SyntheticRandom {
+    state: u32,
+}
+
+impl SyntheticRandom {
+    fn new(seed: u32) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    fn next(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    /// 返回 [min, max] 范围内的随机数
+    fn range(&mut self, min: u32, max: u32) -> u32 {
+        min + self.next() % (max - min + 1)
+    }
+
+    fn color(&mut self, min: u32, max: u32) -> Rgb<u8> {
+        Rgb([
+            self.range(min, max) as u8,
+            self.range(min, max) as u8,
+            self.range(min, max) as u8,
+        ])
+    }
+}
+
+fn fill_rect(page: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for pixel_y in y..(y + height).min(page.height()) {
+        for pixel_x in x..(x + width).min(page.width()) {
+            page.put_pixel(pixel_x, pixel_y, color);
+        }
+    }
+}
+
+/**
+ * 绘制文字段落，每个字由随机笔画组成，特征点密集
+ */
+fn draw_text_section(page: &mut RgbImage, random: &mut SyntheticRandom, start: u32, end: u32) {
+    let color = random.color(20, 80);
+    let mut line_y = start + 8;
+    while line_y + 14 < end {
+        let line_end = page.width() - PAGE_MARGIN - random.range(0, page.width() / 3);
+        let mut x = PAGE_MARGIN;
+        while x + 8 < line_end {
+            for _ in 0..random.range(2, 8) {
+                let glyph_width = random.range(4, 8);
+                for y in line_y..line_y + 12 {
+                    for glyph_x in x..(x + glyph_width).min(line_end) {
+                        if random.next().is_multiple_of(3) {
+                            page.put_pixel(glyph_x, y, color);
+                        }
+                    }
+                }
+                x += glyph_width + 1;
+            }
+
+            x += random.range(5, 8);
+        }
+
+        line_y += 14 + random.range(8, 16);
+    }
+}
+
+/**
+ * 绘制图片，渐变背景上叠加随机圆形，颜色过渡平滑
+ */
+fn draw_image_section(page: &mut RgbImage, random: &mut SyntheticRandom, start: u32, end: u32) {
+    let (left, right) = (PAGE_MARGIN, page.width() - PAGE_MARGIN);
+    let base_color = random.color(40, 200);
+    for y in start..end {
+        for x in left..right {
+            let gradient = ((x - left) / 4 + (y - start) / 3) as u8;
+            page.put_pixel(
+                x,
+                y,
+                Rgb([
+                    base_color[0].wrapping_add(gradient),
+                    base_color[1].wrapping_add(gradient / 2),
+                    base_color[2].wrapping_sub(gradient / 3),
+                ]),
+            );
+        }
+    }
+
+    for _ in 0..random.range(3, 8) {
+        let radius = random.range(6, 32) as i32;
+        let center_x = random.range(left, right - 1) as i32;
+        let center_y = random.range(start, end - 1) as i32;
+        let color = random.color(0, 255);
+        for y in (center_y - radius).max(start as i32)..(center_y + radius).min(end as i32) {
+            for x in (center_x - radius).max(left as i32)..(center_x + radius).min(right as i32) {
+                let (dx, dy) = (x - center_x, y - center_y);
+                if dx * dx + dy * dy <= radius * radius {
+                    page.put_pixel(x as u32, y as u32, color);
+                }
+            }
+        }
+    }
+}
+
+/**
+ * 绘制低纹理区域，只有非常缓慢的亮度变化，几乎没有特征点
+ */
+fn draw_low_texture_section(
+    page: &mut RgbImage,
+    random: &mut SyntheticRandom,
+    start: u32,
+    end: u32,
+) {
+    let base_value = random.range(232, 250) as u8;
+    for y in start..end {
+        let value = base_value - ((y - start) / 32) as u8;
+        fill_rect(page, 0, y, page.width(), 1, Rgb([value, value, value]));
+    }
+}
+
+fn create_sticky_header(width: u32, height: u32, random: &mut SyntheticRandom) -> RgbImage {
+    let mut header = RgbImage::from_pixel(width, height, random.color(20, 120));
+
+    // 图标和导航文字
+    fill_rect(
+        &mut header,
+        12,
+        height / 4,
+        height / 2,
+        height / 2,
+        Rgb([255, 255, 255]),
+    );
+    let mut x = 24 + height / 2;
+    while x + 48 < width {
+        let item_width = random.range(24, 48);
+        fill_rect(
+            &mut header,
+            x,
+            height / 2 - 4,
+            item_width,
+            8,
+            Rgb([230, 230, 230]),
+        );
+        x += item_width + 16;
+    }
+
+    fill_rect(&mut header, 0, height - 1, width, 1, Rgb([0, 0, 0]));
+
+    header
+}
+
+/**
+ * 生成合成页面，由文字、图片、低纹理区域交替组成
+ * 返回滚动内容和吸顶导航栏，导航栏高度为 0 时返回 None
+ */
+pub fn create_synthetic_page(options: &SyntheticPageOptions) -> (RgbImage, Option<RgbImage>) {
+    let mut random = SyntheticRandom::new(options.seed);
+    let mut page = RgbImage::from_pixel(options.width, options.height, Rgb([255, 255, 255]));
+
+    let mut section_start = 0;
+    while section_start < options.height {
+        let section_kind = random.next() % 3;
+        let section_size = match section_kind {
+            0 => random.range(160, 420),
+            1 => random.range(120, 300),
+            _ => random.range(60, 160),
+        };
+        let section_end = (section_start + section_size).min(options.height);
+
+        match section_kind {
+            0 => draw_text_section(&mut page, &mut random, section_start, section_end),
+            1 => draw_image_section(&mut page, &mut random, section_start, section_end),
+            _ => draw_low_texture_section(&mut page, &mut random, section_start, section_end),
+        }
+
+        // 段落之间的分隔线
+        if section_end < options.height {
+            fill_rect(
+                &mut page,
+                0,
+                section_end - 1,
+                options.width,
+                1,
+                Rgb([210, 210, 210]),
+            );
+        }
+
+        section_start = section_end;
+    }
+
+    let sticky_header = if options.sticky_header_size > 0 {
+        Some(create_sticky_header(
+            options.width,
+            options.sticky_header_size,
+            &mut random,
+        ))
+    } else {
+        None
+    };
+
+    (page, sticky_header)
+}
+
+/**
+ * 生成合成页面并按随机的滚动距离切分为帧
+ * 最后一帧停在页面底部，吸顶导航栏覆盖在每帧顶部
+ */
+pub fn create_synthetic_sequence(
+    page_options: &SyntheticPageOptions,
+    scroll_options: &SyntheticScrollOptions,
+) -> Result<SyntheticScrollSequence, String> {
+    if scroll_options.viewport_size <= page_options.sticky_header_size
+        || scroll_options.viewport_size > page_options.height
+        || scroll_options.min_step == 0
+        || scroll_options.min_step > scroll_options.max_step
+    {
+        return Err(String::from(
+            "[create_synthetic_sequence] Invalid synthetic scroll options",
+        ));
+    }
+
+    let (content, sticky_header) = create_synthetic_page(page_options);
+    let mut random = SyntheticRandom::new(scroll_options.seed);
+
+    let mut frame_position_list = vec![0];
+    let max_position = page_options.height - scroll_options.viewport_size;
+    while let Some(&position) = frame_position_list.last()
+        && position < max_position
+    {
+        let step = random.range(scroll_options.min_step, scroll_options.max_step);
+        frame_position_list.push((position + step).min(max_position));
+    }
+
+    let frame_list = frame_position_list
+        .iter()
+        .map(|&position| {
+            let mut frame = image::imageops::crop_imm(
+                &content,
+                0,
+                position,
+                page_options.width,
+                scroll_options.viewport_size,
+            )
+            .to_image();
+
+            let noise_amplitude = scroll_options.noise_amplitude as i32;
+            if noise_amplitude > 0 {
+                for pixel in frame.pixels_mut() {
+                    for channel in pixel.0.iter_mut() {
+                        let noise =
+                            random.range(0, noise_amplitude as u32 * 2) as i32 - noise_amplitude;
+                        *channel = (*channel as i32 + noise).clamp(0, 255) as u8;
+                    }
+                }
+            }
+
+            // 吸顶导航栏每帧由同一份像素绘制，不叠加噪声
+            if let Some(sticky_header) = sticky_header.as_ref() {
+                image::imageops::replace(&mut frame, sticky_header, 0, 0);
+            }
+
+            DynamicImage::ImageRgb8(frame)
+        })
+        .collect();
+
+    let mut page = content;
+    if let Some(sticky_header) = sticky_header.as_ref() {
+        image::imageops::replace(&mut page, sticky_header, 0, 0);
+    }
+
+    Ok(SyntheticScrollSequence {
+        page,
+        frame_list,
+        frame_position_list,
+    })
+}
+
+/**
+ * 拼接合成序列，统计每帧的偏移误差以及拼接结果与真实页面的像素差
+ * 拼接结果与页面左上角对齐，只比较重叠区域
+ */
+pub fn evaluate_scroll_accuracy(
+    sequence: &SyntheticScrollSequence,
+    options: &ScrollStitchOptions,
+) -> Result<ScrollAccuracyReport, String> {
+    let mut scroll_screenshot_service = ScrollScreenshotService::new();
+
+    let start_time = Instant::now();
+    let result =
+        scroll_screenshot_service.stitch_frames(options, sequence.frame_list.iter().cloned())?;
+    let elapsed = start_time.elapsed();
+
+    let first_position = sequence.frame_position_list[0] as i32;
+    let offset_error_list: Vec<u32> = result
+        .frame_position_list
+        .iter()
+        .zip(sequence.frame_position_list.iter())
+        .filter_map(|(position, &expected_position)| {
+            position.map(|position| position.abs_diff(expected_position as i32 - first_position))
+        })
+        .collect();
+
+    let image = result.image.to_rgb8();
+    let (compare_width, compare_height) = (
+        image.width().min(sequence.page.width()),
+        image.height().min(sequence.page.height()),
+    );
+
+    let mut diff_sum: u64 = 0;
+    let mut mismatched_pixel_count: u64 = 0;
+    for y in 0..compare_height {
+        for x in 0..compare_width {
+            let pixel = image.get_pixel(x, y);
+            let expected_pixel = sequence.page.get_pixel(x, y);
+
+            let mut max_diff = 0;
+            for channel in 0..3 {
+                let diff = pixel[channel].abs_diff(expected_pixel[channel]);
+                diff_sum += diff as u64;
+                max_diff = max_diff.max(diff);
+            }
+
+            if max_diff > MISMATCHED_PIXEL_THRESHOLD {
+                mismatched_pixel_count += 1;
+            }
+        }
+    }
+    let pixel_count = (compare_width as u64 * compare_height as u64).max(1);
+
+    Ok(ScrollAccuracyReport {
+        frame_count: result.frame_status_list.len(),
+        not_matched_count: result.count(ScrollStitchFrameStatus::NotMatched),
+        positioned_frame_count: offset_error_list.len(),
+        max_offset_error: offset_error_list.iter().copied().max().unwrap_or(0),
+        mean_offset_error: offset_error_list.iter().sum::<u32>() as f32
+            / offset_error_list.len().max(1) as f32,
+        width_error: image.width() as i32 - sequence.page.width() as i32,
+        height_error: image.height() as i32 - sequence.page.height() as i32,
+        mean_pixel_diff: diff_sum as f32 / (pixel_count * 3) as f32,
+        mismatched_pixel_ratio: mismatched_pixel_count as f32 / pixel_count as f32,
+        elapsed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_matcher::ScrollMatcherType;
+
+    fn get_test_options(sticky_header_size: u32) -> (SyntheticPageOptions, SyntheticScrollOptions) {
+        (
+            SyntheticPageOptions {
+                width: 360,
+                height: 2400,
+                sticky_header_size,
+                ..Default::default()
+            },
+            SyntheticScrollOptions {
+                viewport_size: 360,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_create_synthetic_sequence() {
+        let (page_options, scroll_options) = get_test_options(48);
+        let sequence = create_synthetic_sequence(&page_options, &scroll_options).unwrap();
+
+        assert_eq!(sequence.page.dimensions(), (360, 2400));
+        assert_eq!(
+            sequence.frame_list.len(),
+            sequence.frame_position_list.len()
+        );
+        assert_eq!(*sequence.frame_position_list.last().unwrap(), 2400 - 360);
+
+        // 没有噪声时，每帧滚动内容与页面对应区域一致
+        for (frame, &position) in sequence
+            .frame_list
+            .iter()
+            .zip(sequence.frame_position_list.iter())
+        {
+            let frame = frame.to_rgb8();
+            for y in 48..360 {
+                for x in 0..360 {
+                    if position + y >= 48 {
+                        assert_eq!(
+                            frame.get_pixel(x, y),
+                            sequence.page.get_pixel(x, position + y)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// 固定种子下的基线，调整拼接参数后准确度下降时测试失败
+    #[test]
+    fn test_scroll_accuracy() {
+        let (page_options, scroll_options) = get_test_options(0);
+
+        for noise_amplitude in [0, 4] {
+            let sequence = create_synthetic_sequence(
+                &page_options,
+                &SyntheticScrollOptions {
+                    max_step: 150,
+                    noise_amplitude,
+                    ..scroll_options
+                },
+            )
+            .unwrap();
+
+            for matcher_type in [
+                ScrollMatcherType::Corner,
+                ScrollMatcherType::PhaseCorrelation,
+            ] {
+                let options = ScrollStitchOptions {
+                    matcher_type,
+                    ..Default::default()
+                };
+                let report = evaluate_scroll_accuracy(&sequence, &options).unwrap();
+
+                assert_eq!(report.not_matched_count, 0, "{:?}", report);
+                assert_eq!(
+                    report.positioned_frame_count, report.frame_count,
+                    "{:?}",
+                    report
+                );
+                assert_eq!(report.max_offset_error, 0, "{:?}", report);
+                assert_eq!(report.width_error, 0, "{:?}", report);
+                assert_eq!(report.height_error, 0, "{:?}", report);
+                assert!(
+                    report.mean_pixel_diff <= noise_amplitude as f32,
+                    "{:?}",
+                    report
+                );
+                assert_eq!(report.mismatched_pixel_ratio, 0.0, "{:?}", report);
+            }
+        }
+    }
+}
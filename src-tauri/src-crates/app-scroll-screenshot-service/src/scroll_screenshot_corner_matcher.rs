@@ -0,0 +1,354 @@
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use hora::core::ann_index::ANNIndex;
+use hora::core::metrics::Metric;
+use hora::index::{hnsw_idx::HNSWIndex, hnsw_params::HNSWParams};
+use image::{GenericImageView, GrayImage};
+use imageproc::corners;
+use rayon::prelude::*;
+
+use crate::scroll_screenshot_dynamic_mask::ScrollDynamicMask;
+use crate::scroll_screenshot_matcher::{
+    ScrollFeatures, ScrollMatchConstraint, ScrollMatchQuality, ScrollMatchResult, ScrollMatcher,
+    downcast_features,
+};
+use crate::scroll_screenshot_service::{ScrollDirection, ScrollOffset};
+
+pub struct CornerFeatures {
+    pub corners: Vec<ScrollOffset>,
+    pub descriptors: Vec<Vec<f32>>,
+    /// 只有作为索引时才会建立
+    pub ann_index: Option<HNSWIndex<f32, usize>>,
+}
+
+impl ScrollFeatures for CornerFeatures {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub(crate) fn compute_descriptor(
+    img: &image::GrayImage,
+    corner: &ScrollOffset,
+    descriptor_patch_size: usize,
+) -> Vec<f32> {
+    let descriptor_size = descriptor_patch_size;
+    let mut descriptor = Vec::with_capacity(descriptor_patch_size & !1);
+    let half_size = descriptor_size as i32 / 2;
+
+    let corner_x = corner.x;
+    let corner_y = corner.y;
+    let width = img.width() as i32;
+    let height = img.height() as i32;
+
+    // 计算行特征
+    for row in 0..(descriptor_size / 2) {
+        let y = corner_y + (-half_size + row as i32 * 2);
+        let mut sum = 0.0;
+        let mut valid_pixels = 0;
+
+        for col in 0..(descriptor_size / 2) {
+            let x = corner_x + (-half_size + col as i32 * 2);
+
+            if x >= 0 && x < width && y >= 0 && y < height {
+                let pixel = unsafe { img.unsafe_get_pixel(x as u32, y as u32) };
+                sum += pixel[0] as f32 / 255.0;
+                valid_pixels += 1;
+            }
+        }
+
+        descriptor.push(if valid_pixels > 0 {
+            sum / valid_pixels as f32
+        } else {
+            0.0
+        });
+    }
+
+    // 计算列特征
+    for col in 0..(descriptor_size / 2) {
+        let x = corner_x + (-half_size + col as i32 * 2);
+        let mut sum = 0.0;
+        let mut valid_pixels = 0;
+
+        for row in 0..(descriptor_size / 2) {
+            let y = corner_y + (-half_size + row as i32 * 2);
+
+            if x >= 0 && x < width && y >= 0 && y < height {
+                let pixel = unsafe { img.unsafe_get_pixel(x as u32, y as u32) };
+                sum += pixel[0] as f32 / 255.0;
+                valid_pixels += 1;
+            }
+        }
+
+        descriptor.push(if valid_pixels > 0 {
+            sum / valid_pixels as f32
+        } else {
+            0.0
+        });
+    }
+
+    descriptor
+}
+
+pub(crate) fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/**
+ * 使用 FAST 特征点、行列均值描述符和 HNSW 索引匹配图片
+ */
+pub struct CornerScrollMatcher {
+    direction: ScrollDirection,
+    /// 特征点阈值
+    corner_threshold: u8,
+    /// 描述符块大小
+    descriptor_patch_size: usize,
+    /// 是否启用 fast12 算法进行角点检测，在首次提取特征时确定
+    enable_corner_fast12: Option<bool>,
+}
+
+impl CornerScrollMatcher {
+    pub fn new(
+        direction: ScrollDirection,
+        corner_threshold: u8,
+        descriptor_patch_size: usize,
+    ) -> Self {
+        Self {
+            direction,
+            corner_threshold,
+            descriptor_patch_size,
+            enable_corner_fast12: None,
+        }
+    }
+
+    fn get_descriptor_size(&self) -> usize {
+        self.descriptor_patch_size & !1
+    }
+
+    fn get_corners(&mut self, image: &image::GrayImage) -> Vec<ScrollOffset> {
+        let corners;
+        if self.enable_corner_fast12.is_none() {
+            let fast12_corners = corners::corners_fast12(image, self.corner_threshold);
+
+            if fast12_corners.len() > 200 {
+                corners = fast12_corners;
+                self.enable_corner_fast12 = Some(true);
+            } else {
+                corners = corners::corners_fast9(image, self.corner_threshold);
+                self.enable_corner_fast12 = Some(false);
+            }
+        } else {
+            if self.enable_corner_fast12.unwrap() {
+                corners = corners::corners_fast12(image, self.corner_threshold);
+            } else {
+                corners = corners::corners_fast9(image, self.corner_threshold);
+            }
+        }
+
+        corners
+            .iter()
+            .map(|corner| ScrollOffset {
+                x: corner.x as i32,
+                y: corner.y as i32,
+            })
+            .collect()
+    }
+
+    fn get_descriptors(
+        &self,
+        image: &image::ImageBuffer<image::Luma<u8>, Vec<u8>>,
+        corners: &[ScrollOffset],
+    ) -> Vec<Vec<f32>> {
+        corners
+            .par_iter()
+            .map(|corner| compute_descriptor(image, corner, self.descriptor_patch_size))
+            .collect()
+    }
+}
+
+impl ScrollMatcher for CornerScrollMatcher {
+    fn extract_features(
+        &mut self,
+        gray_image: &GrayImage,
+        mask: &ScrollDynamicMask,
+    ) -> Option<Box<dyn ScrollFeatures>> {
+        let mut corners = self.get_corners(gray_image);
+        // 不稳定区域的特征点会独立移动，干扰主要偏移的判断
+        if !mask.is_empty() {
+            corners.retain(|corner| !mask.contains(corner.x, corner.y));
+        }
+        if corners.is_empty() {
+            return None;
+        }
+
+        let descriptors = self.get_descriptors(gray_image, &corners);
+
+        Some(Box::new(CornerFeatures {
+            corners,
+            descriptors,
+            ann_index: None,
+        }))
+    }
+
+    fn build_index_features(&self, features: Box<dyn ScrollFeatures>) -> Box<dyn ScrollFeatures> {
+        let features = match downcast_features::<CornerFeatures>(features.as_ref()) {
+            Some(features) => features,
+            None => return features,
+        };
+
+        let mut index_params = HNSWParams::<f32>::default();
+        index_params.ef_search = 24;
+        index_params.ef_build = 12;
+
+        let mut ann_index = HNSWIndex::new(self.get_descriptor_size(), &index_params);
+        features
+            .descriptors
+            .iter()
+            .enumerate()
+            .for_each(|(i, descriptor)| {
+                ann_index.add(descriptor, i).unwrap();
+            });
+        ann_index.build(Metric::Euclidean).unwrap();
+
+        Box::new(CornerFeatures {
+            corners: features.corners.clone(),
+            descriptors: features.descriptors.clone(),
+            ann_index: Some(ann_index),
+        })
+    }
+
+    fn match_features(
+        &self,
+        index_features: &dyn ScrollFeatures,
+        features: &dyn ScrollFeatures,
+        constraint: &ScrollMatchConstraint,
+    ) -> ScrollMatchResult {
+        let (index, index_ann_index, image_features) = match (
+            downcast_features::<CornerFeatures>(index_features),
+            downcast_features::<CornerFeatures>(features),
+        ) {
+            (Some(index), Some(image_features)) => match &index.ann_index {
+                Some(index_ann_index) => (index, index_ann_index, image_features),
+                None => return ScrollMatchResult::NotMatched,
+            },
+            _ => return ScrollMatchResult::NotMatched,
+        };
+        let image_corners = &image_features.corners;
+
+        let min_diff_count = AtomicUsize::new(0);
+
+        let offsets: Vec<(i32, i32)> = image_features
+            .descriptors
+            .par_iter()
+            .enumerate()
+            .filter_map(|(i, descriptor)| {
+                let search_result = index_ann_index.search(descriptor, 1);
+                if search_result.is_empty() {
+                    return None;
+                }
+
+                let idx1 = search_result[0];
+                let dist = euclidean_distance(&index.descriptors[idx1], descriptor);
+
+                let point1 = &index.corners[idx1];
+                let point2 = &image_corners[i];
+                let dy = point2.y - point1.y;
+                let dx = point2.x - point1.x;
+
+                let (diff, cross_diff) = if self.direction == ScrollDirection::Vertical {
+                    (dy, dx)
+                } else {
+                    (dx, dy)
+                };
+                if cross_diff.abs() > constraint.cross_axis_tolerance {
+                    return None;
+                }
+
+                if !constraint.accepts_diff(diff) {
+                    min_diff_count.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+
+                if dist < 0.1 {
+                    Some((diff, cross_diff))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if min_diff_count.load(Ordering::Relaxed) > (image_corners.len() as f32 * 0.72) as usize {
+            return ScrollMatchResult::NoNewArea;
+        }
+
+        if offsets.is_empty() {
+            return ScrollMatchResult::NotMatched;
+        }
+
+        let matched_count = offsets.len();
+
+        // 按滚动方向的偏移分组，寻找频率最高的偏移作为主要偏移模式
+        let mut offset_groups: std::collections::HashMap<i32, Vec<i32>> =
+            std::collections::HashMap::new();
+        for (offset, cross_offset) in offsets {
+            offset_groups.entry(offset).or_default().push(cross_offset);
+        }
+
+        let mut max_count = 0;
+        let mut second_max_count = 0;
+        let mut max_offset = None;
+
+        for (offset, cross_offset_list) in &offset_groups {
+            let count = cross_offset_list.len() as i32;
+            if count > max_count {
+                second_max_count = max_count;
+                max_count = count;
+                max_offset = Some(*offset);
+            } else if count > second_max_count {
+                second_max_count = count;
+            }
+        }
+
+        let dominant_offset = match max_offset {
+            Some(offset) => offset,
+            None => return ScrollMatchResult::NotMatched,
+        };
+
+        if max_count < (image_corners.len() as i32 / 10) {
+            return ScrollMatchResult::NotMatched;
+        }
+
+        if max_count < second_max_count * 2 {
+            return ScrollMatchResult::NotMatched;
+        }
+
+        // 垂直于滚动方向的偏移取主要偏移中所有特征点的中位数，避免个别误匹配的特征点决定结果
+        let mut cross_offset_list = offset_groups.remove(&dominant_offset).unwrap_or_default();
+        cross_offset_list.sort_unstable();
+        let cross_offset = cross_offset_list[cross_offset_list.len() / 2].clamp(
+            -constraint.cross_axis_tolerance,
+            constraint.cross_axis_tolerance,
+        );
+
+        let (dx, dy) = if self.direction == ScrollDirection::Vertical {
+            (cross_offset, dominant_offset)
+        } else {
+            (dominant_offset, cross_offset)
+        };
+
+        ScrollMatchResult::Matched(
+            // 偏移为索引图片相对新图片的位置
+            ScrollOffset::new(-dx, -dy),
+            // 描述符匹配成功的特征点中支持主要偏移的比例
+            ScrollMatchQuality {
+                confidence: max_count as f32 / matched_count as f32,
+                inlier_count: max_count as usize,
+            },
+        )
+    }
+}
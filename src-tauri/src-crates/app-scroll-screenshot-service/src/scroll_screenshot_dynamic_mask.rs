@@ -0,0 +1,176 @@
+use image::GrayImage;
+use snow_shot_app_shared::ElementRect;
+
+/// 判断是否变化的块大小（缩放后的像素）
+const BLOCK_SIZE: u32 = 16;
+/// 像素差超过该值时认为像素发生了变化
+const PIXEL_DIFF_THRESHOLD: u8 = 24;
+/// 块内对比度不超过该值时认为是留白，无法判断是否滚动
+const FLAT_BLOCK_CONTRAST: u8 = 8;
+/// 有内容的块中未变化的比例不低于该值时，认为两帧处于同一滚动位置
+const MIN_STABLE_BLOCK_RATIO: f32 = 0.5;
+/// 未变化的有内容的块少于该值时无法判断是否滚动
+const MIN_STABLE_BLOCK_COUNT: usize = 8;
+
+/**
+ * 不稳定区域的掩码，坐标为缩放后的灰度图坐标
+ * 闪烁的光标、动画、视频和鼠标指针会产生独立移动的特征，干扰偏移的判断
+ * 同一滚动位置的相邻两帧中变化的块会被标记为不稳定，调用方也可以直接指定排除区域
+ */
+pub struct ScrollDynamicMask {
+    width: u32,
+    height: u32,
+    column_count: u32,
+    row_count: u32,
+    /// 按块记录是否不稳定
+    unstable_block_list: Vec<bool>,
+    /// 调用方指定的排除区域
+    exclusion_rect_list: Vec<ElementRect>,
+}
+
+impl ScrollDynamicMask {
+    pub fn new(width: u32, height: u32) -> Self {
+        let column_count = width.div_ceil(BLOCK_SIZE);
+        let row_count = height.div_ceil(BLOCK_SIZE);
+
+        Self {
+            width,
+            height,
+            column_count,
+            row_count,
+            unstable_block_list: vec![false; (column_count * row_count) as usize],
+            exclusion_rect_list: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exclusion_rect_list.is_empty() && !self.unstable_block_list.iter().any(|x| *x)
+    }
+
+    pub fn set_exclusion_rects(&mut self, exclusion_rect_list: Vec<ElementRect>) {
+        self.exclusion_rect_list = exclusion_rect_list;
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+
+        let block_index = (y as u32 / BLOCK_SIZE) * self.column_count + x as u32 / BLOCK_SIZE;
+        if self.unstable_block_list[block_index as usize] {
+            return true;
+        }
+
+        self.exclusion_rect_list
+            .iter()
+            .any(|rect| x >= rect.min_x && x < rect.max_x && y >= rect.min_y && y < rect.max_y)
+    }
+
+    /**
+     * 比较相邻的两帧，处于同一滚动位置时将变化的块标记为不稳定
+     * 返回是否更新了掩码
+     */
+    pub fn update(&mut self, previous_image: &GrayImage, image: &GrayImage) -> bool {
+        if previous_image.dimensions() != (self.width, self.height)
+            || image.dimensions() != (self.width, self.height)
+        {
+            return false;
+        }
+
+        let mut changed_block_list = vec![false; self.unstable_block_list.len()];
+        let mut stable_block_count = 0;
+        let mut content_block_count = 0;
+
+        for row in 0..self.row_count {
+            for column in 0..self.column_count {
+                let x_end = ((column + 1) * BLOCK_SIZE).min(self.width);
+                let y_end = ((row + 1) * BLOCK_SIZE).min(self.height);
+
+                let mut min_pixel = u8::MAX;
+                let mut max_pixel = u8::MIN;
+                let mut is_changed = false;
+                for y in row * BLOCK_SIZE..y_end {
+                    for x in column * BLOCK_SIZE..x_end {
+                        let previous_pixel = previous_image.get_pixel(x, y)[0];
+                        let pixel = image.get_pixel(x, y)[0];
+
+                        min_pixel = min_pixel.min(previous_pixel).min(pixel);
+                        max_pixel = max_pixel.max(previous_pixel).max(pixel);
+                        is_changed |= previous_pixel.abs_diff(pixel) > PIXEL_DIFF_THRESHOLD;
+                    }
+                }
+
+                if max_pixel - min_pixel <= FLAT_BLOCK_CONTRAST {
+                    continue;
+                }
+
+                content_block_count += 1;
+                if is_changed {
+                    changed_block_list[(row * self.column_count + column) as usize] = true;
+                } else {
+                    stable_block_count += 1;
+                }
+            }
+        }
+
+        // 滚动时几乎所有有内容的块都会变化，此时不能判断哪些区域不稳定
+        if stable_block_count < MIN_STABLE_BLOCK_COUNT
+            || (stable_block_count as f32) < content_block_count as f32 * MIN_STABLE_BLOCK_RATIO
+            || stable_block_count == content_block_count
+        {
+            return false;
+        }
+
+        self.unstable_block_list
+            .iter_mut()
+            .zip(changed_block_list.iter())
+            .for_each(|(unstable, changed)| *unstable |= *changed);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_text_page, crop_frame};
+    use image::Luma;
+
+    /// 文字页面滚动 offset 像素后的灰度图，caret 为 true 时显示光标
+    fn create_text_image(offset: u32, caret: bool) -> GrayImage {
+        let mut image = crop_frame(&create_text_page(160, 640), 0, offset, 160, 320).to_luma8();
+        if caret {
+            for y in 40..60 {
+                for x in 100..104 {
+                    image.put_pixel(x, y, Luma([0]));
+                }
+            }
+        }
+
+        image
+    }
+
+    #[test]
+    fn test_update_dynamic_mask() {
+        let mut mask = ScrollDynamicMask::new(160, 320);
+        assert!(mask.is_empty());
+
+        // 滚动后的帧不会更新掩码
+        assert!(!mask.update(&create_text_image(0, false), &create_text_image(6, false)));
+        assert!(mask.is_empty());
+
+        // 同一位置闪烁的光标被标记为不稳定
+        assert!(mask.update(&create_text_image(0, false), &create_text_image(0, true)));
+        assert!(mask.contains(101, 50));
+        assert!(!mask.contains(20, 200));
+
+        mask.set_exclusion_rects(vec![ElementRect {
+            min_x: 0,
+            min_y: 180,
+            max_x: 40,
+            max_y: 220,
+        }]);
+        assert!(mask.contains(20, 200));
+        assert!(!mask.contains(60, 200));
+    }
+}
@@ -0,0 +1,1128 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_frame_store::ScrollFrameImage;
+use crate::scroll_screenshot_pdf::ScrollPdfWriter;
+use crate::scroll_screenshot_scrollbar_band::ScrollbarBand;
+use crate::scroll_screenshot_seam::{
+    ScrollSeamMode, SeamOverlap, blend_pixel, get_feather_weights, get_min_difference_weights,
+};
+use crate::scroll_screenshot_service::{CropRegion, ScrollDirection, ScrollScreenshotService};
+
+const RGB_CHANNEL_COUNT: usize = 3;
+const RGBA_CHANNEL_COUNT: usize = 4;
+
+/// 流式写入时每次渲染的行数
+const STREAM_BAND_SIZE: u32 = 256;
+
+/// 智能分页时在每页末尾的该比例范围内查找分页位置
+const PAGE_BREAK_SEARCH_RATIO: f32 = 0.25;
+
+/// 导出时保留的已解码图片数量，相邻的段通常只涉及少量图片
+const DECODED_FRAME_CACHE_SIZE: usize = 4;
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ScrollExportFormat {
+    /// 单个 PNG 文件，逐行写入
+    Png = 0,
+    /// 按最大尺寸分割为多个编号的 PNG 文件
+    PngTiles = 1,
+    /// 单个多页 TIFF 文件，每页不超过最大尺寸
+    MultiPageTiff = 2,
+    /// 在接近最大尺寸的留白处分页，输出多个编号的 PNG 文件
+    PngPages = 3,
+    /// 在接近最大尺寸的留白处分页，输出单个多页 PDF 文件
+    Pdf = 4,
+}
+
+/// 导出时绘制的图片
+enum ExportImage<'a> {
+    /// 未压缩的图片，如固定区域
+    Image(&'a DynamicImage),
+    /// 压缩保存的图片，绘制时解码
+    Frame(&'a ScrollFrameImage),
+}
+
+impl ExportImage<'_> {
+    fn width(&self) -> u32 {
+        match self {
+            ExportImage::Image(image) => image.width(),
+            ExportImage::Frame(frame) => frame.width(),
+        }
+    }
+
+    fn height(&self) -> u32 {
+        match self {
+            ExportImage::Image(image) => image.height(),
+            ExportImage::Frame(frame) => frame.height(),
+        }
+    }
+}
+
+/// 单张图片在最终图片中的位置
+struct ExportPlacement<'a> {
+    image: ExportImage<'a>,
+    /// 图片在滚动方向上的起始位置
+    scroll_position: i32,
+    /// 图片在滚动垂直方向上的裁剪起点，用于对齐存在偏移的图片
+    cross_start: u32,
+    /// 与之前绘制内容的重叠区域
+    overlap: SeamOverlap,
+    /// 重叠区域内每一行（列）的权重，为空时直接覆盖
+    overlap_weights: Vec<f32>,
+}
+
+/**
+ * 导出时各图片在最终图片中的布局
+ * 可以只渲染最终图片的一部分，避免为很长的截图一次分配整张图片的内存
+ */
+pub struct ScrollExportLayout<'a> {
+    /// 最终图片宽度
+    pub width: u32,
+    /// 最终图片高度
+    pub height: u32,
+    direction: ScrollDirection,
+    /// 滚动垂直方向上的尺寸
+    cross_side_size: u32,
+    /// 每个像素的通道数，任意一张图片带有透明度时为 RGBA
+    channel_count: usize,
+    seam_mode: ScrollSeamMode,
+    placements: Vec<ExportPlacement<'a>>,
+    /// 最近解码的图片，按图片序号查找
+    decoded_frame_cache: Mutex<VecDeque<(u64, Arc<DynamicImage>)>>,
+}
+
+impl<'a> ScrollExportLayout<'a> {
+    /// 图片在最终图片中的区域 (x, y, width, height)
+    fn get_placement_rect(&self, placement: &ExportPlacement) -> (i32, i32, i32, i32) {
+        if self.direction == ScrollDirection::Vertical {
+            (
+                0,
+                placement.scroll_position,
+                self.cross_side_size as i32,
+                placement.image.height() as i32,
+            )
+        } else {
+            (
+                placement.scroll_position,
+                0,
+                placement.image.width() as i32,
+                self.cross_side_size as i32,
+            )
+        }
+    }
+
+    fn get_line_count(&self, image: &ExportImage) -> usize {
+        if self.direction == ScrollDirection::Vertical {
+            image.height() as usize
+        } else {
+            image.width() as usize
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.channel_count == RGBA_CHANNEL_COUNT
+    }
+
+    /**
+     * 转换为最终图片的像素格式
+     */
+    fn convert_image(&self, image: DynamicImage) -> DynamicImage {
+        match image {
+            DynamicImage::ImageRgb8(_) if !self.has_alpha() => image,
+            DynamicImage::ImageRgba8(_) if self.has_alpha() => image,
+            image if self.has_alpha() => DynamicImage::ImageRgba8(image.to_rgba8()),
+            image => DynamicImage::ImageRgb8(image.to_rgb8()),
+        }
+    }
+
+    /**
+     * 使用图片的像素，压缩保存的图片会先解码，像素格式与最终图片一致
+     */
+    fn with_image<R>(
+        &self,
+        image: &ExportImage,
+        f: impl FnOnce(&DynamicImage) -> R,
+    ) -> Result<R, String> {
+        let frame = match image {
+            ExportImage::Image(image) => {
+                if image.color().has_alpha() == self.has_alpha() {
+                    return Ok(f(image));
+                }
+
+                return Ok(f(&self.convert_image((*image).clone())));
+            }
+            ExportImage::Frame(frame) => frame,
+        };
+
+        let mut decoded_frame_cache = self
+            .decoded_frame_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let decoded_image = match decoded_frame_cache
+            .iter()
+            .find(|(sequence, _)| *sequence == frame.sequence())
+        {
+            Some((_, decoded_image)) => decoded_image.clone(),
+            None => {
+                let decoded_image = Arc::new(self.convert_image(frame.decode()?));
+                decoded_frame_cache.push_back((frame.sequence(), decoded_image.clone()));
+                if decoded_frame_cache.len() > DECODED_FRAME_CACHE_SIZE {
+                    decoded_frame_cache.pop_front();
+                }
+
+                decoded_image
+            }
+        };
+        drop(decoded_frame_cache);
+
+        Ok(f(&decoded_image))
+    }
+
+    fn draw_placement(
+        &self,
+        placement: &ExportPlacement,
+        buffer: &mut [u8],
+        region: CropRegion,
+    ) -> Result<(), String> {
+        let (image_x, image_y, image_width, image_height) = self.get_placement_rect(placement);
+
+        let x_start = image_x.max(region.x as i32);
+        let x_end = (image_x + image_width).min((region.x + region.width) as i32);
+        let y_start = image_y.max(region.y as i32);
+        let y_end = (image_y + image_height).min((region.y + region.height) as i32);
+        if x_start >= x_end || y_start >= y_end {
+            return Ok(());
+        }
+
+        let (overlap_start, overlap_end) = if placement.overlap_weights.is_empty() {
+            (0, 0)
+        } else {
+            placement
+                .overlap
+                .line_range(self.get_line_count(&placement.image))
+        };
+
+        let (source_x_offset, source_y_offset) = if self.direction == ScrollDirection::Vertical {
+            (placement.cross_start as usize, 0)
+        } else {
+            (0, placement.cross_start as usize)
+        };
+
+        self.with_image(&placement.image, |image| {
+            let source_pixels = image.as_bytes();
+            let source_row_size = image.width() as usize * self.channel_count;
+            let region_row_size = region.width as usize * self.channel_count;
+            let copy_size = (x_end - x_start) as usize * self.channel_count;
+            let line_start = (x_start - image_x) as usize;
+
+            for y in y_start..y_end {
+                let source_index = ((y - image_y) as usize + source_y_offset) * source_row_size
+                    + (line_start + source_x_offset) * self.channel_count;
+                let buffer_index = (y - region.y as i32) as usize * region_row_size
+                    + (x_start - region.x as i32) as usize * self.channel_count;
+
+                let source_row = &source_pixels[source_index..source_index + copy_size];
+                let buffer_row = &mut buffer[buffer_index..buffer_index + copy_size];
+
+                if self.direction == ScrollDirection::Vertical {
+                    let line = (y - image_y) as usize;
+                    if line < overlap_start || line >= overlap_end {
+                        buffer_row.copy_from_slice(source_row);
+                        continue;
+                    }
+
+                    let weight = placement.overlap_weights[line - overlap_start];
+                    buffer_row
+                        .chunks_exact_mut(self.channel_count)
+                        .zip(source_row.chunks_exact(self.channel_count))
+                        .for_each(|(buffer_pixel, source_pixel)| {
+                            blend_pixel(buffer_pixel, source_pixel, weight);
+                        });
+                } else {
+                    if overlap_start == overlap_end {
+                        buffer_row.copy_from_slice(source_row);
+                        continue;
+                    }
+
+                    buffer_row
+                        .chunks_exact_mut(self.channel_count)
+                        .zip(source_row.chunks_exact(self.channel_count))
+                        .enumerate()
+                        .for_each(|(pixel_index, (buffer_pixel, source_pixel))| {
+                            let line = line_start + pixel_index;
+                            let weight = if line >= overlap_start && line < overlap_end {
+                                placement.overlap_weights[line - overlap_start]
+                            } else {
+                                1.0
+                            };
+
+                            blend_pixel(buffer_pixel, source_pixel, weight);
+                        });
+                }
+            }
+        })
+    }
+
+    /**
+     * 渲染最终图片的一部分，返回 RGB 像素，带有透明度时为 RGBA 像素
+     */
+    pub fn render_region(&self, region: CropRegion) -> Result<Vec<u8>, String> {
+        let mut buffer =
+            vec![0; region.width as usize * region.height as usize * self.channel_count];
+
+        for placement in self.placements.iter() {
+            self.draw_placement(placement, &mut buffer, region)?;
+        }
+
+        Ok(buffer)
+    }
+
+    /**
+     * 渲染最终图片的一部分，带有透明度时为 RGBA 图片
+     */
+    pub fn render_region_image(&self, region: CropRegion) -> Result<DynamicImage, String> {
+        let pixels = self.render_region(region)?;
+
+        Ok(if self.has_alpha() {
+            DynamicImage::ImageRgba8(
+                image::RgbaImage::from_raw(region.width, region.height, pixels).unwrap(),
+            )
+        } else {
+            DynamicImage::ImageRgb8(
+                image::RgbImage::from_raw(region.width, region.height, pixels).unwrap(),
+            )
+        })
+    }
+
+    /**
+     * 渲染最终图片的一部分，透明区域与白色背景混合，返回 RGB 像素
+     */
+    pub fn render_region_rgb(&self, region: CropRegion) -> Result<Vec<u8>, String> {
+        let pixels = self.render_region(region)?;
+        if !self.has_alpha() {
+            return Ok(pixels);
+        }
+
+        Ok(pixels
+            .chunks_exact(RGBA_CHANNEL_COUNT)
+            .flat_map(|pixel| {
+                let alpha = pixel[3] as u32;
+                [0, 1, 2].map(|channel| {
+                    ((pixel[channel] as u32 * alpha + u8::MAX as u32 * (u8::MAX as u32 - alpha))
+                        / u8::MAX as u32) as u8
+                })
+            })
+            .collect())
+    }
+
+    /**
+     * max_page_size 为 0 时不分页
+     */
+    fn get_max_page_size(&self, max_page_size: u32) -> u32 {
+        if max_page_size > 0 {
+            return max_page_size;
+        }
+
+        if self.direction == ScrollDirection::Vertical {
+            self.height
+        } else {
+            self.width
+        }
+        .max(1)
+    }
+
+    /**
+     * 沿滚动方向将最终图片分割为不超过 max_page_size 的若干页，为 0 时不分页
+     */
+    pub fn get_page_region_list(&self, max_page_size: u32) -> Vec<CropRegion> {
+        let max_page_size = self.get_max_page_size(max_page_size);
+        let scroll_side_size = if self.direction == ScrollDirection::Vertical {
+            self.height
+        } else {
+            self.width
+        };
+
+        (0..scroll_side_size)
+            .step_by(max_page_size as usize)
+            .map(|page_start| {
+                let page_size = max_page_size.min(scroll_side_size - page_start);
+                if self.direction == ScrollDirection::Vertical {
+                    CropRegion::new(0, page_start, self.width, page_size)
+                } else {
+                    CropRegion::new(page_start, 0, page_size, self.height)
+                }
+            })
+            .collect()
+    }
+
+    /**
+     * 沿滚动方向分页，每页不超过 max_page_size，为 0 时不分页
+     * 分页位置选在每页末尾附近内容最少的行（列），避免从文字中间截断
+     */
+    pub fn get_smart_page_region_list(
+        &self,
+        max_page_size: u32,
+    ) -> Result<Vec<CropRegion>, String> {
+        let max_page_size = self.get_max_page_size(max_page_size);
+        let is_vertical = self.direction == ScrollDirection::Vertical;
+        let scroll_side_size = if is_vertical { self.height } else { self.width };
+        let search_size = ((max_page_size as f32 * PAGE_BREAK_SEARCH_RATIO) as u32).max(1);
+
+        let mut page_region_list = vec![];
+        let mut page_start = 0;
+        while page_start < scroll_side_size {
+            let page_end = if scroll_side_size - page_start <= max_page_size {
+                scroll_side_size
+            } else {
+                let search_end = page_start + max_page_size;
+                let search_start = (search_end - search_size).max(page_start + 1);
+                search_start + self.find_page_break(search_start, search_end - search_start)?
+            };
+
+            let page_size = page_end - page_start;
+            page_region_list.push(if is_vertical {
+                CropRegion::new(0, page_start, self.width, page_size)
+            } else {
+                CropRegion::new(page_start, 0, page_size, self.height)
+            });
+            page_start = page_end;
+        }
+
+        Ok(page_region_list)
+    }
+
+    /**
+     * 在指定范围内查找内容最少的行（列），返回相对范围起点的位置
+     * 以相邻像素的差异衡量内容，留白处差异为 0，相同时优先选择靠后的位置
+     */
+    fn find_page_break(&self, search_start: u32, search_size: u32) -> Result<u32, String> {
+        let is_vertical = self.direction == ScrollDirection::Vertical;
+        let region = if is_vertical {
+            CropRegion::new(0, search_start, self.width, search_size)
+        } else {
+            CropRegion::new(search_start, 0, search_size, self.height)
+        };
+        let pixels = self.render_region(region)?;
+
+        let get_pixel = |line: u32, cross: u32| -> &[u8] {
+            let index = if is_vertical {
+                line * region.width + cross
+            } else {
+                cross * region.width + line
+            } as usize
+                * self.channel_count;
+
+            &pixels[index..index + self.channel_count]
+        };
+
+        let mut page_break = search_size - 1;
+        let mut min_score = u64::MAX;
+        for line in (0..search_size).rev() {
+            let score = (1..self.cross_side_size)
+                .map(|cross| {
+                    get_pixel(line, cross)
+                        .iter()
+                        .zip(get_pixel(line, cross - 1).iter())
+                        .map(|(a, b)| a.abs_diff(*b) as u64)
+                        .sum::<u64>()
+                })
+                .sum::<u64>();
+
+            if score < min_score {
+                min_score = score;
+                page_break = line;
+            }
+        }
+
+        Ok(page_break)
+    }
+
+    /**
+     * 按绘制顺序添加图片，并根据之前绘制的内容计算重叠区域的接缝
+     */
+    fn push_placement(
+        &mut self,
+        image: ExportImage<'a>,
+        scroll_position: i32,
+        cross_start: u32,
+        overlap: SeamOverlap,
+    ) -> Result<(), String> {
+        let line_count = self.get_line_count(&image);
+        let overlap = SeamOverlap {
+            size: overlap.size.min(line_count),
+            at_start: overlap.at_start,
+        };
+
+        let mut placement = ExportPlacement {
+            image,
+            scroll_position,
+            cross_start,
+            overlap,
+            overlap_weights: vec![],
+        };
+
+        if overlap.size > 0 {
+            placement.overlap_weights = match self.seam_mode {
+                ScrollSeamMode::Overwrite => vec![],
+                ScrollSeamMode::Feather => get_feather_weights(overlap),
+                ScrollSeamMode::MinDifference => {
+                    let line_diff_list = self.get_overlap_line_diff_list(&placement)?;
+                    get_min_difference_weights(overlap, &line_diff_list)
+                }
+            };
+        }
+
+        self.placements.push(placement);
+        Ok(())
+    }
+
+    /**
+     * 计算重叠区域内每一行（列）图片与已绘制内容的差异
+     */
+    fn get_overlap_line_diff_list(&self, placement: &ExportPlacement) -> Result<Vec<u64>, String> {
+        let (overlap_start, overlap_end) = placement
+            .overlap
+            .line_range(self.get_line_count(&placement.image));
+        let overlap_size = (overlap_end - overlap_start) as u32;
+        let is_vertical = self.direction == ScrollDirection::Vertical;
+
+        let (image_x, image_y, _, _) = self.get_placement_rect(placement);
+        let region = if is_vertical {
+            CropRegion::new(
+                0,
+                (image_y + overlap_start as i32) as u32,
+                self.cross_side_size,
+                overlap_size,
+            )
+        } else {
+            CropRegion::new(
+                (image_x + overlap_start as i32) as u32,
+                0,
+                overlap_size,
+                self.cross_side_size,
+            )
+        };
+
+        let canvas_pixels = self.render_region(region)?;
+
+        self.with_image(&placement.image, |image| {
+            let source_pixels = image.as_bytes();
+            let source_width = image.width() as usize;
+
+            (0..overlap_size as usize)
+                .map(|line_offset| {
+                    (0..self.cross_side_size as usize)
+                        .map(|cross| {
+                            let (canvas_index, source_index) = if is_vertical {
+                                (
+                                    line_offset * self.cross_side_size as usize + cross,
+                                    (overlap_start + line_offset) * source_width
+                                        + cross
+                                        + placement.cross_start as usize,
+                                )
+                            } else {
+                                (
+                                    cross * overlap_size as usize + line_offset,
+                                    (cross + placement.cross_start as usize) * source_width
+                                        + overlap_start
+                                        + line_offset,
+                                )
+                            };
+
+                            (0..self.channel_count)
+                                .map(|channel| {
+                                    canvas_pixels[canvas_index * self.channel_count + channel]
+                                        .abs_diff(
+                                            source_pixels
+                                                [source_index * self.channel_count + channel],
+                                        ) as u64
+                                })
+                                .sum::<u64>()
+                        })
+                        .sum()
+                })
+                .collect()
+        })
+    }
+}
+
+fn write_png_file(
+    layout: &ScrollExportLayout,
+    region: CropRegion,
+    file_path: &Path,
+) -> Result<(), String> {
+    let file = File::create(file_path).map_err(|e| {
+        format!(
+            "[write_png_file] Failed to create file: {} {}",
+            e,
+            file_path.display()
+        )
+    })?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), region.width, region.height);
+    encoder.set_color(if layout.has_alpha() {
+        png::ColorType::Rgba
+    } else {
+        png::ColorType::Rgb
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Fast);
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("[write_png_file] Failed to write header: {}", e))?;
+    let mut stream_writer = writer
+        .stream_writer()
+        .map_err(|e| format!("[write_png_file] Failed to create stream writer: {}", e))?;
+
+    // 逐段渲染并写入，内存占用只与段的大小有关
+    for band_start in (0..region.height).step_by(STREAM_BAND_SIZE as usize) {
+        let band_region = CropRegion::new(
+            region.x,
+            region.y + band_start,
+            region.width,
+            STREAM_BAND_SIZE.min(region.height - band_start),
+        );
+
+        stream_writer
+            .write_all(&layout.render_region(band_region)?)
+            .map_err(|e| format!("[write_png_file] Failed to write rows: {}", e))?;
+    }
+
+    stream_writer
+        .finish()
+        .map_err(|e| format!("[write_png_file] Failed to finish image: {}", e))?;
+
+    Ok(())
+}
+
+fn write_tiff_file(
+    layout: &ScrollExportLayout,
+    page_region_list: &[CropRegion],
+    file_path: &Path,
+) -> Result<(), String> {
+    let file = File::create(file_path).map_err(|e| {
+        format!(
+            "[write_tiff_file] Failed to create file: {} {}",
+            e,
+            file_path.display()
+        )
+    })?;
+
+    let mut encoder = tiff::encoder::TiffEncoder::new(BufWriter::new(file))
+        .map_err(|e| format!("[write_tiff_file] Failed to create encoder: {}", e))?;
+
+    for page_region in page_region_list {
+        let pixels = layout.render_region(*page_region)?;
+        let result = if layout.has_alpha() {
+            encoder.write_image::<tiff::encoder::colortype::RGBA8>(
+                page_region.width,
+                page_region.height,
+                &pixels,
+            )
+        } else {
+            encoder.write_image::<tiff::encoder::colortype::RGB8>(
+                page_region.width,
+                page_region.height,
+                &pixels,
+            )
+        };
+        result.map_err(|e| format!("[write_tiff_file] Failed to write page: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn write_pdf_file(
+    layout: &ScrollExportLayout,
+    page_region_list: &[CropRegion],
+    file_path: &Path,
+) -> Result<(), String> {
+    let file = File::create(file_path).map_err(|e| {
+        format!(
+            "[write_pdf_file] Failed to create file: {} {}",
+            e,
+            file_path.display()
+        )
+    })?;
+
+    let mut pdf_writer = ScrollPdfWriter::new(BufWriter::new(file), page_region_list.len())?;
+    for page_region in page_region_list {
+        // PDF 页面只写入 RGB 图片，透明区域显示为白色
+        pdf_writer.write_page(
+            page_region.width,
+            page_region.height,
+            &layout.render_region_rgb(*page_region)?,
+        )?;
+    }
+    pdf_writer.finish()?;
+
+    Ok(())
+}
+
+/// 分块文件的路径，如 image.png 的第一块为 image_001.png
+fn get_tile_file_path(file_path: &Path, tile_index: usize) -> PathBuf {
+    let file_stem = file_path
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    file_path.with_file_name(format!("{}_{:03}.png", file_stem, tile_index + 1))
+}
+
+impl ScrollScreenshotService {
+    /**
+     * 计算导出时各图片的布局，top 会覆盖 bottom，最先推入的图片优先级最低
+     */
+    pub fn get_export_layout(&self) -> Result<ScrollExportLayout<'_>, String> {
+        if self.top_image_list.is_empty() && self.bottom_image_list.is_empty() {
+            return Err(String::from(
+                "[ScrollScreenshotService::get_export_layout] No image to export",
+            ));
+        }
+
+        // 固定区域只在首尾各保留一份
+        let sticky_header_size = self.sticky_band.header_size as i32;
+        let sticky_footer_size = self.sticky_band.footer_size as i32;
+
+        // 各图片在滚动垂直方向上存在偏移时，只保留所有图片共同覆盖的区域
+        let (min_cross_offset, max_cross_offset) = self
+            .top_image_list
+            .iter()
+            .chain(self.bottom_image_list.iter())
+            .fold((0, 0), |(min_offset, max_offset), scroll_image| {
+                (
+                    min_offset.min(scroll_image.cross_offset),
+                    max_offset.max(scroll_image.cross_offset),
+                )
+            });
+        let image_cross_side_size = if self.current_direction == ScrollDirection::Vertical {
+            self.image_width as i32
+        } else {
+            self.image_height as i32
+        };
+        // 贴边区域（滚动条等）从每张图片中裁剪，对齐后首尾的图片恰好贴边
+        let scrollbar_band = if self.enable_scrollbar_trim {
+            self.scrollbar_band
+        } else {
+            ScrollbarBand::default()
+        };
+        let cross_side_size = image_cross_side_size
+            - (max_cross_offset - min_cross_offset)
+            - (scrollbar_band.start_size + scrollbar_band.end_size) as i32;
+        if cross_side_size <= 0 {
+            return Err(String::from(
+                "[ScrollScreenshotService::get_export_layout] Images do not overlap on the cross axis",
+            ));
+        }
+
+        // 计算最终图片尺寸
+        let total_scroll_side_size =
+            sticky_header_size + self.top_image_size + self.bottom_image_size + sticky_footer_size;
+        let (width, height) = if self.current_direction == ScrollDirection::Vertical {
+            (cross_side_size as u32, total_scroll_side_size as u32)
+        } else {
+            (total_scroll_side_size as u32, cross_side_size as u32)
+        };
+
+        // 任意一张图片带有透明度时，最终图片保留透明度
+        let has_alpha = self
+            .top_image_list
+            .iter()
+            .chain(self.bottom_image_list.iter())
+            .any(|scroll_image| scroll_image.image.has_alpha())
+            || self
+                .sticky_header_image
+                .iter()
+                .chain(self.sticky_footer_image.iter())
+                .any(|image| image.color().has_alpha());
+
+        let mut layout = ScrollExportLayout {
+            width,
+            height,
+            direction: self.current_direction,
+            cross_side_size: cross_side_size as u32,
+            channel_count: if has_alpha {
+                RGBA_CHANNEL_COUNT
+            } else {
+                RGB_CHANNEL_COUNT
+            },
+            seam_mode: self.seam_mode,
+            placements: Vec::with_capacity(
+                self.top_image_list.len() + self.bottom_image_list.len() + 2,
+            ),
+            decoded_frame_cache: Mutex::new(VecDeque::with_capacity(DECODED_FRAME_CACHE_SIZE + 1)),
+        };
+
+        let get_scroll_side_size = |image: &ScrollFrameImage| -> i32 {
+            if self.current_direction == ScrollDirection::Vertical {
+                image.height() as i32
+            } else {
+                image.width() as i32
+            }
+        };
+
+        // bottom 从 top 的末尾开始，图片头部与上一张图片重叠
+        let mut offset = sticky_header_size + self.top_image_size;
+        for scroll_image in self.bottom_image_list.iter() {
+            let overlay_size = scroll_image.overlay_size;
+
+            layout.push_placement(
+                ExportImage::Frame(&scroll_image.image),
+                offset - overlay_size,
+                (max_cross_offset - scroll_image.cross_offset) as u32 + scrollbar_band.start_size,
+                SeamOverlap {
+                    size: overlay_size.max(0) as usize,
+                    at_start: true,
+                },
+            )?;
+
+            offset += get_scroll_side_size(&scroll_image.image) - overlay_size;
+        }
+
+        // top 从同一位置向前排列，图片尾部与下一张图片重叠
+        let mut offset = sticky_header_size + self.top_image_size;
+        for scroll_image in self.top_image_list.iter() {
+            let overlay_size = scroll_image.overlay_size;
+            let actual_size = get_scroll_side_size(&scroll_image.image) + overlay_size;
+
+            layout.push_placement(
+                ExportImage::Frame(&scroll_image.image),
+                offset - actual_size,
+                (max_cross_offset - scroll_image.cross_offset) as u32 + scrollbar_band.start_size,
+                SeamOverlap {
+                    size: (-overlay_size).max(0) as usize,
+                    at_start: false,
+                },
+            )?;
+
+            offset -= actual_size;
+        }
+
+        // 固定区域取自首帧，首帧没有偏移
+        let no_overlap = SeamOverlap {
+            size: 0,
+            at_start: true,
+        };
+        if let Some(sticky_header_image) = &self.sticky_header_image {
+            layout.push_placement(
+                ExportImage::Image(sticky_header_image),
+                0,
+                max_cross_offset as u32 + scrollbar_band.start_size,
+                no_overlap,
+            )?;
+        }
+
+        if let Some(sticky_footer_image) = &self.sticky_footer_image {
+            layout.push_placement(
+                ExportImage::Image(sticky_footer_image),
+                total_scroll_side_size - sticky_footer_size,
+                max_cross_offset as u32 + scrollbar_band.start_size,
+                no_overlap,
+            )?;
+        }
+
+        Ok(layout)
+    }
+
+    /**
+     * 将拼接结果直接写入文件，不会在内存中生成整张图片
+     * 返回写入的文件列表，分块导出时为多个编号的文件，max_page_size 为 0 时不分块
+     */
+    pub fn export_to_file(
+        &self,
+        file_path: &Path,
+        export_format: ScrollExportFormat,
+        max_page_size: u32,
+    ) -> Result<Vec<PathBuf>, String> {
+        let layout = self.get_export_layout()?;
+
+        match export_format {
+            ScrollExportFormat::Png => {
+                write_png_file(
+                    &layout,
+                    CropRegion::new(0, 0, layout.width, layout.height),
+                    file_path,
+                )?;
+
+                Ok(vec![file_path.to_path_buf()])
+            }
+            ScrollExportFormat::PngTiles | ScrollExportFormat::PngPages => {
+                let tile_region_list = if export_format == ScrollExportFormat::PngPages {
+                    layout.get_smart_page_region_list(max_page_size)?
+                } else {
+                    layout.get_page_region_list(max_page_size)
+                };
+
+                let mut tile_file_path_list = vec![];
+                for (tile_index, tile_region) in tile_region_list.into_iter().enumerate() {
+                    let tile_file_path = get_tile_file_path(file_path, tile_index);
+                    write_png_file(&layout, tile_region, &tile_file_path)?;
+                    tile_file_path_list.push(tile_file_path);
+                }
+
+                Ok(tile_file_path_list)
+            }
+            ScrollExportFormat::MultiPageTiff => {
+                write_tiff_file(
+                    &layout,
+                    &layout.get_page_region_list(max_page_size),
+                    file_path,
+                )?;
+
+                Ok(vec![file_path.to_path_buf()])
+            }
+            ScrollExportFormat::Pdf => {
+                write_pdf_file(
+                    &layout,
+                    &layout.get_smart_page_region_list(max_page_size)?,
+                    file_path,
+                )?;
+
+                Ok(vec![file_path.to_path_buf()])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_matcher::ScrollMatcherType;
+    use crate::scroll_screenshot_stitch_service::ScrollStitchOptions;
+    use crate::scroll_screenshot_test_utils::{
+        TempDirectory, create_page, create_scroll_frames, create_text_page,
+    };
+
+    #[test]
+    fn test_export_to_file() {
+        let page = create_page(200, 900);
+        let frames = create_scroll_frames(&page, 300, 100);
+
+        let mut service = ScrollScreenshotService::new();
+        let options = ScrollStitchOptions {
+            seam_mode: ScrollSeamMode::Feather,
+            ..Default::default()
+        };
+        let expected_image = service.stitch_frames(&options, frames).unwrap().image;
+        // 图片全部溢出到临时目录后导出结果不变
+        service.set_frame_memory_budget(1);
+        assert!(
+            service
+                .bottom_image_list
+                .iter()
+                .all(|scroll_image| scroll_image.image.is_spilled())
+        );
+
+        let temp_directory = TempDirectory::new();
+        let output_dir = temp_directory.path();
+
+        // 逐段写入的结果与整张导出一致
+        let file_path_list = service
+            .export_to_file(&output_dir.join("image.png"), ScrollExportFormat::Png, 0)
+            .unwrap();
+        assert_eq!(file_path_list.len(), 1);
+        assert_eq!(
+            image::open(&file_path_list[0]).unwrap().to_rgb8().as_raw(),
+            expected_image.to_rgb8().as_raw()
+        );
+
+        // 分块文件依次拼接后与整张导出一致
+        let file_path_list = service
+            .export_to_file(
+                &output_dir.join("tile.png"),
+                ScrollExportFormat::PngTiles,
+                400,
+            )
+            .unwrap();
+        assert_eq!(file_path_list.len(), 3);
+        assert_eq!(file_path_list[0], output_dir.join("tile_001.png"));
+        let tile_pixels = file_path_list
+            .iter()
+            .flat_map(|file_path| image::open(file_path).unwrap().to_rgb8().into_raw())
+            .collect::<Vec<u8>>();
+        assert_eq!(&tile_pixels, expected_image.to_rgb8().as_raw());
+
+        let file_path_list = service
+            .export_to_file(
+                &output_dir.join("image.tiff"),
+                ScrollExportFormat::MultiPageTiff,
+                400,
+            )
+            .unwrap();
+        let mut decoder =
+            tiff::decoder::Decoder::new(File::open(&file_path_list[0]).unwrap()).unwrap();
+        let mut page_count = 1;
+        while decoder.more_images() {
+            decoder.next_image().unwrap();
+            page_count += 1;
+        }
+        assert_eq!(page_count, 3);
+    }
+
+    #[test]
+    fn test_export_with_alpha() {
+        // 左右两侧为圆角窗口外的透明区域
+        let page = image::RgbaImage::from_fn(200, 900, {
+            let page = create_page(200, 900);
+            move |x, y| {
+                let image::Rgb([r, g, b]) = *page.get_pixel(x, y);
+                let alpha = if !(8..192).contains(&x) {
+                    (x % 8 * 32) as u8
+                } else {
+                    255
+                };
+                image::Rgba([r, g, b, alpha])
+            }
+        });
+        let get_frames = |page: &image::RgbaImage| -> Vec<DynamicImage> {
+            (0..7)
+                .map(|index| {
+                    DynamicImage::ImageRgba8(
+                        image::imageops::crop_imm(page, 0, index * 100, 200, 300).to_image(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut service = ScrollScreenshotService::new();
+        let image = service
+            .stitch_frames(&ScrollStitchOptions::default(), get_frames(&page))
+            .unwrap()
+            .image;
+        assert!(service.get_export_layout().unwrap().has_alpha());
+        assert_eq!(image.color(), image::ColorType::Rgba8);
+        assert_eq!(image.as_bytes(), page.as_raw());
+
+        let temp_directory = TempDirectory::new();
+        let file_path_list = service
+            .export_to_file(
+                &temp_directory.path().join("image.png"),
+                ScrollExportFormat::Png,
+                0,
+            )
+            .unwrap();
+        let exported_image = image::open(&file_path_list[0]).unwrap();
+        assert_eq!(exported_image.color(), image::ColorType::Rgba8);
+        assert_eq!(exported_image.as_bytes(), page.as_raw());
+
+        // 完全不透明或没有填写透明度的图片按 RGB 导出
+        for alpha in [0, 255] {
+            let mut opaque_page = page.clone();
+            opaque_page.pixels_mut().for_each(|pixel| pixel[3] = alpha);
+
+            let mut service = ScrollScreenshotService::new();
+            let image = service
+                .stitch_frames(&ScrollStitchOptions::default(), get_frames(&opaque_page))
+                .unwrap()
+                .image;
+            assert_eq!(image.color(), image::ColorType::Rgb8);
+            assert_eq!(
+                image.as_bytes(),
+                DynamicImage::ImageRgba8(opaque_page).to_rgb8().as_raw()
+            );
+        }
+    }
+
+    #[test]
+    fn test_export_smart_pages() {
+        let page = create_text_page(320, 1200);
+        let frames = create_scroll_frames(&page, 300, 100);
+
+        let mut service = ScrollScreenshotService::new();
+        let options = ScrollStitchOptions {
+            matcher_type: ScrollMatcherType::RowHash,
+            ..Default::default()
+        };
+        let expected_image = service
+            .stitch_frames(&options, frames)
+            .unwrap()
+            .image
+            .to_rgb8();
+
+        let page_region_list = service
+            .get_export_layout()
+            .unwrap()
+            .get_smart_page_region_list(250)
+            .unwrap();
+        assert_eq!(
+            page_region_list
+                .iter()
+                .map(|page_region| page_region.height)
+                .sum::<u32>(),
+            expected_image.height()
+        );
+
+        // 每页不超过最大尺寸，且分页位置都在留白处
+        for page_region in page_region_list.iter() {
+            assert!(page_region.height <= 250);
+            if page_region.y > 0 {
+                assert!(
+                    (0..expected_image.width())
+                        .all(|x| expected_image.get_pixel(x, page_region.y).0 == [255, 255, 255])
+                );
+            }
+        }
+
+        let temp_directory = TempDirectory::new();
+        let output_dir = temp_directory.path();
+
+        let file_path_list = service
+            .export_to_file(
+                &output_dir.join("page.png"),
+                ScrollExportFormat::PngPages,
+                250,
+            )
+            .unwrap();
+        assert_eq!(file_path_list.len(), page_region_list.len());
+        let page_pixels = file_path_list
+            .iter()
+            .flat_map(|file_path| image::open(file_path).unwrap().to_rgb8().into_raw())
+            .collect::<Vec<u8>>();
+        assert_eq!(&page_pixels, expected_image.as_raw());
+
+        let file_path_list = service
+            .export_to_file(&output_dir.join("page.pdf"), ScrollExportFormat::Pdf, 250)
+            .unwrap();
+        let pdf_content = std::fs::read(&file_path_list[0]).unwrap();
+        assert!(pdf_content.starts_with(b"%PDF-"));
+        assert!(pdf_content.ends_with(b"%%EOF\n"));
+
+        // 最大尺寸为 0 时不分页
+        let layout = service.get_export_layout().unwrap();
+        assert_eq!(
+            layout.get_page_region_list(0),
+            vec![CropRegion::new(0, 0, layout.width, layout.height)]
+        );
+        assert_eq!(
+            layout.get_smart_page_region_list(0).unwrap(),
+            vec![CropRegion::new(0, 0, layout.width, layout.height)]
+        );
+        let file_path_list = service
+            .export_to_file(
+                &output_dir.join("single.png"),
+                ScrollExportFormat::PngTiles,
+                0,
+            )
+            .unwrap();
+        assert_eq!(file_path_list.len(), 1);
+        assert_eq!(
+            image::open(&file_path_list[0]).unwrap().to_rgb8().as_raw(),
+            expected_image.as_raw()
+        );
+        assert_eq!(
+            pdf_content
+                .windows(b"/Type /Page ".len())
+                .filter(|window| *window == b"/Type /Page ")
+                .count(),
+            page_region_list.len()
+        );
+    }
+}
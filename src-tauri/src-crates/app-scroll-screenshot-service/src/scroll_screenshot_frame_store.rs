@@ -0,0 +1,313 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use image::DynamicImage;
+
+/// 用于区分同一进程内不同存储的溢出目录
+static SPILL_DIRECTORY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * 统一拼接流程处理的图片格式，带有透明区域的图片保留为 RGBA，其余转为 RGB
+ * 部分截图接口不填写透明度，透明度全为 0 时视为不透明
+ */
+pub fn normalize_image_color(image: DynamicImage) -> DynamicImage {
+    if !image.color().has_alpha() {
+        return match image {
+            DynamicImage::ImageRgb8(image) => DynamicImage::ImageRgb8(image),
+            image => DynamicImage::ImageRgb8(image.to_rgb8()),
+        };
+    }
+
+    let rgba_image = match image {
+        DynamicImage::ImageRgba8(image) => image,
+        image => image.to_rgba8(),
+    };
+
+    let has_translucent_pixel = rgba_image.pixels().any(|pixel| pixel[3] < u8::MAX);
+    let has_visible_pixel = rgba_image.pixels().any(|pixel| pixel[3] > 0);
+    if has_translucent_pixel && has_visible_pixel {
+        DynamicImage::ImageRgba8(rgba_image)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba_image).to_rgb8())
+    }
+}
+
+enum ScrollFrameData {
+    /// QOI 压缩后的 RGB 或 RGBA 像素
+    Compressed(Vec<u8>),
+    /// 超出内存预算后写入临时目录的 QOI 文件
+    Spilled(PathBuf),
+    /// 压缩失败时保留原图
+    Raw(DynamicImage),
+}
+
+/**
+ * 拼接后保留的单帧图片
+ * 长时间滚动截图会保留大量帧，解码后的图片只在导出或生成缩略图时临时创建
+ */
+pub struct ScrollFrameImage {
+    width: u32,
+    height: u32,
+    /// 是否保留了透明度
+    has_alpha: bool,
+    /// 创建顺序，超出内存预算时优先溢出较早的帧
+    sequence: u64,
+    data: ScrollFrameData,
+}
+
+impl ScrollFrameImage {
+    fn new(image: &DynamicImage, sequence: u64) -> Self {
+        // 只有 RGBA 图片保留透明度，其余按 RGB 保存
+        let (image, has_alpha) = match image {
+            DynamicImage::ImageRgba8(_) => (Cow::Borrowed(image), true),
+            DynamicImage::ImageRgb8(_) => (Cow::Borrowed(image), false),
+            image => (Cow::Owned(DynamicImage::ImageRgb8(image.to_rgb8())), false),
+        };
+
+        let data = match qoi::encode_to_vec(image.as_bytes(), image.width(), image.height()) {
+            Ok(data) => ScrollFrameData::Compressed(data),
+            Err(_) => ScrollFrameData::Raw(image.as_ref().clone()),
+        };
+
+        Self {
+            width: image.width(),
+            height: image.height(),
+            has_alpha,
+            sequence,
+            data,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.has_alpha
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.data, ScrollFrameData::Spilled(_))
+    }
+
+    /**
+     * 在内存中占用的字节数，溢出到磁盘的帧不计算
+     */
+    pub fn memory_size(&self) -> usize {
+        match &self.data {
+            ScrollFrameData::Compressed(data) => data.len(),
+            ScrollFrameData::Spilled(_) => 0,
+            ScrollFrameData::Raw(image) => image.as_bytes().len(),
+        }
+    }
+
+    /**
+     * 解码为 RGB 图片，保留了透明度时解码为 RGBA 图片
+     */
+    pub fn decode(&self) -> Result<DynamicImage, String> {
+        let data = match &self.data {
+            ScrollFrameData::Compressed(data) => Cow::Borrowed(data),
+            ScrollFrameData::Spilled(file_path) => {
+                Cow::Owned(std::fs::read(file_path).map_err(|e| {
+                    format!(
+                        "[ScrollFrameImage::decode] Failed to read frame: {} {}",
+                        e,
+                        file_path.display()
+                    )
+                })?)
+            }
+            ScrollFrameData::Raw(image) => return Ok(image.clone()),
+        };
+
+        let (header, pixels) = qoi::decode_to_vec(data.as_slice())
+            .map_err(|e| format!("[ScrollFrameImage::decode] Failed to decode frame: {}", e))?;
+
+        let image = if header.channels.is_rgba() {
+            image::RgbaImage::from_raw(header.width, header.height, pixels)
+                .map(DynamicImage::ImageRgba8)
+        } else {
+            image::RgbImage::from_raw(header.width, header.height, pixels)
+                .map(DynamicImage::ImageRgb8)
+        };
+
+        image.ok_or_else(|| String::from("[ScrollFrameImage::decode] Invalid frame size"))
+    }
+
+    /**
+     * 将压缩后的数据写入目录并释放内存
+     */
+    fn spill(&mut self, directory: &Path) -> Result<(), String> {
+        let ScrollFrameData::Compressed(data) = &self.data else {
+            return Ok(());
+        };
+
+        let file_path = directory.join(format!("frame_{:08}.qoi", self.sequence));
+        std::fs::write(&file_path, data).map_err(|e| {
+            format!(
+                "[ScrollFrameImage::spill] Failed to write frame: {} {}",
+                e,
+                file_path.display()
+            )
+        })?;
+
+        self.data = ScrollFrameData::Spilled(file_path);
+        Ok(())
+    }
+}
+
+impl Drop for ScrollFrameImage {
+    fn drop(&mut self) {
+        if let ScrollFrameData::Spilled(file_path) = &self.data {
+            let _ = std::fs::remove_file(file_path);
+        }
+    }
+}
+
+/**
+ * 创建压缩帧，并在超出内存预算时将较早的帧溢出到临时目录
+ */
+pub struct ScrollFrameStore {
+    /// 内存中压缩帧的预算（字节），为 0 时不限制
+    memory_budget: usize,
+    next_sequence: u64,
+    /// 溢出目录，第一次溢出时创建
+    spill_directory: Option<PathBuf>,
+}
+
+impl ScrollFrameStore {
+    pub fn new() -> Self {
+        Self {
+            memory_budget: 0,
+            next_sequence: 0,
+            spill_directory: None,
+        }
+    }
+
+    pub fn memory_budget(&self) -> usize {
+        self.memory_budget
+    }
+
+    pub fn set_memory_budget(&mut self, memory_budget: usize) {
+        self.memory_budget = memory_budget;
+    }
+
+    pub fn create_frame(&mut self, image: &DynamicImage) -> ScrollFrameImage {
+        let frame = ScrollFrameImage::new(image, self.next_sequence);
+        self.next_sequence += 1;
+
+        frame
+    }
+
+    fn get_spill_directory(&mut self) -> Result<PathBuf, String> {
+        if let Some(spill_directory) = &self.spill_directory {
+            return Ok(spill_directory.clone());
+        }
+
+        let spill_directory = std::env::temp_dir().join(format!(
+            "snow-shot-scroll-screenshot-{}-{}",
+            std::process::id(),
+            SPILL_DIRECTORY_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&spill_directory).map_err(|e| {
+            format!(
+                "[ScrollFrameStore::get_spill_directory] Failed to create directory: {} {}",
+                e,
+                spill_directory.display()
+            )
+        })?;
+
+        self.spill_directory = Some(spill_directory.clone());
+        Ok(spill_directory)
+    }
+
+    /**
+     * 内存占用超出预算时，按创建顺序将较早的帧溢出到临时目录
+     */
+    pub fn enforce_memory_budget<'a>(
+        &mut self,
+        frame_list: impl Iterator<Item = &'a mut ScrollFrameImage>,
+    ) -> Result<(), String> {
+        if self.memory_budget == 0 {
+            return Ok(());
+        }
+
+        let mut frame_list = frame_list.collect::<Vec<_>>();
+        let mut memory_size = frame_list
+            .iter()
+            .map(|frame| frame.memory_size())
+            .sum::<usize>();
+        if memory_size <= self.memory_budget {
+            return Ok(());
+        }
+
+        let spill_directory = self.get_spill_directory()?;
+        frame_list.sort_by_key(|frame| frame.sequence);
+        for frame in frame_list {
+            if memory_size <= self.memory_budget {
+                break;
+            }
+
+            let frame_memory_size = frame.memory_size();
+            frame.spill(&spill_directory)?;
+            if frame.is_spilled() {
+                memory_size -= frame_memory_size;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ScrollFrameStore {
+    fn drop(&mut self) {
+        if let Some(spill_directory) = &self.spill_directory {
+            let _ = std::fs::remove_dir_all(spill_directory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, create_scroll_frames};
+
+    #[test]
+    fn test_frame_store_spill() {
+        let image_list = create_scroll_frames(&create_page(120, 320), 80, 80);
+
+        let mut store = ScrollFrameStore::new();
+        let mut frame_list = image_list
+            .iter()
+            .map(|image| store.create_frame(image))
+            .collect::<Vec<_>>();
+
+        // 不限制预算时全部保留在内存中
+        store.enforce_memory_budget(frame_list.iter_mut()).unwrap();
+        assert!(frame_list.iter().all(|frame| !frame.is_spilled()));
+
+        let frame_memory_size = frame_list[0].memory_size();
+        store.set_memory_budget(frame_memory_size * 2 + 1);
+        store.enforce_memory_budget(frame_list.iter_mut()).unwrap();
+
+        // 较早的帧被溢出，解码结果不变
+        assert!(frame_list[0].is_spilled());
+        assert!(!frame_list[3].is_spilled());
+        for (image, frame) in image_list.iter().zip(frame_list.iter()) {
+            assert_eq!(frame.decode().unwrap().as_bytes(), image.as_bytes());
+        }
+
+        let spill_directory = store.spill_directory.clone().unwrap();
+        drop(frame_list);
+        drop(store);
+        assert!(!spill_directory.exists());
+    }
+}
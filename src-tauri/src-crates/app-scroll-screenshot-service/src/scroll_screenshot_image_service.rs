@@ -1,16 +1,61 @@
 use image::DynamicImage;
+use image::imageops::FilterType;
+use serde::Serialize;
 use std::collections::VecDeque;
 
 use crate::scroll_screenshot_service::ScrollImageList;
 
+/// 队列中待处理的图片达到该数量时，开始丢弃和队尾几乎相同的图片
+const BACKPRESSURE_IMAGE_COUNT: usize = 2;
+/// 队列中待处理图片的上限，达到上限后丢弃新的图片
+const MAX_IMAGE_COUNT: usize = 16;
+/// 用于比较图片是否几乎相同的缩略图尺寸
+const SIGNATURE_SIZE: u32 = 32;
+/// 缩略图平均像素差不超过该值时认为几乎相同
+const MAX_DUPLICATE_DIFF: f32 = 1.5;
+
 pub struct ScrollScreenshotImage {
     pub image: DynamicImage,
     pub direction: ScrollImageList,
+    /// 灰度缩略图，用于丢弃几乎相同的图片
+    signature: Vec<u8>,
+}
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub enum ScrollImagePushResult {
+    /// 已加入队列
+    Queued,
+    /// 处理不及时，且和队尾图片几乎相同，已丢弃
+    DroppedDuplicate,
+    /// 队列已满，已丢弃
+    DroppedQueueFull,
+}
+
+fn get_image_signature(image: &DynamicImage) -> Vec<u8> {
+    image
+        .resize_exact(SIGNATURE_SIZE, SIGNATURE_SIZE, FilterType::Triangle)
+        .to_luma8()
+        .into_raw()
+}
+
+fn is_duplicate_signature(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() || a.is_empty() {
+        return false;
+    }
+
+    let diff_sum: u32 = a
+        .iter()
+        .zip(b.iter())
+        .map(|(a, b)| a.abs_diff(*b) as u32)
+        .sum();
+
+    diff_sum as f32 / a.len() as f32 <= MAX_DUPLICATE_DIFF
 }
 
 /**
  * 将截图和处理截图分开处理
  * 通过短时间内多次截图来提高滚动截图的响应速度和可靠性
+ * 处理跟不上截图时，丢弃和队尾几乎相同的图片，避免队列无限增长
  */
 pub struct ScrollScreenshotImageService {
     image_queue: VecDeque<ScrollScreenshotImage>,
@@ -26,9 +71,34 @@ impl ScrollScreenshotImageService {
     /**
      * 将截图添加到待处理队列尾部
      */
-    pub fn push_image(&mut self, image: DynamicImage, direction: ScrollImageList) {
-        self.image_queue
-            .push_back(ScrollScreenshotImage { image, direction });
+    pub fn push_image(
+        &mut self,
+        image: DynamicImage,
+        direction: ScrollImageList,
+    ) -> ScrollImagePushResult {
+        if self.image_queue.len() >= MAX_IMAGE_COUNT {
+            return ScrollImagePushResult::DroppedQueueFull;
+        }
+
+        let signature = get_image_signature(&image);
+
+        if self.image_queue.len() >= BACKPRESSURE_IMAGE_COUNT {
+            if let Some(last_image) = self.image_queue.back() {
+                if last_image.direction == direction
+                    && is_duplicate_signature(&last_image.signature, &signature)
+                {
+                    return ScrollImagePushResult::DroppedDuplicate;
+                }
+            }
+        }
+
+        self.image_queue.push_back(ScrollScreenshotImage {
+            image,
+            direction,
+            signature,
+        });
+
+        ScrollImagePushResult::Queued
     }
 
     /**
@@ -50,3 +120,55 @@ impl ScrollScreenshotImageService {
         self.image_queue.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, crop_frame};
+
+    /// 页面滚动 offset 像素后的截图
+    fn create_image(offset: u32) -> DynamicImage {
+        crop_frame(&create_page(200, 1300), 0, offset, 200, 300)
+    }
+
+    #[test]
+    fn test_push_image_backpressure() {
+        let mut service = ScrollScreenshotImageService::new();
+
+        // 队列较短时不丢弃相同的图片
+        for _ in 0..BACKPRESSURE_IMAGE_COUNT {
+            assert_eq!(
+                service.push_image(create_image(0), ScrollImageList::Bottom),
+                ScrollImagePushResult::Queued
+            );
+        }
+
+        assert_eq!(
+            service.push_image(create_image(0), ScrollImageList::Bottom),
+            ScrollImagePushResult::DroppedDuplicate
+        );
+        // 方向不同时保留
+        assert_eq!(
+            service.push_image(create_image(0), ScrollImageList::Top),
+            ScrollImagePushResult::Queued
+        );
+        assert_eq!(
+            service.push_image(create_image(5), ScrollImageList::Top),
+            ScrollImagePushResult::Queued
+        );
+
+        for offset in 0..(MAX_IMAGE_COUNT - service.image_count()) {
+            assert_eq!(
+                service.push_image(create_image(offset as u32 * 5 + 10), ScrollImageList::Top),
+                ScrollImagePushResult::Queued
+            );
+        }
+        assert_eq!(
+            service.push_image(create_image(1000), ScrollImageList::Top),
+            ScrollImagePushResult::DroppedQueueFull
+        );
+
+        service.pop_image();
+        assert_eq!(service.image_count(), MAX_IMAGE_COUNT - 1);
+    }
+}
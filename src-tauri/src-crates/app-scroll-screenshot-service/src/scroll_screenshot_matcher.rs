@@ -0,0 +1,194 @@
+use std::any::Any;
+
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_corner_matcher::CornerScrollMatcher;
+use crate::scroll_screenshot_dynamic_mask::ScrollDynamicMask;
+use crate::scroll_screenshot_phase_matcher::PhaseCorrelationScrollMatcher;
+use crate::scroll_screenshot_row_hash_matcher::RowHashScrollMatcher;
+use crate::scroll_screenshot_service::{ScrollDirection, ScrollOffset};
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ScrollMatcherType {
+    /// 特征点匹配，适合内容丰富的页面
+    Corner = 0,
+    /// 相位相关，利用整张图片的结构，适合特征点较少的页面
+    PhaseCorrelation = 1,
+    /// 逐行（列）哈希对齐，适合大面积留白或纯色的页面
+    RowHash = 2,
+}
+
+/// 匹配器从图片中提取的特征，只能由提取它的匹配器使用
+pub trait ScrollFeatures: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// 匹配新图片时的限制条件
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollMatchConstraint {
+    /// 同一内容在新图片与索引图片中滚动方向上的最小位置差（新图片减索引图片）
+    /// 负数时位置差不能大于该值，正数时不能小于该值，否则新图片没有带来新的区域
+    pub min_diff: i32,
+    /// 滚动垂直方向上允许的最大偏移（缩放后的像素）
+    pub cross_axis_tolerance: i32,
+}
+
+impl ScrollMatchConstraint {
+    /// 位置差是否能带来新的区域
+    pub fn accepts_diff(&self, diff: i32) -> bool {
+        !((self.min_diff < 0 && self.min_diff < diff)
+            || (self.min_diff > 0 && self.min_diff > diff))
+    }
+}
+
+/// 匹配结果的可信程度
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub struct ScrollMatchQuality {
+    /// 置信度，范围 0 ~ 1，不同匹配方式的计算方法不同，只适合同一匹配方式之间比较
+    pub confidence: f32,
+    /// 支持该偏移的特征数量，如一致的特征点或线的数量
+    pub inlier_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollMatchResult {
+    /// 匹配成功，偏移为同一内容在索引图片中的位置减去在新图片中的位置（缩放后的图片坐标）
+    Matched(ScrollOffset, ScrollMatchQuality),
+    /// 新图片与已有内容一致，没有带来新的区域
+    NoNewArea,
+    /// 没有匹配到已有内容
+    NotMatched,
+}
+
+/**
+ * 计算新图片相对索引图片的偏移
+ * 图片均为缩放后的灰度图，只有滚动垂直方向被缩放
+ */
+pub trait ScrollMatcher: Send + Sync {
+    /// 提取图片特征，忽略掩码覆盖的区域，图片没有可用于匹配的特征时返回 None
+    fn extract_features(
+        &mut self,
+        gray_image: &GrayImage,
+        mask: &ScrollDynamicMask,
+    ) -> Option<Box<dyn ScrollFeatures>>;
+
+    /// 将图片特征转换为索引，例如建立近似最近邻索引
+    fn build_index_features(&self, features: Box<dyn ScrollFeatures>) -> Box<dyn ScrollFeatures> {
+        features
+    }
+
+    /// 匹配新图片与索引
+    fn match_features(
+        &self,
+        index_features: &dyn ScrollFeatures,
+        features: &dyn ScrollFeatures,
+        constraint: &ScrollMatchConstraint,
+    ) -> ScrollMatchResult;
+}
+
+pub fn downcast_features<T: 'static>(features: &dyn ScrollFeatures) -> Option<&T> {
+    features.as_any().downcast_ref::<T>()
+}
+
+pub fn create_scroll_matcher(
+    matcher_type: ScrollMatcherType,
+    direction: ScrollDirection,
+    corner_threshold: u8,
+    descriptor_patch_size: usize,
+) -> Box<dyn ScrollMatcher> {
+    match matcher_type {
+        ScrollMatcherType::Corner => Box::new(CornerScrollMatcher::new(
+            direction,
+            corner_threshold,
+            descriptor_patch_size,
+        )),
+        ScrollMatcherType::PhaseCorrelation => {
+            Box::new(PhaseCorrelationScrollMatcher::new(direction))
+        }
+        ScrollMatcherType::RowHash => Box::new(RowHashScrollMatcher::new(direction)),
+    }
+}
+
+/**
+ * 按滚动方向排列的灰度像素，每一行（列）为一条线
+ * 垂直滚动时与图片一致，水平滚动时为转置后的图片
+ */
+pub struct ScrollLines {
+    /// 线的数量（滚动方向尺寸）
+    pub line_count: usize,
+    /// 每条线的像素数（滚动垂直方向尺寸）
+    pub line_size: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl ScrollLines {
+    pub fn new(gray_image: &GrayImage, direction: ScrollDirection) -> Self {
+        let width = gray_image.width() as usize;
+        let height = gray_image.height() as usize;
+
+        if direction == ScrollDirection::Vertical {
+            return Self {
+                line_count: height,
+                line_size: width,
+                pixels: gray_image.as_raw().clone(),
+            };
+        }
+
+        let source_pixels = gray_image.as_raw();
+        let mut pixels = vec![0; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                pixels[x * height + y] = source_pixels[y * width + x];
+            }
+        }
+
+        Self {
+            line_count: width,
+            line_size: height,
+            pixels,
+        }
+    }
+
+    pub fn line(&self, line: usize) -> &[u8] {
+        &self.pixels[line * self.line_size..(line + 1) * self.line_size]
+    }
+
+    /**
+     * 计算两组线在给定偏移下重叠区域的平均像素差
+     * 偏移为同一内容在 index_lines 中的位置减去在当前线中的位置，重叠不足 min_overlap_size 条线时返回 None
+     */
+    pub fn get_overlap_diff(
+        &self,
+        index_lines: &ScrollLines,
+        scroll_offset: i32,
+        cross_offset: i32,
+        min_overlap_size: usize,
+    ) -> Option<f32> {
+        let line_count = self.line_count as i32;
+        let line_size = self.line_size as i32;
+
+        let line_start = (-scroll_offset).max(0);
+        let line_end = line_count.min(index_lines.line_count as i32 - scroll_offset);
+        let cross_start = (-cross_offset).max(0);
+        let cross_end = line_size.min(index_lines.line_size as i32 - cross_offset);
+        if line_end - line_start < min_overlap_size.max(1) as i32 || cross_start >= cross_end {
+            return None;
+        }
+
+        let mut diff_sum: u64 = 0;
+        for line in line_start..line_end {
+            let pixels = &self.line(line as usize)[cross_start as usize..cross_end as usize];
+            let index_pixels = &index_lines.line((line + scroll_offset) as usize)
+                [(cross_start + cross_offset) as usize..(cross_end + cross_offset) as usize];
+
+            diff_sum += pixels
+                .iter()
+                .zip(index_pixels.iter())
+                .map(|(pixel, index_pixel)| pixel.abs_diff(*index_pixel) as u64)
+                .sum::<u64>();
+        }
+
+        Some(diff_sum as f32 / ((line_end - line_start) * (cross_end - cross_start)) as f32)
+    }
+}
@@ -0,0 +1,668 @@
+use hora::core::ann_index::ANNIndex;
+use hora::core::metrics::Metric;
+use hora::index::{hnsw_idx::HNSWIndex, hnsw_params::HNSWParams};
+use image::{DynamicImage, GenericImageView, GrayImage};
+use imageproc::corners;
+use rayon::prelude::*;
+use snow_shot_app_shared::ElementRect;
+use std::collections::{HashMap, HashSet};
+
+use crate::scroll_screenshot_corner_matcher::{compute_descriptor, euclidean_distance};
+use crate::scroll_screenshot_frame_store::{ScrollFrameImage, ScrollFrameStore};
+use crate::scroll_screenshot_service::ScrollOffset;
+use crate::scroll_screenshot_stitch_service::ScrollStitchFrameStatus;
+
+/// 已放置到画布上的帧
+pub struct PanoramaFrame {
+    /// 帧原图，压缩后保存
+    pub image: ScrollFrameImage,
+    /// 帧左上角在画布上的位置（相对首帧）
+    pub position: ScrollOffset,
+    /// 特征点（缩放后的坐标）
+    pub corners: Vec<ScrollOffset>,
+    pub descriptors: Vec<Vec<f32>>,
+    pub ann_index: HNSWIndex<f32, usize>,
+}
+
+/**
+ * 二维全景拼接，适用于需要上下左右平移的表格、地图和流程图
+ * 每帧按相对首帧的 (x, y) 偏移放置到画布上，并按网格记录已覆盖的区域
+ * 只保留带来新区域的帧，来回平移时帧列表不会持续增长
+ */
+pub struct ScrollPanoramaService {
+    /// 已放置的帧，按放置顺序排列，后放置的帧覆盖先放置的帧
+    pub frame_list: Vec<PanoramaFrame>,
+    /// 图片宽度
+    pub image_width: u32,
+    /// 图片高度
+    pub image_height: u32,
+    /// 图片缩放
+    pub image_scale: f32,
+    /// 缩放的图片宽度
+    pub image_dst_width: u32,
+    /// 缩放的图片高度
+    pub image_dst_height: u32,
+    /// 采样率
+    pub sample_rate: f32,
+    /// 最小采样尺寸
+    pub min_sample_size: u32,
+    /// 最大采样尺寸
+    pub max_sample_size: u32,
+    /// 特征点阈值
+    pub corner_threshold: u8,
+    /// 描述符块大小
+    pub descriptor_patch_size: usize,
+    /// 覆盖网格的边长
+    pub tile_size: u32,
+    /// 已被某一帧完整覆盖的网格
+    pub covered_tile_set: HashSet<(i32, i32)>,
+    /// 所有帧的外接矩形
+    pub bounds: ElementRect,
+    /// 压缩保存帧原图，超出内存预算时溢出到临时目录
+    pub frame_store: ScrollFrameStore,
+}
+
+impl ScrollPanoramaService {
+    pub fn new() -> Self {
+        Self {
+            frame_list: vec![],
+            image_width: 0,
+            image_height: 0,
+            image_scale: 1.0,
+            image_dst_width: 0,
+            image_dst_height: 0,
+            sample_rate: 0.0,
+            min_sample_size: 0,
+            max_sample_size: 0,
+            corner_threshold: 64,
+            descriptor_patch_size: 9,
+            tile_size: 64,
+            covered_tile_set: HashSet::new(),
+            bounds: ElementRect {
+                min_x: 0,
+                min_y: 0,
+                max_x: 0,
+                max_y: 0,
+            },
+            frame_store: ScrollFrameStore::new(),
+        }
+    }
+
+    pub fn init(
+        &mut self,
+        sample_rate: f32,
+        min_sample_size: u32,
+        max_sample_size: u32,
+        corner_threshold: u8,
+        descriptor_patch_size: usize,
+        tile_size: u32,
+    ) {
+        self.sample_rate = sample_rate;
+        self.min_sample_size = min_sample_size;
+        self.max_sample_size = max_sample_size;
+        self.corner_threshold = corner_threshold;
+        self.descriptor_patch_size = descriptor_patch_size;
+        self.tile_size = tile_size.max(1);
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.frame_list.clear();
+        self.image_width = 0;
+        self.image_height = 0;
+        self.covered_tile_set.clear();
+        self.bounds = ElementRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: 0,
+            max_y: 0,
+        };
+    }
+
+    /**
+     * 设置压缩帧的内存预算（字节），超出后较早的帧会溢出到临时目录，为 0 时不限制
+     */
+    pub fn set_frame_memory_budget(&mut self, frame_memory_budget: usize) {
+        self.frame_store.set_memory_budget(frame_memory_budget);
+        self.enforce_frame_memory_budget();
+    }
+
+    fn enforce_frame_memory_budget(&mut self) {
+        let frame_list = self.frame_list.iter_mut().map(|frame| &mut frame.image);
+
+        // 溢出失败时帧保留在内存中，不影响拼接
+        if let Err(e) = self.frame_store.enforce_memory_budget(frame_list) {
+            log::warn!("[ScrollPanoramaService::enforce_frame_memory_budget] {}", e);
+        }
+    }
+
+    fn get_descriptor_size(&self) -> usize {
+        self.descriptor_patch_size & !1
+    }
+
+    fn init_image_size(&mut self, image_width: u32, image_height: u32) {
+        self.image_width = image_width;
+        self.image_height = image_height;
+
+        // 两个方向都会移动，按短边计算缩放
+        let image_scale_side_size = image_width.min(image_height) as f32;
+        let target_side_size = (image_scale_side_size * self.sample_rate)
+            .min(self.max_sample_size as f32)
+            .max(self.min_sample_size as f32);
+
+        self.image_scale = (target_side_size / image_scale_side_size).min(1.0);
+        self.image_dst_width = ((image_width as f32 * self.image_scale) as u32).max(1);
+        self.image_dst_height = ((image_height as f32 * self.image_scale) as u32).max(1);
+    }
+
+    fn get_gray_image(&self, luma_image: &GrayImage) -> GrayImage {
+        if self.image_scale >= 1.0 {
+            return luma_image.clone();
+        }
+
+        // 两个方向都存在亚像素偏移，使用插值缩放让特征描述更稳定
+        image::imageops::resize(
+            luma_image,
+            self.image_dst_width,
+            self.image_dst_height,
+            image::imageops::FilterType::Triangle,
+        )
+    }
+
+    /**
+     * 估计新帧相对已放置帧的偏移（缩放后的坐标）
+     * 缩放会让同一偏移落在相邻的几个值上，所以按 3x3 邻域统计
+     */
+    fn estimate_offset(
+        &self,
+        frame: &PanoramaFrame,
+        image_descriptors: &[Vec<f32>],
+        image_corners: &[ScrollOffset],
+    ) -> Option<(f32, f32)> {
+        let offsets: Vec<(i32, i32)> = image_descriptors
+            .par_iter()
+            .enumerate()
+            .filter_map(|(i, descriptor)| {
+                let search_result = frame.ann_index.search(descriptor, 1);
+                if search_result.is_empty() {
+                    return None;
+                }
+
+                let idx1 = search_result[0];
+                let dist = euclidean_distance(&frame.descriptors[idx1], descriptor);
+                if dist >= 0.1 {
+                    return None;
+                }
+
+                let point1 = &frame.corners[idx1];
+                let point2 = &image_corners[i];
+                Some((point1.x - point2.x, point1.y - point2.y))
+            })
+            .collect();
+
+        let mut offset_counts: HashMap<(i32, i32), usize> = HashMap::new();
+        for offset in offsets {
+            *offset_counts.entry(offset).or_insert(0) += 1;
+        }
+
+        let neighbour_count = |(x, y): (i32, i32)| -> usize {
+            (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+                .map(|offset| offset_counts.get(&offset).copied().unwrap_or(0))
+                .sum()
+        };
+
+        let (max_offset, max_count) = offset_counts
+            .keys()
+            .map(|offset| (*offset, neighbour_count(*offset)))
+            .max_by_key(|(offset, count)| (*count, offset_counts[offset]))?;
+
+        let second_max_count = offset_counts
+            .keys()
+            .filter(|(x, y)| (x - max_offset.0).abs() > 2 || (y - max_offset.1).abs() > 2)
+            .map(|offset| neighbour_count(*offset))
+            .max()
+            .unwrap_or(0);
+
+        if max_count < image_corners.len() / 10 || max_count < second_max_count * 2 {
+            return None;
+        }
+
+        // 邻域内按票数加权平均
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let offset = (max_offset.0 + dx, max_offset.1 + dy);
+                let count = offset_counts.get(&offset).copied().unwrap_or(0) as f32;
+                sum_x += offset.0 as f32 * count;
+                sum_y += offset.1 as f32 * count;
+            }
+        }
+
+        Some((sum_x / max_count as f32, sum_y / max_count as f32))
+    }
+
+    /**
+     * 在原尺寸灰度图上搜索重叠区域差异最小的偏移（原图像素）
+     */
+    fn refine_offset(
+        &self,
+        frame_luma_image: &GrayImage,
+        luma_image: &GrayImage,
+        estimated_offset: (f32, f32),
+    ) -> Option<ScrollOffset> {
+        let width = luma_image.width() as i32;
+        let height = luma_image.height() as i32;
+        let search_radius = (1.0 / self.image_scale).ceil() as i32;
+        let estimated_x = (estimated_offset.0 / self.image_scale).round() as i32;
+        let estimated_y = (estimated_offset.1 / self.image_scale).round() as i32;
+
+        // 重叠区域太小时差异没有参考价值
+        let min_overlap_size = (width.min(height) / 4).max(1);
+
+        let candidates: Vec<(i32, i32)> = (estimated_y - search_radius
+            ..=estimated_y + search_radius)
+            .flat_map(|y| {
+                (estimated_x - search_radius..=estimated_x + search_radius).map(move |x| (x, y))
+            })
+            .collect();
+
+        candidates
+            .into_par_iter()
+            .filter_map(|(offset_x, offset_y)| {
+                let x_start = (-offset_x).max(0);
+                let x_end = width.min(width - offset_x);
+                let y_start = (-offset_y).max(0);
+                let y_end = height.min(height - offset_y);
+                if x_end - x_start < min_overlap_size || y_end - y_start < min_overlap_size {
+                    return None;
+                }
+
+                let mut diff_sum: u64 = 0;
+                let mut pixel_count: u64 = 0;
+                for y in (y_start..y_end).step_by(3) {
+                    for x in (x_start..x_end).step_by(3) {
+                        let pixel = unsafe { luma_image.unsafe_get_pixel(x as u32, y as u32)[0] };
+                        let frame_pixel = unsafe {
+                            frame_luma_image
+                                .unsafe_get_pixel((x + offset_x) as u32, (y + offset_y) as u32)[0]
+                        };
+
+                        diff_sum += pixel.abs_diff(frame_pixel) as u64;
+                        pixel_count += 1;
+                    }
+                }
+
+                Some((
+                    diff_sum * 1024 / pixel_count,
+                    (offset_x - estimated_x).abs() + (offset_y - estimated_y).abs(),
+                    offset_x,
+                    offset_y,
+                ))
+            })
+            .min()
+            .map(|(_, _, offset_x, offset_y)| ScrollOffset::new(offset_x, offset_y))
+    }
+
+    /**
+     * 放置在 position 的帧在画布上的矩形
+     */
+    fn get_frame_rect(&self, position: ScrollOffset) -> ElementRect {
+        ElementRect {
+            min_x: position.x,
+            min_y: position.y,
+            max_x: position.x + self.image_width as i32,
+            max_y: position.y + self.image_height as i32,
+        }
+    }
+
+    /**
+     * 矩形是否完全位于已放置帧的并集内
+     * 按各帧的边界把矩形切分成若干格，每一格都需要被某一帧覆盖
+     */
+    fn is_rect_covered(&self, rect: ElementRect) -> bool {
+        let frame_rect_list: Vec<ElementRect> = self
+            .frame_list
+            .iter()
+            .map(|frame| self.get_frame_rect(frame.position))
+            .filter(|frame_rect| {
+                frame_rect.min_x < rect.max_x
+                    && frame_rect.max_x > rect.min_x
+                    && frame_rect.min_y < rect.max_y
+                    && frame_rect.max_y > rect.min_y
+            })
+            .collect();
+
+        let mut x_list = vec![rect.min_x, rect.max_x];
+        let mut y_list = vec![rect.min_y, rect.max_y];
+        for frame_rect in frame_rect_list.iter() {
+            x_list.extend(
+                [frame_rect.min_x, frame_rect.max_x]
+                    .into_iter()
+                    .filter(|x| *x > rect.min_x && *x < rect.max_x),
+            );
+            y_list.extend(
+                [frame_rect.min_y, frame_rect.max_y]
+                    .into_iter()
+                    .filter(|y| *y > rect.min_y && *y < rect.max_y),
+            );
+        }
+        x_list.sort_unstable();
+        x_list.dedup();
+        y_list.sort_unstable();
+        y_list.dedup();
+
+        y_list.windows(2).all(|y_range| {
+            x_list.windows(2).all(|x_range| {
+                frame_rect_list.iter().any(|frame_rect| {
+                    frame_rect.min_x <= x_range[0]
+                        && frame_rect.max_x >= x_range[1]
+                        && frame_rect.min_y <= y_range[0]
+                        && frame_rect.max_y >= y_range[1]
+                })
+            })
+        })
+    }
+
+    /**
+     * 放置在 position 的帧完整覆盖的网格
+     */
+    fn get_frame_tiles(&self, position: ScrollOffset) -> Vec<(i32, i32)> {
+        let tile_size = self.tile_size as i32;
+        let tile_min_x = (position.x + tile_size - 1).div_euclid(tile_size);
+        let tile_min_y = (position.y + tile_size - 1).div_euclid(tile_size);
+        let tile_max_x = (position.x + self.image_width as i32).div_euclid(tile_size);
+        let tile_max_y = (position.y + self.image_height as i32).div_euclid(tile_size);
+
+        (tile_min_y..tile_max_y)
+            .flat_map(|tile_y| (tile_min_x..tile_max_x).map(move |tile_x| (tile_x, tile_y)))
+            .collect()
+    }
+
+    fn add_frame(
+        &mut self,
+        image: DynamicImage,
+        position: ScrollOffset,
+        corners: Vec<ScrollOffset>,
+        descriptors: Vec<Vec<f32>>,
+    ) {
+        let mut index_params = HNSWParams::<f32>::default();
+        index_params.ef_search = 24;
+        index_params.ef_build = 12;
+
+        let mut ann_index = HNSWIndex::new(self.get_descriptor_size(), &index_params);
+        descriptors.iter().enumerate().for_each(|(i, descriptor)| {
+            ann_index.add(descriptor, i).unwrap();
+        });
+        ann_index.build(Metric::Euclidean).unwrap();
+
+        let frame_rect = self.get_frame_rect(position);
+
+        if self.frame_list.is_empty() {
+            self.bounds = frame_rect;
+        } else {
+            self.bounds = ElementRect {
+                min_x: self.bounds.min_x.min(frame_rect.min_x),
+                min_y: self.bounds.min_y.min(frame_rect.min_y),
+                max_x: self.bounds.max_x.max(frame_rect.max_x),
+                max_y: self.bounds.max_y.max(frame_rect.max_y),
+            };
+        }
+
+        // 只记录被该帧完整覆盖的网格
+        let frame_tile_list = self.get_frame_tiles(position);
+        self.covered_tile_set.extend(frame_tile_list);
+
+        let image = self.frame_store.create_frame(&image);
+        self.frame_list.push(PanoramaFrame {
+            image,
+            position,
+            corners,
+            descriptors,
+            ann_index,
+        });
+        self.enforce_frame_memory_budget();
+    }
+
+    /**
+     * 处理一帧图片，匹配成功时返回帧在画布上的位置
+     * 优先与最近放置的帧匹配，失败后再依次尝试更早的帧
+     */
+    pub fn handle_image(
+        &mut self,
+        image: DynamicImage,
+    ) -> (ScrollStitchFrameStatus, Option<ScrollOffset>) {
+        let image_width = image.width();
+        let image_height = image.height();
+
+        if self.image_width == 0 || self.image_height == 0 {
+            self.init_image_size(image_width, image_height);
+        } else if image_width != self.image_width || image_height != self.image_height {
+            return (ScrollStitchFrameStatus::NotMatched, None);
+        }
+
+        let luma_image = image.to_luma8();
+        let gray_image = self.get_gray_image(&luma_image);
+
+        let image_corners: Vec<ScrollOffset> =
+            corners::corners_fast9(&gray_image, self.corner_threshold)
+                .iter()
+                .map(|corner| ScrollOffset::new(corner.x as i32, corner.y as i32))
+                .collect();
+
+        if image_corners.is_empty() {
+            return (ScrollStitchFrameStatus::NotMatched, None);
+        }
+
+        let image_descriptors: Vec<Vec<f32>> = image_corners
+            .par_iter()
+            .map(|corner| compute_descriptor(&gray_image, corner, self.descriptor_patch_size))
+            .collect();
+
+        if self.frame_list.is_empty() {
+            let position = ScrollOffset::new(0, 0);
+            self.add_frame(image, position, image_corners, image_descriptors);
+            return (ScrollStitchFrameStatus::Appended, Some(position));
+        }
+
+        let matched = self.frame_list.iter().rev().find_map(|frame| {
+            let estimated_offset =
+                self.estimate_offset(frame, &image_descriptors, &image_corners)?;
+
+            // 不常驻原尺寸灰度图，特征点匹配成功后再从压缩的帧原图解码
+            let frame_luma_image = match frame.image.decode() {
+                Ok(frame_image) => frame_image.to_luma8(),
+                Err(e) => {
+                    log::warn!("[ScrollPanoramaService::handle_image] {}", e);
+                    return None;
+                }
+            };
+            let offset = self.refine_offset(&frame_luma_image, &luma_image, estimated_offset)?;
+
+            Some((frame.position, offset))
+        });
+
+        let (frame_position, offset) = match matched {
+            Some(matched) => matched,
+            None => return (ScrollStitchFrameStatus::NotMatched, None),
+        };
+
+        let position = ScrollOffset::new(frame_position.x + offset.x, frame_position.y + offset.y);
+
+        // 完全位于已放置帧范围内的帧不保留，包括没有移动的帧
+        // 不足一个网格的边缘条带也属于新区域
+        if self.is_rect_covered(self.get_frame_rect(position)) {
+            return (ScrollStitchFrameStatus::Unchanged, Some(position));
+        }
+
+        self.add_frame(image, position, image_corners, image_descriptors);
+
+        (ScrollStitchFrameStatus::Appended, Some(position))
+    }
+
+    /**
+     * 外接矩形内尚未被完整覆盖的网格，坐标相对画布左上角
+     */
+    pub fn get_uncovered_tiles(&self) -> Vec<ElementRect> {
+        if self.frame_list.is_empty() {
+            return vec![];
+        }
+
+        let tile_size = self.tile_size as i32;
+        let tile_min_x = (self.bounds.min_x + tile_size - 1).div_euclid(tile_size);
+        let tile_min_y = (self.bounds.min_y + tile_size - 1).div_euclid(tile_size);
+        let tile_max_x = self.bounds.max_x.div_euclid(tile_size);
+        let tile_max_y = self.bounds.max_y.div_euclid(tile_size);
+
+        (tile_min_y..tile_max_y)
+            .flat_map(|tile_y| (tile_min_x..tile_max_x).map(move |tile_x| (tile_x, tile_y)))
+            .filter(|tile| !self.covered_tile_set.contains(tile))
+            .map(|(tile_x, tile_y)| ElementRect {
+                min_x: tile_x * tile_size - self.bounds.min_x,
+                min_y: tile_y * tile_size - self.bounds.min_y,
+                max_x: (tile_x + 1) * tile_size - self.bounds.min_x,
+                max_y: (tile_y + 1) * tile_size - self.bounds.min_y,
+            })
+            .collect()
+    }
+
+    /**
+     * 导出画布，未覆盖的区域使用背景色填充，背景色为空时保持透明
+     */
+    pub fn export(&self, background_color: Option<[u8; 4]>) -> Result<DynamicImage, String> {
+        if self.frame_list.is_empty() {
+            return Err(String::from(
+                "[ScrollPanoramaService::export] No frame to export",
+            ));
+        }
+
+        let canvas_width = (self.bounds.max_x - self.bounds.min_x) as u32;
+        let canvas_height = (self.bounds.max_y - self.bounds.min_y) as u32;
+
+        let mut canvas = image::RgbaImage::from_pixel(
+            canvas_width,
+            canvas_height,
+            image::Rgba(background_color.unwrap_or([0, 0, 0, 0])),
+        );
+
+        for frame in self.frame_list.iter() {
+            let frame_image = frame.image.decode().map_err(|e| {
+                format!(
+                    "[ScrollPanoramaService::export] Failed to decode frame: {}",
+                    e
+                )
+            })?;
+
+            image::imageops::replace(
+                &mut canvas,
+                &frame_image.to_rgba8(),
+                (frame.position.x - self.bounds.min_x) as i64,
+                (frame.position.y - self.bounds.min_y) as i64,
+            );
+        }
+
+        Ok(DynamicImage::ImageRgba8(canvas))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, crop_frame};
+
+    #[test]
+    fn test_panorama_stitching() {
+        let map = create_page(640, 520);
+        // 先向右平移，再向下，再向左
+        let frame_position_list = [
+            (0, 0),
+            (120, 0),
+            (240, 0),
+            (240, 100),
+            (240, 220),
+            (120, 220),
+        ];
+
+        let mut service = ScrollPanoramaService::new();
+        service.init(1.0, 128, 128, 24, 28, 64);
+
+        for (frame_x, frame_y) in frame_position_list {
+            let (status, position) =
+                service.handle_image(crop_frame(&map, frame_x, frame_y, 400, 300));
+
+            assert_eq!(status, ScrollStitchFrameStatus::Appended);
+            assert_eq!(
+                position,
+                Some(ScrollOffset::new(frame_x as i32, frame_y as i32))
+            );
+        }
+
+        // 完全位于已放置帧范围内的帧不保留
+        let (status, position) = service.handle_image(crop_frame(&map, 130, 110, 400, 300));
+        assert_eq!(status, ScrollStitchFrameStatus::Unchanged);
+        assert_eq!(position, Some(ScrollOffset::new(130, 110)));
+        assert_eq!(service.frame_list.len(), frame_position_list.len());
+
+        // 帧全部溢出到临时目录后导出结果不变
+        service.set_frame_memory_budget(1);
+        assert!(
+            service
+                .frame_list
+                .iter()
+                .all(|frame| frame.image.is_spilled())
+        );
+
+        let image = service.export(None).unwrap().to_rgba8();
+        assert_eq!((image.width(), image.height()), (640, 520));
+
+        // 左下角没有帧覆盖
+        assert_eq!(image.get_pixel(10, 510).0, [0, 0, 0, 0]);
+        assert_eq!(
+            image.get_pixel(600, 500).0,
+            [
+                map.get_pixel(600, 500)[0],
+                map.get_pixel(600, 500)[1],
+                map.get_pixel(600, 500)[2],
+                255
+            ]
+        );
+        assert!(
+            service
+                .get_uncovered_tiles()
+                .iter()
+                .any(|tile| tile.min_x == 0 && tile.max_y == 512)
+        );
+    }
+    #[test]
+    fn test_panorama_edge_strip() {
+        let map = create_page(640, 520);
+
+        let mut service = ScrollPanoramaService::new();
+        service.init(1.0, 128, 128, 24, 28, 64);
+
+        let (status, _) = service.handle_image(crop_frame(&map, 0, 0, 400, 300));
+        assert_eq!(status, ScrollStitchFrameStatus::Appended);
+
+        // 向右平移不足一个网格，没有覆盖新网格，但右侧多出 40 像素
+        let (status, position) = service.handle_image(crop_frame(&map, 40, 0, 400, 300));
+        assert_eq!(status, ScrollStitchFrameStatus::Appended);
+        assert_eq!(position, Some(ScrollOffset::new(40, 0)));
+
+        // 回到两帧之间的位置不带来新区域
+        let (status, _) = service.handle_image(crop_frame(&map, 20, 0, 400, 300));
+        assert_eq!(status, ScrollStitchFrameStatus::Unchanged);
+        assert_eq!(service.frame_list.len(), 2);
+
+        let image = service.export(None).unwrap().to_rgba8();
+        assert_eq!((image.width(), image.height()), (440, 300));
+        assert_eq!(
+            image.get_pixel(430, 150).0,
+            [
+                map.get_pixel(430, 150)[0],
+                map.get_pixel(430, 150)[1],
+                map.get_pixel(430, 150)[2],
+                255
+            ]
+        );
+    }
+}
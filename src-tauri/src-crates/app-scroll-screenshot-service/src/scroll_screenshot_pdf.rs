@@ -0,0 +1,187 @@
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+/// 按 96 DPI 将像素转换为 PDF 的点
+const POINTS_PER_PIXEL: f32 = 0.75;
+
+/// 目录和页面树的对象编号，每页依次占用页面、内容和图片三个对象
+const CATALOG_OBJECT_ID: usize = 1;
+const PAGES_OBJECT_ID: usize = 2;
+const FIRST_PAGE_OBJECT_ID: usize = 3;
+
+/**
+ * 逐页写入只包含一张 RGB 图片的 PDF
+ * 页数需要预先确定，每页写入后即可释放像素，内存占用只与单页大小有关
+ */
+pub struct ScrollPdfWriter<W: Write> {
+    writer: W,
+    /// 已写入的字节数，用于生成交叉引用表
+    offset: usize,
+    /// 各对象的起始位置，下标为对象编号减一
+    object_offset_list: Vec<usize>,
+    page_count: usize,
+    written_page_count: usize,
+}
+
+impl<W: Write> ScrollPdfWriter<W> {
+    pub fn new(writer: W, page_count: usize) -> Result<Self, String> {
+        let mut pdf_writer = Self {
+            writer,
+            offset: 0,
+            object_offset_list: vec![],
+            page_count,
+            written_page_count: 0,
+        };
+
+        pdf_writer.write_bytes(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n")?;
+
+        pdf_writer.begin_object(CATALOG_OBJECT_ID)?;
+        pdf_writer.write_bytes(
+            format!(
+                "<< /Type /Catalog /Pages {} 0 R >>\nendobj\n",
+                PAGES_OBJECT_ID
+            )
+            .as_bytes(),
+        )?;
+
+        let kids = (0..page_count)
+            .map(|page_index| format!("{} 0 R", Self::get_page_object_id(page_index)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        pdf_writer.begin_object(PAGES_OBJECT_ID)?;
+        pdf_writer.write_bytes(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>\nendobj\n",
+                kids, page_count
+            )
+            .as_bytes(),
+        )?;
+
+        Ok(pdf_writer)
+    }
+
+    fn get_page_object_id(page_index: usize) -> usize {
+        FIRST_PAGE_OBJECT_ID + page_index * 3
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|e| format!("[ScrollPdfWriter::write_bytes] Failed to write: {}", e))?;
+        self.offset += bytes.len();
+
+        Ok(())
+    }
+
+    fn begin_object(&mut self, object_id: usize) -> Result<(), String> {
+        if self.object_offset_list.len() < object_id {
+            self.object_offset_list.resize(object_id, 0);
+        }
+        self.object_offset_list[object_id - 1] = self.offset;
+
+        self.write_bytes(format!("{} 0 obj\n", object_id).as_bytes())
+    }
+
+    fn write_stream(&mut self, dictionary: &str, data: &[u8]) -> Result<(), String> {
+        self.write_bytes(
+            format!("<< {} /Length {} >>\nstream\n", dictionary, data.len()).as_bytes(),
+        )?;
+        self.write_bytes(data)?;
+        self.write_bytes(b"\nendstream\nendobj\n")
+    }
+
+    /**
+     * 写入一页，pixels 为 RGB 像素
+     */
+    pub fn write_page(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<(), String> {
+        if self.written_page_count >= self.page_count {
+            return Err(String::from(
+                "[ScrollPdfWriter::write_page] Page count exceeded",
+            ));
+        }
+
+        let page_object_id = Self::get_page_object_id(self.written_page_count);
+        let content_object_id = page_object_id + 1;
+        let image_object_id = page_object_id + 2;
+        let page_width = width as f32 * POINTS_PER_PIXEL;
+        let page_height = height as f32 * POINTS_PER_PIXEL;
+
+        self.begin_object(page_object_id)?;
+        self.write_bytes(
+            format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>\nendobj\n",
+                PAGES_OBJECT_ID, page_width, page_height, image_object_id, content_object_id
+            )
+            .as_bytes(),
+        )?;
+
+        let content = format!(
+            "q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q",
+            page_width, page_height
+        );
+        self.begin_object(content_object_id)?;
+        self.write_stream("", content.as_bytes())?;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(pixels).map_err(|e| {
+            format!(
+                "[ScrollPdfWriter::write_page] Failed to compress image: {}",
+                e
+            )
+        })?;
+        let image_data = encoder.finish().map_err(|e| {
+            format!(
+                "[ScrollPdfWriter::write_page] Failed to compress image: {}",
+                e
+            )
+        })?;
+
+        self.begin_object(image_object_id)?;
+        self.write_stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode",
+                width, height
+            ),
+            &image_data,
+        )?;
+
+        self.written_page_count += 1;
+        Ok(())
+    }
+
+    /**
+     * 写入交叉引用表，所有页面写入后调用
+     */
+    pub fn finish(mut self) -> Result<W, String> {
+        if self.written_page_count != self.page_count {
+            return Err(format!(
+                "[ScrollPdfWriter::finish] Expected {} pages, but {} pages were written",
+                self.page_count, self.written_page_count
+            ));
+        }
+
+        let xref_offset = self.offset;
+        let mut xref = format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            self.object_offset_list.len() + 1
+        );
+        for object_offset in self.object_offset_list.iter() {
+            xref.push_str(&format!("{:010} 00000 n \n", object_offset));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.object_offset_list.len() + 1,
+            CATALOG_OBJECT_ID,
+            xref_offset
+        ));
+        self.write_bytes(xref.as_bytes())?;
+
+        self.writer
+            .flush()
+            .map_err(|e| format!("[ScrollPdfWriter::finish] Failed to flush: {}", e))?;
+
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,260 @@
+use std::any::Any;
+
+use image::GrayImage;
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+use crate::scroll_screenshot_dynamic_mask::ScrollDynamicMask;
+use crate::scroll_screenshot_matcher::{
+    ScrollFeatures, ScrollLines, ScrollMatchConstraint, ScrollMatchQuality, ScrollMatchResult,
+    ScrollMatcher, downcast_features,
+};
+use crate::scroll_screenshot_service::{ScrollDirection, ScrollOffset};
+
+/// 参与校验的相关峰数量
+const PEAK_CANDIDATE_COUNT: usize = 5;
+/// 重叠区域平均像素差的上限，超过时认为没有匹配
+const MAX_OVERLAP_DIFF: f32 = 10.0;
+
+pub struct PhaseCorrelationFeatures {
+    lines: ScrollLines,
+    /// 补零后滚动方向的尺寸，避免循环相关时首尾混叠
+    padded_line_count: usize,
+    /// 频谱，按滚动垂直方向优先排列
+    spectrum: Vec<Complex<f32>>,
+}
+
+impl ScrollFeatures for PhaseCorrelationFeatures {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// 将按 [row][column] 排列的数据转置为 [column][row]
+fn transpose(buffer: &[Complex<f32>], row_count: usize, column_count: usize) -> Vec<Complex<f32>> {
+    let mut transposed = vec![Complex::new(0.0, 0.0); buffer.len()];
+    for row in 0..row_count {
+        for column in 0..column_count {
+            transposed[column * row_count + row] = buffer[row * column_count + column];
+        }
+    }
+
+    transposed
+}
+
+/**
+ * 使用相位相关计算图片偏移
+ * 不依赖特征点，对留白较多、特征点稀少的页面更稳定
+ */
+pub struct PhaseCorrelationScrollMatcher {
+    direction: ScrollDirection,
+}
+
+impl PhaseCorrelationScrollMatcher {
+    pub fn new(direction: ScrollDirection) -> Self {
+        Self { direction }
+    }
+}
+
+impl ScrollMatcher for PhaseCorrelationScrollMatcher {
+    fn extract_features(
+        &mut self,
+        gray_image: &GrayImage,
+        mask: &ScrollDynamicMask,
+    ) -> Option<Box<dyn ScrollFeatures>> {
+        let lines = ScrollLines::new(gray_image, self.direction);
+        if lines.line_count == 0 || lines.line_size == 0 {
+            return None;
+        }
+
+        // 纯色图片没有可用于匹配的结构
+        let min_pixel = lines.pixels.iter().min().copied().unwrap_or(0);
+        let max_pixel = lines.pixels.iter().max().copied().unwrap_or(0);
+        if max_pixel - min_pixel <= 2 {
+            return None;
+        }
+
+        // 去掉均值，避免补零的边界产生虚假的相关峰
+        let mean =
+            lines.pixels.iter().map(|pixel| *pixel as f32).sum::<f32>() / lines.pixels.len() as f32;
+
+        let padded_line_count = (lines.line_count * 2).next_power_of_two();
+        let mut buffer = vec![Complex::new(0.0, 0.0); padded_line_count * lines.line_size];
+        for (i, (value, pixel)) in buffer.iter_mut().zip(lines.pixels.iter()).enumerate() {
+            // 不稳定区域按均值处理，不参与相关
+            let (line, position) = (i / lines.line_size, i % lines.line_size);
+            let is_masked = !mask.is_empty()
+                && if self.direction == ScrollDirection::Vertical {
+                    mask.contains(position as i32, line as i32)
+                } else {
+                    mask.contains(line as i32, position as i32)
+                };
+
+            if !is_masked {
+                *value = Complex::new(*pixel as f32 - mean, 0.0);
+            }
+        }
+
+        let mut planner = FftPlanner::<f32>::new();
+        planner
+            .plan_fft_forward(lines.line_size)
+            .process(&mut buffer);
+        let mut spectrum = transpose(&buffer, padded_line_count, lines.line_size);
+        planner
+            .plan_fft_forward(padded_line_count)
+            .process(&mut spectrum);
+
+        Some(Box::new(PhaseCorrelationFeatures {
+            lines,
+            padded_line_count,
+            spectrum,
+        }))
+    }
+
+    fn match_features(
+        &self,
+        index_features: &dyn ScrollFeatures,
+        features: &dyn ScrollFeatures,
+        constraint: &ScrollMatchConstraint,
+    ) -> ScrollMatchResult {
+        let (index, image_features) = match (
+            downcast_features::<PhaseCorrelationFeatures>(index_features),
+            downcast_features::<PhaseCorrelationFeatures>(features),
+        ) {
+            (Some(index), Some(image_features)) => (index, image_features),
+            _ => return ScrollMatchResult::NotMatched,
+        };
+
+        if index.padded_line_count != image_features.padded_line_count
+            || index.lines.line_size != image_features.lines.line_size
+        {
+            return ScrollMatchResult::NotMatched;
+        }
+
+        let padded_line_count = index.padded_line_count;
+        let line_size = index.lines.line_size;
+        let line_count = image_features.lines.line_count as i32;
+
+        // 归一化互功率谱，逆变换后峰值位置即为偏移
+        let mut cross_power: Vec<Complex<f32>> = index
+            .spectrum
+            .iter()
+            .zip(image_features.spectrum.iter())
+            .map(|(index_value, value)| {
+                let product = index_value * value.conj();
+                let norm = product.norm();
+                if norm > f32::EPSILON {
+                    product / norm
+                } else {
+                    Complex::new(0.0, 0.0)
+                }
+            })
+            .collect();
+
+        let mut planner = FftPlanner::<f32>::new();
+        planner
+            .plan_fft_inverse(padded_line_count)
+            .process(&mut cross_power);
+        let mut correlation = transpose(&cross_power, line_size, padded_line_count);
+        planner
+            .plan_fft_inverse(line_size)
+            .process(&mut correlation);
+
+        // 每个滚动偏移只保留允许范围内最强的滚动垂直偏移
+        let mut peak_list: Vec<(f32, i32, i32)> = correlation
+            .chunks_exact(line_size)
+            .enumerate()
+            .filter_map(|(scroll_index, values)| {
+                let scroll_offset = if scroll_index < padded_line_count / 2 {
+                    scroll_index as i32
+                } else {
+                    scroll_index as i32 - padded_line_count as i32
+                };
+                if scroll_offset.abs() >= line_count {
+                    return None;
+                }
+
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(cross_index, value)| {
+                        let cross_offset = if cross_index <= line_size / 2 {
+                            cross_index as i32
+                        } else {
+                            cross_index as i32 - line_size as i32
+                        };
+
+                        (value.re, scroll_offset, cross_offset)
+                    })
+                    .filter(|(_, _, cross_offset)| {
+                        cross_offset.abs() <= constraint.cross_axis_tolerance
+                    })
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+            })
+            .collect();
+        peak_list.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        // 相关峰可能来自重复的内容，用重叠区域的像素差校验
+        let min_overlap_size = (line_count / 8) as usize;
+        let mut candidate_list: Vec<(i32, i32)> = Vec::with_capacity(PEAK_CANDIDATE_COUNT);
+        let mut best_match: Option<(f32, f32, i32, i32)> = None;
+        for (peak, scroll_offset, cross_offset) in peak_list {
+            if candidate_list.len() >= PEAK_CANDIDATE_COUNT {
+                break;
+            }
+
+            // 相邻的峰通常属于同一个偏移
+            if candidate_list.iter().any(|(candidate_scroll_offset, _)| {
+                (candidate_scroll_offset - scroll_offset).abs() <= 2
+            }) {
+                continue;
+            }
+            candidate_list.push((scroll_offset, cross_offset));
+
+            let overlap_diff = match image_features.lines.get_overlap_diff(
+                &index.lines,
+                scroll_offset,
+                cross_offset,
+                min_overlap_size,
+            ) {
+                Some(overlap_diff) => overlap_diff,
+                None => continue,
+            };
+
+            // 差异相近时保留相关性更强的峰
+            let is_better = match best_match {
+                Some((best_overlap_diff, _, _, _)) => overlap_diff + 0.5 < best_overlap_diff,
+                None => true,
+            };
+            if is_better {
+                best_match = Some((overlap_diff, peak, scroll_offset, cross_offset));
+            }
+        }
+
+        let (peak, scroll_offset, cross_offset) = match best_match {
+            Some((overlap_diff, peak, scroll_offset, cross_offset))
+                if overlap_diff <= MAX_OVERLAP_DIFF =>
+            {
+                (peak, scroll_offset, cross_offset)
+            }
+            _ => return ScrollMatchResult::NotMatched,
+        };
+
+        if !constraint.accepts_diff(-scroll_offset) {
+            return ScrollMatchResult::NoNewArea;
+        }
+
+        ScrollMatchResult::Matched(
+            if self.direction == ScrollDirection::Vertical {
+                ScrollOffset::new(cross_offset, scroll_offset)
+            } else {
+                ScrollOffset::new(scroll_offset, cross_offset)
+            },
+            // 逆变换没有归一化，完全一致时峰值等于元素数量
+            ScrollMatchQuality {
+                confidence: (peak / (padded_line_count * line_size) as f32).clamp(0.0, 1.0),
+                inlier_count: (line_count - scroll_offset.abs()) as usize,
+            },
+        )
+    }
+}
@@ -0,0 +1,191 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use image::GrayImage;
+
+use crate::scroll_screenshot_dynamic_mask::ScrollDynamicMask;
+use crate::scroll_screenshot_matcher::{
+    ScrollFeatures, ScrollLines, ScrollMatchConstraint, ScrollMatchQuality, ScrollMatchResult,
+    ScrollMatcher, downcast_features,
+};
+use crate::scroll_screenshot_service::{ScrollDirection, ScrollOffset};
+
+/// 完全一致的线少于该值时认为没有匹配
+const MIN_MATCHED_LINE_COUNT: usize = 8;
+/// 重叠区域内可比较的线中完全一致的最小比例
+const MIN_MATCHED_LINE_RATIO: f32 = 0.6;
+
+pub struct RowHashFeatures {
+    /// 每条线的哈希，颜色一致的线（留白）无法区分位置，为 None
+    line_hash_list: Vec<Option<u64>>,
+    /// 哈希对应的线位置，只有作为索引时才会建立
+    line_position_map: HashMap<u64, Vec<usize>>,
+}
+
+impl ScrollFeatures for RowHashFeatures {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn get_line_hash(line: &[u8]) -> Option<u64> {
+    let min_pixel = line.iter().min().copied().unwrap_or(0);
+    let max_pixel = line.iter().max().copied().unwrap_or(0);
+    if max_pixel - min_pixel <= 2 {
+        return None;
+    }
+
+    // FNV-1a
+    Some(line.iter().fold(0xcbf29ce484222325, |hash, pixel| {
+        (hash ^ *pixel as u64).wrapping_mul(0x100000001b3)
+    }))
+}
+
+/**
+ * 比较每一行（列）的哈希，寻找完全一致的线最多的偏移
+ * 适合大面积留白或纯色的页面，不支持滚动垂直方向上的偏移
+ */
+pub struct RowHashScrollMatcher {
+    direction: ScrollDirection,
+}
+
+impl RowHashScrollMatcher {
+    pub fn new(direction: ScrollDirection) -> Self {
+        Self { direction }
+    }
+}
+
+impl ScrollMatcher for RowHashScrollMatcher {
+    fn extract_features(
+        &mut self,
+        gray_image: &GrayImage,
+        mask: &ScrollDynamicMask,
+    ) -> Option<Box<dyn ScrollFeatures>> {
+        let lines = ScrollLines::new(gray_image, self.direction);
+        let line_hash_list: Vec<Option<u64>> = (0..lines.line_count)
+            .map(|line| {
+                // 经过不稳定区域的线无法完全一致
+                let is_masked = !mask.is_empty()
+                    && (0..lines.line_size).any(|position| {
+                        if self.direction == ScrollDirection::Vertical {
+                            mask.contains(position as i32, line as i32)
+                        } else {
+                            mask.contains(line as i32, position as i32)
+                        }
+                    });
+                if is_masked {
+                    return None;
+                }
+
+                get_line_hash(lines.line(line))
+            })
+            .collect();
+
+        if line_hash_list.iter().all(|line_hash| line_hash.is_none()) {
+            return None;
+        }
+
+        Some(Box::new(RowHashFeatures {
+            line_hash_list,
+            line_position_map: HashMap::new(),
+        }))
+    }
+
+    fn build_index_features(&self, features: Box<dyn ScrollFeatures>) -> Box<dyn ScrollFeatures> {
+        let features = match downcast_features::<RowHashFeatures>(features.as_ref()) {
+            Some(features) => features,
+            None => return features,
+        };
+
+        let mut line_position_map: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (line, line_hash) in features.line_hash_list.iter().enumerate() {
+            if let Some(line_hash) = line_hash {
+                line_position_map.entry(*line_hash).or_default().push(line);
+            }
+        }
+
+        Box::new(RowHashFeatures {
+            line_hash_list: features.line_hash_list.clone(),
+            line_position_map,
+        })
+    }
+
+    fn match_features(
+        &self,
+        index_features: &dyn ScrollFeatures,
+        features: &dyn ScrollFeatures,
+        constraint: &ScrollMatchConstraint,
+    ) -> ScrollMatchResult {
+        let (index, image_features) = match (
+            downcast_features::<RowHashFeatures>(index_features),
+            downcast_features::<RowHashFeatures>(features),
+        ) {
+            (Some(index), Some(image_features)) => (index, image_features),
+            _ => return ScrollMatchResult::NotMatched,
+        };
+
+        // 每对哈希相同的线为其位置差投票
+        let mut vote_map: HashMap<i32, usize> = HashMap::new();
+        for (line, line_hash) in image_features.line_hash_list.iter().enumerate() {
+            let index_line_list = match line_hash
+                .as_ref()
+                .and_then(|line_hash| index.line_position_map.get(line_hash))
+            {
+                Some(index_line_list) => index_line_list,
+                None => continue,
+            };
+
+            for index_line in index_line_list {
+                *vote_map
+                    .entry(*index_line as i32 - line as i32)
+                    .or_default() += 1;
+            }
+        }
+
+        let (scroll_offset, matched_line_count) = match vote_map
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.abs().cmp(&a.0.abs())))
+        {
+            Some(best_vote) => best_vote,
+            None => return ScrollMatchResult::NotMatched,
+        };
+
+        // 重叠区域内两边都能比较的线
+        let index_line_count = index.line_hash_list.len() as i32;
+        let comparable_line_count = image_features
+            .line_hash_list
+            .iter()
+            .enumerate()
+            .filter(|(line, line_hash)| {
+                let index_line = *line as i32 + scroll_offset;
+                line_hash.is_some()
+                    && index_line >= 0
+                    && index_line < index_line_count
+                    && index.line_hash_list[index_line as usize].is_some()
+            })
+            .count();
+
+        if matched_line_count < MIN_MATCHED_LINE_COUNT
+            || (matched_line_count as f32) < comparable_line_count as f32 * MIN_MATCHED_LINE_RATIO
+        {
+            return ScrollMatchResult::NotMatched;
+        }
+
+        if !constraint.accepts_diff(-scroll_offset) {
+            return ScrollMatchResult::NoNewArea;
+        }
+
+        ScrollMatchResult::Matched(
+            if self.direction == ScrollDirection::Vertical {
+                ScrollOffset::new(0, scroll_offset)
+            } else {
+                ScrollOffset::new(scroll_offset, 0)
+            },
+            ScrollMatchQuality {
+                confidence: (matched_line_count as f32 / comparable_line_count.max(1) as f32)
+                    .min(1.0),
+                inlier_count: matched_line_count,
+            },
+        )
+    }
+}
@@ -0,0 +1,285 @@
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_service::ScrollDirection;
+
+/// 像素差超过该值时认为像素发生了变化
+const PIXEL_DIFF_THRESHOLD: u8 = 24;
+/// 贴边区域在滚动垂直方向上的最大尺寸（原图像素）
+const MAX_BAND_SIZE: u32 = 32;
+/// 贴边区域内允许的连续同步列数，如滚动条与窗口边缘之间的边框
+const MAX_SYNCED_GAP: u32 = 4;
+/// 列内变化像素的比例不低于该值时认为与滚动不同步
+const MIN_CHANGED_PIXEL_RATIO: f32 = 0.005;
+/// 列内变化像素不少于该值时才认为与滚动不同步
+const MIN_CHANGED_PIXEL_COUNT: u32 = 2;
+/// 中间区域变化像素的比例超过该值时，认为两帧没有对齐，不做检测
+const MAX_CONTENT_CHANGED_RATIO: f32 = 0.05;
+/// 前后两帧至少重叠的行数
+const MIN_OVERLAP_SIZE: i32 = 16;
+
+/// 滚动条、浮动按钮等贴边区域在滚动垂直方向上的尺寸
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrollbarBand {
+    /// 起始边区域尺寸（左或上）
+    pub start_size: u32,
+    /// 结束边区域尺寸（右或下）
+    pub end_size: u32,
+}
+
+impl ScrollbarBand {
+    pub fn is_empty(&self) -> bool {
+        self.start_size == 0 && self.end_size == 0
+    }
+
+    /// 合并多次检测的结果，各边取较大的尺寸
+    pub fn merge(&mut self, other: ScrollbarBand) {
+        self.start_size = self.start_size.max(other.start_size);
+        self.end_size = self.end_size.max(other.end_size);
+    }
+}
+
+/// 上一张匹配成功的图片，用于检测贴边区域
+pub struct ScrollbarReference {
+    /// 原尺寸灰度图
+    pub luma_image: GrayImage,
+    /// 相对首帧在滚动方向上的位置
+    pub position: i32,
+    /// 相对首帧在滚动垂直方向上的偏移
+    pub cross_offset: i32,
+}
+
+/**
+ * 统计一列（水平滚动时为一行）在两帧重叠区域内变化的像素
+ * 返回变化像素数和比较的像素数，列超出上一帧范围时返回 None
+ */
+fn get_changed_pixel_count(
+    previous_image: &GrayImage,
+    image: &GrayImage,
+    direction: ScrollDirection,
+    cross: i32,
+    scroll_range: (i32, i32),
+    scroll_offset: i32,
+    cross_offset: i32,
+) -> Option<(u32, u32)> {
+    let is_vertical = direction == ScrollDirection::Vertical;
+    let cross_side_size = if is_vertical {
+        image.width()
+    } else {
+        image.height()
+    } as i32;
+
+    let previous_cross = cross + cross_offset;
+    if previous_cross < 0 || previous_cross >= cross_side_size {
+        return None;
+    }
+
+    let get_pixel = |image: &GrayImage, scroll: i32, cross: i32| -> u8 {
+        if is_vertical {
+            image.get_pixel(cross as u32, scroll as u32)[0]
+        } else {
+            image.get_pixel(scroll as u32, cross as u32)[0]
+        }
+    };
+
+    let changed_pixel_count = (scroll_range.0..scroll_range.1)
+        .filter(|scroll| {
+            get_pixel(image, *scroll, cross).abs_diff(get_pixel(
+                previous_image,
+                scroll + scroll_offset,
+                previous_cross,
+            )) > PIXEL_DIFF_THRESHOLD
+        })
+        .count() as u32;
+
+    Some((
+        changed_pixel_count,
+        (scroll_range.1 - scroll_range.0) as u32,
+    ))
+}
+
+/**
+ * 一列（水平滚动时为一行）在两帧中都是同一种颜色，如滚动条的轨道
+ */
+fn is_flat_line(
+    previous_image: &GrayImage,
+    image: &GrayImage,
+    direction: ScrollDirection,
+    cross: u32,
+) -> bool {
+    let is_vertical = direction == ScrollDirection::Vertical;
+    let scroll_side_size = if is_vertical {
+        image.height()
+    } else {
+        image.width()
+    };
+
+    let get_pixel = |image: &GrayImage, scroll: u32| -> u8 {
+        if is_vertical {
+            image.get_pixel(cross, scroll)[0]
+        } else {
+            image.get_pixel(scroll, cross)[0]
+        }
+    };
+
+    let first_pixel = get_pixel(image, 0);
+    (0..scroll_side_size).all(|scroll| {
+        get_pixel(image, scroll).abs_diff(first_pixel) <= PIXEL_DIFF_THRESHOLD
+            && get_pixel(previous_image, scroll).abs_diff(first_pixel) <= PIXEL_DIFF_THRESHOLD
+    })
+}
+
+fn is_out_of_sync(changed_pixel_count: u32, pixel_count: u32) -> bool {
+    changed_pixel_count >= MIN_CHANGED_PIXEL_COUNT
+        && changed_pixel_count as f32 >= pixel_count as f32 * MIN_CHANGED_PIXEL_RATIO
+}
+
+/**
+ * 对比两帧滚动垂直方向两侧贴边的列（水平滚动时为行），找出内容与滚动偏移不同步的区域
+ * 当前帧第 line 行对应上一帧第 line + scroll_offset 行，第 cross 列对应上一帧第 cross + cross_offset 列
+ * 滚动条滑块的位置随滚动变化，对齐后仍然不一致
+ */
+pub fn detect_scrollbar_band(
+    previous_image: &GrayImage,
+    image: &GrayImage,
+    direction: ScrollDirection,
+    scroll_offset: i32,
+    cross_offset: i32,
+) -> ScrollbarBand {
+    if previous_image.dimensions() != image.dimensions() || scroll_offset == 0 {
+        return ScrollbarBand::default();
+    }
+
+    let (scroll_side_size, cross_side_size) = if direction == ScrollDirection::Vertical {
+        (image.height() as i32, image.width())
+    } else {
+        (image.width() as i32, image.height())
+    };
+    // 贴边区域只占很小的一部分，尺寸过小时无法区分
+    if cross_side_size < MAX_BAND_SIZE * 4 {
+        return ScrollbarBand::default();
+    }
+
+    let scroll_range = (
+        (-scroll_offset).max(0),
+        scroll_side_size.min(scroll_side_size - scroll_offset),
+    );
+    if scroll_range.1 - scroll_range.0 < MIN_OVERLAP_SIZE {
+        return ScrollbarBand::default();
+    }
+
+    let get_count = |cross: u32| {
+        get_changed_pixel_count(
+            previous_image,
+            image,
+            direction,
+            cross as i32,
+            scroll_range,
+            scroll_offset,
+            cross_offset,
+        )
+    };
+
+    // 中间的内容区域对齐后应当基本一致，否则检测结果不可信
+    let (content_changed_pixel_count, content_pixel_count) = (MAX_BAND_SIZE
+        ..cross_side_size - MAX_BAND_SIZE)
+        .step_by(4)
+        .filter_map(get_count)
+        .fold((0, 0), |(changed_sum, pixel_sum), (changed, pixels)| {
+            (changed_sum + changed, pixel_sum + pixels)
+        });
+    if content_pixel_count == 0
+        || content_changed_pixel_count as f32
+            > content_pixel_count as f32 * MAX_CONTENT_CHANGED_RATIO
+    {
+        return ScrollbarBand::default();
+    }
+
+    // 从边缘向内查找，连续多列同步时认为到达了内容区域
+    let get_band_size = |get_cross: &dyn Fn(u32) -> u32| -> u32 {
+        let mut band_size = 0;
+        for index in 0..MAX_BAND_SIZE {
+            if index >= band_size + MAX_SYNCED_GAP {
+                break;
+            }
+
+            if let Some((changed_pixel_count, pixel_count)) = get_count(get_cross(index))
+                && is_out_of_sync(changed_pixel_count, pixel_count)
+            {
+                band_size = index + 1;
+            }
+        }
+
+        // 滑块内侧的轨道没有变化，与滑块一起裁剪
+        if band_size > 0 {
+            let max_band_size = (band_size + MAX_SYNCED_GAP).min(MAX_BAND_SIZE);
+            while band_size < max_band_size
+                && is_flat_line(previous_image, image, direction, get_cross(band_size))
+            {
+                band_size += 1;
+            }
+        }
+
+        band_size
+    };
+
+    ScrollbarBand {
+        start_size: get_band_size(&|index| index),
+        end_size: get_band_size(&|index| cross_side_size - 1 - index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, create_window};
+    use image::Luma;
+    use snow_shot_app_shared::ElementRect;
+
+    /// 滚动到 scroll_position 的窗口，右侧 12 像素为滚动条，最右侧一列为窗口边框
+    fn create_scrollbar_window(page: &GrayImage, scroll_position: u32) -> GrayImage {
+        let thumb_start = scroll_position / 4;
+        let content_rect = ElementRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: 287,
+            max_y: 200,
+        };
+
+        create_window(page, scroll_position, 300, 200, content_rect, |x, y| {
+            if x == 299 {
+                Luma([60])
+            } else if y >= thumb_start && y < thumb_start + 50 {
+                Luma([90])
+            } else {
+                Luma([230])
+            }
+        })
+    }
+
+    #[test]
+    fn test_detect_scrollbar_band() {
+        let page = image::DynamicImage::ImageRgb8(create_page(287, 800)).to_luma8();
+
+        let band = detect_scrollbar_band(
+            &create_scrollbar_window(&page, 0),
+            &create_scrollbar_window(&page, 60),
+            ScrollDirection::Vertical,
+            60,
+            0,
+        );
+        // 边框没有变化，但位于滚动条外侧，也会被裁剪
+        assert_eq!(band.start_size, 0);
+        assert_eq!(band.end_size, 13);
+
+        // 偏移错误时内容区域无法对齐，不做检测
+        let band = detect_scrollbar_band(
+            &create_scrollbar_window(&page, 0),
+            &create_scrollbar_window(&page, 60),
+            ScrollDirection::Vertical,
+            50,
+            0,
+        );
+        assert!(band.is_empty());
+    }
+}
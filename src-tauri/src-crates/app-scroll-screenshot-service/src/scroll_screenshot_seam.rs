@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ScrollSeamMode {
+    /// 直接覆盖重叠区域
+    Overwrite = 0,
+    /// 在重叠区域内渐变过渡
+    Feather = 1,
+    /// 在重叠区域内选取差异最小的一行（列）作为接缝
+    MinDifference = 2,
+}
+
+/// 图片与已拼接内容的重叠区域
+#[derive(Debug, Clone, Copy)]
+pub struct SeamOverlap {
+    /// 重叠区域尺寸（滚动方向）
+    pub size: usize,
+    /// 重叠区域是否位于图片头部（上或左），否则位于尾部（下或右）
+    pub at_start: bool,
+}
+
+impl SeamOverlap {
+    /// 重叠区域在图片中的行（列）范围
+    pub fn line_range(&self, line_count: usize) -> (usize, usize) {
+        let size = self.size.min(line_count);
+        if self.at_start {
+            (0, size)
+        } else {
+            (line_count - size, line_count)
+        }
+    }
+}
+
+/**
+ * 按权重将图片像素混合到已拼接的像素上
+ */
+pub fn blend_pixel(image_pixel: &mut [u8], target_image_pixel: &[u8], weight: f32) {
+    if weight >= 1.0 {
+        image_pixel.copy_from_slice(target_image_pixel);
+        return;
+    }
+
+    if weight <= 0.0 {
+        return;
+    }
+
+    image_pixel
+        .iter_mut()
+        .zip(target_image_pixel.iter())
+        .for_each(|(image_value, target_image_value)| {
+            *image_value = (*target_image_value as f32 * weight
+                + *image_value as f32 * (1.0 - weight))
+                .round() as u8;
+        });
+}
+
+/**
+ * 渐变过渡时重叠区域内每一行（列）图片的权重，0 表示保留已拼接的内容
+ */
+pub fn get_feather_weights(overlap: SeamOverlap) -> Vec<f32> {
+    (0..overlap.size)
+        .map(|line_offset| {
+            let weight = (line_offset + 1) as f32 / (overlap.size + 1) as f32;
+            if overlap.at_start {
+                weight
+            } else {
+                1.0 - weight
+            }
+        })
+        .collect()
+}
+
+/**
+ * 根据重叠区域内每一行（列）与已拼接内容的差异，在差异最小处切换到图片
+ * 差异相同时优先选择靠近重叠区域中间的位置
+ */
+pub fn get_min_difference_weights(overlap: SeamOverlap, line_diff_list: &[u64]) -> Vec<f32> {
+    let seam_line = line_diff_list
+        .iter()
+        .enumerate()
+        .map(|(line_offset, diff)| (*diff, line_offset.abs_diff(overlap.size / 2), line_offset))
+        .min()
+        .map(|(_, _, line_offset)| line_offset)
+        .unwrap_or(0);
+
+    (0..overlap.size)
+        .map(|line_offset| {
+            let use_target_image = if overlap.at_start {
+                line_offset >= seam_line
+            } else {
+                line_offset <= seam_line
+            };
+
+            if use_target_image { 1.0 } else { 0.0 }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seam_weights() {
+        let overlap = SeamOverlap {
+            size: 3,
+            at_start: true,
+        };
+
+        assert_eq!(get_feather_weights(overlap), vec![0.25, 0.5, 0.75]);
+        // 第二行差异最小，接缝选在该行
+        assert_eq!(
+            get_min_difference_weights(overlap, &[30, 0, 20]),
+            vec![0.0, 1.0, 1.0]
+        );
+
+        let overlap = SeamOverlap {
+            size: 3,
+            at_start: false,
+        };
+        assert_eq!(get_feather_weights(overlap), vec![0.75, 0.5, 0.25]);
+        assert_eq!(
+            get_min_difference_weights(overlap, &[30, 0, 20]),
+            vec![1.0, 1.0, 0.0]
+        );
+
+        let mut pixel = [0, 100, 200];
+        blend_pixel(&mut pixel, &[200, 200, 200], 0.5);
+        assert_eq!(pixel, [100, 150, 200]);
+    }
+}
@@ -1,13 +1,23 @@
 use fast_image_resize::{PixelType, Resizer, images::Image};
 use fast_image_resize::{ResizeAlg, ResizeOptions};
-use hora::core::ann_index::ANNIndex;
-use hora::core::metrics::Metric;
-use hora::index::{hnsw_idx::HNSWIndex, hnsw_params::HNSWParams};
 use image::{DynamicImage, GenericImageView, GrayImage};
-use imageproc::corners;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use snow_shot_app_shared::ElementRect;
+
+use crate::scroll_screenshot_dynamic_mask::ScrollDynamicMask;
+use crate::scroll_screenshot_frame_store::{
+    ScrollFrameImage, ScrollFrameStore, normalize_image_color,
+};
+use crate::scroll_screenshot_matcher::{
+    ScrollFeatures, ScrollMatchConstraint, ScrollMatchQuality, ScrollMatchResult, ScrollMatcher,
+    ScrollMatcherType, create_scroll_matcher,
+};
+use crate::scroll_screenshot_scrollbar_band::{
+    ScrollbarBand, ScrollbarReference, detect_scrollbar_band,
+};
+use crate::scroll_screenshot_seam::ScrollSeamMode;
+use crate::scroll_screenshot_sticky_band::{StickyBand, detect_sticky_band};
 
 #[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum ScrollDirection {
@@ -25,7 +35,7 @@ pub enum ScrollImageList {
     Bottom = 1,
 }
 
-#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct ScrollOffset {
     pub x: i32,
     pub y: i32,
@@ -37,7 +47,7 @@ impl ScrollOffset {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub struct CropRegion {
     pub x: u32,
     pub y: u32,
@@ -56,32 +66,52 @@ impl CropRegion {
     }
 }
 
-#[derive(Debug)]
 pub struct ScrollIndex {
     pub position: i32,
-    pub ann_index: HNSWIndex<f32, usize>,
-    pub corners: Vec<ScrollOffset>,
-    pub descriptors: Vec<Vec<f32>>,
+    /// 匹配器提取的特征，撤销或恢复会话时根据灰度图重新提取
+    pub features: Option<Box<dyn ScrollFeatures>>,
+    /// 建立索引的图片的缩放灰度图
+    pub gray_image: Option<GrayImage>,
+    /// 建立索引的图片相对首帧在滚动垂直方向上的偏移
+    pub cross_position: i32,
+    /// 建立索引的图片的原尺寸灰度图，用于校正滚动垂直方向上的偏移
+    pub luma_image: Option<GrayImage>,
 }
 
 impl ScrollIndex {
-    pub fn new(dimension: usize) -> Self {
-        let mut index_params = HNSWParams::<f32>::default();
-        index_params.ef_search = 24;
-        index_params.ef_build = 12;
-
+    pub fn new() -> Self {
         Self {
             position: 0,
-            ann_index: HNSWIndex::new(dimension, &index_params),
-            corners: vec![],
-            descriptors: vec![],
+            features: None,
+            gray_image: None,
+            cross_position: 0,
+            luma_image: None,
         }
     }
 }
 
 pub struct ScrollImage {
-    pub image: image::DynamicImage,
+    /// 压缩保存的图片，使用时解码
+    pub image: ScrollFrameImage,
     pub overlay_size: i32,
+    /// 相对首帧在滚动垂直方向上的偏移
+    pub cross_offset: i32,
+}
+
+/// 追加图片的记录，用于撤销
+pub struct ScrollImageRecord {
+    /// 图片追加到的列表
+    pub scroll_image_list: ScrollImageList,
+    /// 追加的尺寸（方向边）
+    pub delta_size: i32,
+    /// 追加的索引尺寸（方向边）
+    pub index_delta_size: i32,
+    /// 被替换的边缘索引，不保留特征，撤销时重新提取
+    pub replaced_index: Option<ScrollIndex>,
+    /// 追加前的贴边区域，撤销时恢复
+    pub scrollbar_band: ScrollbarBand,
+    /// 追加前的贴边区域参考帧，撤销时恢复
+    pub scrollbar_reference: Option<ScrollbarReference>,
 }
 
 pub struct ScrollScreenshotService {
@@ -89,6 +119,8 @@ pub struct ScrollScreenshotService {
     pub top_image_list: Vec<ScrollImage>,
     /// 滚动截图列表（下或右）
     pub bottom_image_list: Vec<ScrollImage>,
+    /// 按追加顺序排列的图片记录
+    pub image_record_list: Vec<ScrollImageRecord>,
     /// 当前方向
     pub current_direction: ScrollDirection,
     /// 图片宽度
@@ -107,6 +139,18 @@ pub struct ScrollScreenshotService {
     pub image_scale: f32,
     /// 图片缩放器
     pub image_resizer: Resizer,
+    /// 匹配方式
+    pub matcher_type: ScrollMatcherType,
+    /// 匹配器
+    pub matcher: Box<dyn ScrollMatcher>,
+    /// 最近一次处理图片的匹配质量，首帧或未匹配时为 None
+    pub last_match_quality: Option<ScrollMatchQuality>,
+    /// 不稳定区域的掩码，提取特征时忽略
+    pub dynamic_mask: ScrollDynamicMask,
+    /// 调用方指定的排除区域，坐标相对截图
+    pub exclusion_rect_list: Vec<ElementRect>,
+    /// 上一帧缩放后的灰度图，用于检测不稳定区域
+    pub last_gray_image: Option<GrayImage>,
     /// 特征点阈值
     pub corner_threshold: u8,
     /// 描述符块大小
@@ -123,8 +167,6 @@ pub struct ScrollScreenshotService {
     pub image_dst_height: u32,
     /// 滚动方向的图片尺寸
     pub image_scroll_side_size: i32,
-    /// 是否启用 fast12 算法进行角点检测
-    pub enable_corner_fast12: Option<bool>,
     /// 是否尝试回滚
     pub try_rollback: bool,
     /// 采样率
@@ -133,84 +175,38 @@ pub struct ScrollScreenshotService {
     pub min_sample_size: u32,
     /// 最大采样尺寸
     pub max_sample_size: u32,
+    /// 滚动垂直方向上允许的最大偏移（原图像素），为 0 时不允许偏移
+    pub cross_axis_tolerance: u32,
+    /// 导出时重叠区域的接缝处理方式
+    pub seam_mode: ScrollSeamMode,
+    /// 是否检测固定区域（吸顶导航栏、吸底横幅等）
+    pub enable_sticky_band: bool,
+    /// 固定区域检测是否已完成
+    pub sticky_band_resolved: bool,
+    /// 固定区域检测的参考帧（首帧原图）
+    pub sticky_band_reference: Option<DynamicImage>,
+    /// 检测到的固定区域
+    pub sticky_band: StickyBand,
+    /// 头部固定区域图片（上或左），导出时只保留一份
+    pub sticky_header_image: Option<DynamicImage>,
+    /// 尾部固定区域图片（下或右），导出时只保留一份
+    pub sticky_footer_image: Option<DynamicImage>,
+    /// 是否裁剪滚动条等与滚动不同步的贴边区域
+    pub enable_scrollbar_trim: bool,
+    /// 检测到的贴边区域，导出时从每张图片中裁剪
+    pub scrollbar_band: ScrollbarBand,
+    /// 贴边区域检测的参考帧
+    pub scrollbar_reference: Option<ScrollbarReference>,
+    /// 图片的压缩和溢出，需要在图片列表之后释放
+    pub frame_store: ScrollFrameStore,
 }
 
 impl ScrollScreenshotService {
-    fn get_descriptor_size(&self) -> usize {
-        self.descriptor_patch_size & !1
-    }
-
-    fn compute_descriptor(&self, img: &image::GrayImage, corner: &ScrollOffset) -> Vec<f32> {
-        let descriptor_size = self.descriptor_patch_size;
-        let mut descriptor = Vec::with_capacity(self.get_descriptor_size());
-        let half_size = descriptor_size as i32 / 2;
-
-        let corner_x = corner.x;
-        let corner_y = corner.y;
-        let width = img.width() as i32;
-        let height = img.height() as i32;
-
-        // 计算行特征
-        for row in 0..(descriptor_size / 2) {
-            let y = corner_y + (-half_size + row as i32 * 2);
-            let mut sum = 0.0;
-            let mut valid_pixels = 0;
-
-            for col in 0..(descriptor_size / 2) {
-                let x = corner_x + (-half_size + col as i32 * 2);
-
-                if x >= 0 && x < width && y >= 0 && y < height {
-                    let pixel = unsafe { img.unsafe_get_pixel(x as u32, y as u32) };
-                    sum += pixel[0] as f32 / 255.0;
-                    valid_pixels += 1;
-                }
-            }
-
-            descriptor.push(if valid_pixels > 0 {
-                sum / valid_pixels as f32
-            } else {
-                0.0
-            });
-        }
-
-        // 计算列特征
-        for col in 0..(descriptor_size / 2) {
-            let x = corner_x + (-half_size + col as i32 * 2);
-            let mut sum = 0.0;
-            let mut valid_pixels = 0;
-
-            for row in 0..(descriptor_size / 2) {
-                let y = corner_y + (-half_size + row as i32 * 2);
-
-                if x >= 0 && x < width && y >= 0 && y < height {
-                    let pixel = unsafe { img.unsafe_get_pixel(x as u32, y as u32) };
-                    sum += pixel[0] as f32 / 255.0;
-                    valid_pixels += 1;
-                }
-            }
-
-            descriptor.push(if valid_pixels > 0 {
-                sum / valid_pixels as f32
-            } else {
-                0.0
-            });
-        }
-
-        descriptor
-    }
-
-    fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
-        a.iter()
-            .zip(b.iter())
-            .map(|(x, y)| (x - y).powi(2))
-            .sum::<f32>()
-            .sqrt()
-    }
-
     pub fn new() -> Self {
         Self {
             top_image_list: vec![],
             bottom_image_list: vec![],
+            image_record_list: vec![],
             current_direction: ScrollDirection::Vertical,
             image_width: 0,
             image_height: 0,
@@ -220,27 +216,88 @@ impl ScrollScreenshotService {
             bottom_image_index_size: 0,
             image_scale: 1.0,
             image_resizer: Resizer::new(),
+            matcher_type: ScrollMatcherType::Corner,
+            matcher: create_scroll_matcher(
+                ScrollMatcherType::Corner,
+                ScrollDirection::Vertical,
+                64,
+                9,
+            ),
+            last_match_quality: None,
+            dynamic_mask: ScrollDynamicMask::new(0, 0),
+            exclusion_rect_list: vec![],
+            last_gray_image: None,
             corner_threshold: 64,
             descriptor_patch_size: 9,
             min_size_delta: 64,
             image_dst_width: 0,
             image_dst_height: 0,
             image_scroll_side_size: 0,
-            top_image_ann_index: ScrollIndex::new(0),
-            bottom_image_ann_index: ScrollIndex::new(0),
-            enable_corner_fast12: None,
+            top_image_ann_index: ScrollIndex::new(),
+            bottom_image_ann_index: ScrollIndex::new(),
             try_rollback: false,
             sample_rate: 0.0,
             min_sample_size: 0,
             max_sample_size: 0,
+            cross_axis_tolerance: 0,
+            seam_mode: ScrollSeamMode::Overwrite,
+            enable_sticky_band: true,
+            sticky_band_resolved: false,
+            sticky_band_reference: None,
+            sticky_band: StickyBand::default(),
+            sticky_header_image: None,
+            sticky_footer_image: None,
+            enable_scrollbar_trim: true,
+            scrollbar_band: ScrollbarBand::default(),
+            scrollbar_reference: None,
+            frame_store: ScrollFrameStore::new(),
         }
     }
 
     pub fn clear(&mut self) {
         self.top_image_list.clear();
         self.bottom_image_list.clear();
-        self.top_image_ann_index = ScrollIndex::new(0);
-        self.bottom_image_ann_index = ScrollIndex::new(0);
+        self.image_record_list.clear();
+        self.top_image_ann_index = ScrollIndex::new();
+        self.bottom_image_ann_index = ScrollIndex::new();
+        self.clear_sticky_band();
+    }
+
+    fn clear_sticky_band(&mut self) {
+        self.sticky_band_resolved = false;
+        self.sticky_band_reference = None;
+        self.sticky_band = StickyBand::default();
+        self.sticky_header_image = None;
+        self.sticky_footer_image = None;
+    }
+
+    /**
+     * 清空已拼接的图片和索引，保留拼接参数
+     */
+    fn reset_image_state(&mut self) {
+        self.top_image_list.clear();
+        self.bottom_image_list.clear();
+        self.image_record_list.clear();
+        self.image_width = 0;
+        self.image_height = 0;
+        self.top_image_size = 0;
+        self.bottom_image_size = 0;
+        self.top_image_index_size = 0;
+        self.bottom_image_index_size = 0;
+        self.last_match_quality = None;
+        self.dynamic_mask = ScrollDynamicMask::new(0, 0);
+        self.last_gray_image = None;
+        self.top_image_ann_index = ScrollIndex::new();
+        self.bottom_image_ann_index = ScrollIndex::new();
+        self.scrollbar_band = ScrollbarBand::default();
+        self.scrollbar_reference = None;
+        // 匹配器可能保存了根据首帧确定的状态，需要重新创建
+        self.matcher = create_scroll_matcher(
+            self.matcher_type,
+            self.current_direction,
+            self.corner_threshold,
+            self.descriptor_patch_size,
+        );
     }
 
     pub fn init(
@@ -253,26 +310,24 @@ impl ScrollScreenshotService {
         descriptor_patch_size: usize,
         min_size_delta: i32,
         try_rollback: bool,
+        cross_axis_tolerance: u32,
+        seam_mode: ScrollSeamMode,
+        matcher_type: ScrollMatcherType,
     ) {
-        self.top_image_list.clear();
-        self.bottom_image_list.clear();
         self.current_direction = direction;
-        self.image_width = 0;
-        self.image_height = 0;
-        self.top_image_size = 0;
-        self.bottom_image_size = 0;
+        self.matcher_type = matcher_type;
         self.corner_threshold = corner_threshold;
         self.descriptor_patch_size = descriptor_patch_size;
         self.min_size_delta = min_size_delta;
-        self.top_image_index_size = 0;
-        self.bottom_image_index_size = 0;
-        self.top_image_ann_index = ScrollIndex::new(self.get_descriptor_size());
-        self.bottom_image_ann_index = ScrollIndex::new(self.get_descriptor_size());
+        self.reset_image_state();
+        self.clear_sticky_band();
+        self.exclusion_rect_list.clear();
         self.try_rollback = try_rollback;
-        self.enable_corner_fast12 = None;
         self.sample_rate = sample_rate;
         self.min_sample_size = min_sample_size;
         self.max_sample_size = max_sample_size;
+        self.cross_axis_tolerance = cross_axis_tolerance;
+        self.seam_mode = seam_mode;
     }
 
     pub fn init_image_size(&mut self, image_width: u32, image_height: u32) {
@@ -305,16 +360,81 @@ impl ScrollScreenshotService {
         } else {
             self.image_width as i32
         };
+
+        self.dynamic_mask = ScrollDynamicMask::new(self.image_dst_width, self.image_dst_height);
+        self.dynamic_mask
+            .set_exclusion_rects(self.get_scaled_exclusion_rects());
     }
 
-    fn get_descriptors(
-        &self,
-        image: &image::ImageBuffer<image::Luma<u8>, Vec<u8>>,
-        corners: &[ScrollOffset],
-    ) -> Vec<Vec<f32>> {
-        corners
-            .par_iter()
-            .map(|corner| self.compute_descriptor(image, corner))
+    /**
+     * 设置排除区域，坐标相对截取到的图片，区域内的内容不参与匹配
+     */
+    pub fn set_exclusion_rects(&mut self, exclusion_rect_list: Vec<ElementRect>) {
+        self.exclusion_rect_list = exclusion_rect_list;
+        self.dynamic_mask
+            .set_exclusion_rects(self.get_scaled_exclusion_rects());
+    }
+
+    /**
+     * 设置压缩图片的内存预算（字节），超出后较早的图片会溢出到临时目录，为 0 时不限制
+     */
+    pub fn set_frame_memory_budget(&mut self, frame_memory_budget: usize) {
+        self.frame_store.set_memory_budget(frame_memory_budget);
+        self.enforce_frame_memory_budget();
+    }
+
+    /**
+     * 设置是否裁剪滚动条等与滚动不同步的贴边区域，关闭时导出完整的图片
+     */
+    pub fn set_scrollbar_trim(&mut self, enable_scrollbar_trim: bool) {
+        self.enable_scrollbar_trim = enable_scrollbar_trim;
+        if !enable_scrollbar_trim {
+            self.scrollbar_reference = None;
+        }
+    }
+
+    pub(crate) fn enforce_frame_memory_budget(&mut self) {
+        let frame_list = self
+            .top_image_list
+            .iter_mut()
+            .chain(self.bottom_image_list.iter_mut())
+            .map(|scroll_image| &mut scroll_image.image);
+
+        // 溢出失败时图片保留在内存中，不影响拼接
+        if let Err(e) = self.frame_store.enforce_memory_budget(frame_list) {
+            log::warn!(
+                "[ScrollScreenshotService::enforce_frame_memory_budget] {}",
+                e
+            );
+        }
+    }
+
+    /**
+     * 将排除区域转换到缩放后的灰度图坐标
+     * 去掉固定区域后，滚动方向上的坐标需要减去头部固定区域的尺寸
+     */
+    fn get_scaled_exclusion_rects(&self) -> Vec<ElementRect> {
+        let header_size = self.sticky_band.header_size as i32;
+
+        self.exclusion_rect_list
+            .iter()
+            .map(|rect| {
+                if self.current_direction == ScrollDirection::Vertical {
+                    ElementRect {
+                        min_x: (rect.min_x as f32 * self.image_scale).floor() as i32,
+                        min_y: rect.min_y - header_size,
+                        max_x: (rect.max_x as f32 * self.image_scale).ceil() as i32,
+                        max_y: rect.max_y - header_size,
+                    }
+                } else {
+                    ElementRect {
+                        min_x: rect.min_x - header_size,
+                        min_y: (rect.min_y as f32 * self.image_scale).floor() as i32,
+                        max_x: rect.max_x - header_size,
+                        max_y: (rect.max_y as f32 * self.image_scale).ceil() as i32,
+                    }
+                }
+            })
             .collect()
     }
 
@@ -389,61 +509,20 @@ impl ScrollScreenshotService {
         region
     }
 
-    fn get_corners(&mut self, image: &image::GrayImage) -> Vec<ScrollOffset> {
-        let corners;
-        if self.enable_corner_fast12.is_none() {
-            let fast12_corners = corners::corners_fast12(image, self.corner_threshold);
-
-            if fast12_corners.len() > 200 {
-                corners = fast12_corners;
-                self.enable_corner_fast12 = Some(true);
-            } else {
-                corners = corners::corners_fast9(image, self.corner_threshold);
-                self.enable_corner_fast12 = Some(false);
-            }
-        } else {
-            if self.enable_corner_fast12.unwrap() {
-                corners = corners::corners_fast12(image, self.corner_threshold);
-            } else {
-                corners = corners::corners_fast9(image, self.corner_threshold);
-            }
-        }
-
-        corners
-            .iter()
-            .map(|corner| ScrollOffset {
-                x: corner.x as i32,
-                y: corner.y as i32,
-            })
-            .collect()
-    }
-
     fn build_index(
         &mut self,
         gray_image: image::GrayImage,
-        image_corners: &[ScrollOffset],
+        features: Box<dyn ScrollFeatures>,
+        luma_image: Option<GrayImage>,
         edge_position: i32,
         index_edge_position_distance: i32,
-    ) {
-        let mut new_scroll_index = ScrollIndex::new(self.get_descriptor_size());
-
-        new_scroll_index.descriptors = self.get_descriptors(&gray_image, &image_corners);
-
-        new_scroll_index.corners = image_corners.to_vec();
-
-        let mut index_params = HNSWParams::<f32>::default();
-        index_params.ef_search = 32;
-        index_params.ef_build = 16;
-
-        new_scroll_index
-            .descriptors
-            .iter()
-            .enumerate()
-            .for_each(|(i, descriptor)| {
-                new_scroll_index.ann_index.add(descriptor, i).unwrap();
-            });
-
-        new_scroll_index.ann_index.build(Metric::Euclidean).unwrap();
+        cross_position: i32,
+    ) -> ScrollIndex {
+        let mut new_scroll_index = ScrollIndex::new();
+        new_scroll_index.cross_position = cross_position;
+        new_scroll_index.luma_image = luma_image;
+        new_scroll_index.features = Some(self.matcher.build_index_features(features));
+        new_scroll_index.gray_image = Some(gray_image);
 
         let index_position = if edge_position > 0 {
             self.bottom_image_index_size - index_edge_position_distance
@@ -453,22 +532,44 @@ impl ScrollScreenshotService {
 
         new_scroll_index.position = index_position;
 
-        if edge_position > 0 {
-            self.bottom_image_ann_index = new_scroll_index;
+        let mut replaced_index = if edge_position > 0 {
+            std::mem::replace(&mut self.bottom_image_ann_index, new_scroll_index)
         } else {
-            self.top_image_ann_index = new_scroll_index;
-        }
+            std::mem::replace(&mut self.top_image_ann_index, new_scroll_index)
+        };
+
+        // 撤销时根据灰度图重新提取特征
+        replaced_index.features = None;
+
+        replaced_index
+    }
+
+    /**
+     * 根据索引的灰度图重新提取特征
+     */
+    pub(crate) fn rebuild_index_features(&mut self, index: &mut ScrollIndex) {
+        index.features = index
+            .gray_image
+            .as_ref()
+            .and_then(|gray_image| {
+                self.matcher
+                    .extract_features(gray_image, &self.dynamic_mask)
+            })
+            .map(|features| self.matcher.build_index_features(features));
     }
 
     fn add_index(
         &mut self,
         image: image::DynamicImage,
         gray_image: image::GrayImage,
-        image_corners: Vec<ScrollOffset>,
+        features: Box<dyn ScrollFeatures>,
+        luma_image: Option<GrayImage>,
         edge_position: i32,
         delta_size: i32,
-    ) -> (ScrollImage, i32) {
+        cross_offset: i32,
+    ) -> (ScrollImage, i32, Option<ScrollIndex>) {
         let mut index_delta_size = 0;
+        let mut replaced_index = None;
 
         let image_scroll_side_size = self.image_scroll_side_size;
 
@@ -480,12 +581,14 @@ impl ScrollScreenshotService {
 
         if index_edge_position_distance <= self.min_size_delta {
             index_delta_size = image_scroll_side_size - index_edge_position_distance;
-            self.build_index(
+            replaced_index = Some(self.build_index(
                 gray_image,
-                &image_corners,
+                features,
+                luma_image,
                 edge_position,
                 index_edge_position_distance,
-            );
+                cross_offset,
+            ));
         }
 
         // 一半的区域在拼接时允许
@@ -500,15 +603,17 @@ impl ScrollScreenshotService {
 
         (
             ScrollImage {
-                image: image.crop_imm(
+                image: self.frame_store.create_frame(&image.crop_imm(
                     crop_region.x,
                     crop_region.y,
                     crop_region.width,
                     crop_region.height,
-                ),
+                )),
                 overlay_size: image_overlay_size,
+                cross_offset,
             },
             index_delta_size,
+            replaced_index,
         )
     }
 
@@ -516,20 +621,23 @@ impl ScrollScreenshotService {
         &mut self,
         image: image::DynamicImage,
         gray_image: image::GrayImage,
-        image_corners: Vec<ScrollOffset>,
+        features: Box<dyn ScrollFeatures>,
+        luma_image: Option<GrayImage>,
         index_position: i32,
-        origin_position: ScrollOffset,
-        new_position: ScrollOffset,
+        match_offset: ScrollOffset,
+        cross_offset: i32,
+        scrollbar_band: ScrollbarBand,
+        scrollbar_reference: Option<ScrollbarReference>,
     ) -> (i32, Option<ScrollImageList>) {
         let position_offset = if self.current_direction == ScrollDirection::Vertical {
             ScrollOffset {
-                x: origin_position.x - new_position.x,
-                y: origin_position.y - new_position.y + index_position,
+                x: match_offset.x,
+                y: match_offset.y + index_position,
             }
         } else {
             ScrollOffset {
-                x: origin_position.x - new_position.x + index_position,
-                y: origin_position.y - new_position.y,
+                x: match_offset.x + index_position,
+                y: match_offset.y,
             }
         };
 
@@ -564,31 +672,65 @@ impl ScrollScreenshotService {
                 return (edge_position, None); // 没有新增区域或变化太小
             };
 
-        let (cropped_image, index_delta_size) =
-            self.add_index(image, gray_image, image_corners, edge_position, delta_size);
+        let (cropped_image, index_delta_size, replaced_index) = self.add_index(
+            image,
+            gray_image,
+            features,
+            luma_image,
+            edge_position,
+            delta_size,
+            cross_offset,
+        );
 
-        if is_bottom {
+        let scroll_image_list = if is_bottom {
             self.bottom_image_list.push(cropped_image);
             self.bottom_image_size += delta_size;
             self.bottom_image_index_size += index_delta_size;
 
-            (edge_position, Some(ScrollImageList::Bottom))
+            ScrollImageList::Bottom
         } else {
             self.top_image_list.push(cropped_image);
             self.top_image_size -= delta_size;
             self.top_image_index_size += index_delta_size;
 
-            (edge_position, Some(ScrollImageList::Top))
+            ScrollImageList::Top
+        };
+        self.enforce_frame_memory_budget();
+
+        self.image_record_list.push(ScrollImageRecord {
+            scroll_image_list,
+            delta_size,
+            index_delta_size,
+            replaced_index,
+            scrollbar_band,
+            scrollbar_reference,
+        });
+
+        (edge_position, Some(scroll_image_list))
+    }
+
+    fn get_scroll_index(&self, scroll_image_list: ScrollImageList) -> &ScrollIndex {
+        if scroll_image_list == ScrollImageList::Top {
+            &self.top_image_ann_index
+        } else {
+            &self.bottom_image_ann_index
         }
     }
 
-    pub fn get_offsets<'a>(
+    /**
+     * 匹配新图片与指定列表边缘的索引
+     */
+    fn match_scroll_index(
         &self,
-        index: &'a ScrollIndex,
-        image_descriptors: &[Vec<f32>],
-        image_corners: &[ScrollOffset],
+        features: &dyn ScrollFeatures,
         scroll_image_list: ScrollImageList,
-    ) -> (Option<(&'a ScrollIndex, usize, usize)>, bool) {
+    ) -> ScrollMatchResult {
+        let index = self.get_scroll_index(scroll_image_list);
+        let index_features = match index.features.as_ref() {
+            Some(index_features) => index_features,
+            None => return ScrollMatchResult::NotMatched,
+        };
+
         let image_scroll_side_size = if self.current_direction == ScrollDirection::Vertical {
             self.image_height as i32
         } else {
@@ -600,130 +742,255 @@ impl ScrollScreenshotService {
             (self.top_image_size + 1) + index.position
         };
 
-        let min_diff_count = AtomicUsize::new(0);
+        let constraint = ScrollMatchConstraint {
+            min_diff,
+            // 匹配在缩放后的图片上进行，允许的偏移同样需要缩放
+            cross_axis_tolerance: (self.cross_axis_tolerance as f32 * self.image_scale).ceil()
+                as i32,
+        };
 
-        let offsets: Vec<(i32, &'a ScrollIndex, usize, usize)> = image_descriptors
-            .par_iter()
-            .enumerate()
-            .filter_map(|(i, descriptor)| {
-                let search_result = index.ann_index.search(descriptor, 1);
-                if search_result.is_empty() {
-                    return None;
-                }
+        self.matcher
+            .match_features(index_features.as_ref(), features, &constraint)
+    }
 
-                let idx1 = search_result[0];
-                let dist = Self::euclidean_distance(&index.descriptors[idx1], descriptor);
+    /**
+     * 计算新图片相对索引图片在滚动垂直方向上的偏移（原图像素）
+     * 匹配器只能给出缩放后的粗略偏移，在原尺寸灰度图上搜索重叠区域差异最小的偏移
+     */
+    fn get_cross_offset(
+        &self,
+        index: &ScrollIndex,
+        luma_image: Option<&GrayImage>,
+        match_offset: ScrollOffset,
+    ) -> i32 {
+        let (index_luma_image, luma_image) = match (index.luma_image.as_ref(), luma_image) {
+            (Some(index_luma_image), Some(luma_image)) => (index_luma_image, luma_image),
+            _ => return 0,
+        };
 
-                let point1 = &index.corners[idx1];
-                let point2 = &image_corners[i];
-                let dy = point2.y - point1.y;
-                let dx = point2.x - point1.x;
+        let is_vertical = self.current_direction == ScrollDirection::Vertical;
+        let (scroll_offset, estimated_cross_offset) = if is_vertical {
+            (match_offset.y, match_offset.x)
+        } else {
+            (match_offset.x, match_offset.y)
+        };
 
-                let diff: i32 = if self.current_direction == ScrollDirection::Vertical {
-                    if dx != 0 {
-                        return None;
-                    }
+        // 缩放后的一个像素对应原图的多个像素，在该范围内搜索
+        let search_radius = (1.0 / self.image_scale).ceil() as i32;
+        let estimated_cross_offset =
+            (estimated_cross_offset as f32 / self.image_scale).round() as i32;
+        let cross_axis_tolerance = self.cross_axis_tolerance as i32;
 
-                    dy
-                } else {
-                    if dy != 0 {
+        let (scroll_side_size, cross_side_size) = if is_vertical {
+            (luma_image.height() as i32, luma_image.width() as i32)
+        } else {
+            (luma_image.width() as i32, luma_image.height() as i32)
+        };
+
+        // 新图片与索引图片在滚动方向上的重叠区域
+        let scroll_start = (-scroll_offset).max(0);
+        let scroll_end = scroll_side_size.min(scroll_side_size - scroll_offset);
+        if scroll_start >= scroll_end {
+            return estimated_cross_offset.clamp(-cross_axis_tolerance, cross_axis_tolerance);
+        }
+
+        let get_pixel = |image: &GrayImage, scroll: i32, cross: i32| -> u32 {
+            let (x, y) = if is_vertical {
+                (cross, scroll)
+            } else {
+                (scroll, cross)
+            };
+
+            unsafe { image.unsafe_get_pixel(x as u32, y as u32)[0] as u32 }
+        };
+
+        let best_cross_offset =
+            ((estimated_cross_offset - search_radius).max(-cross_axis_tolerance)
+                ..=(estimated_cross_offset + search_radius).min(cross_axis_tolerance))
+                .into_par_iter()
+                .filter_map(|cross_offset| {
+                    let cross_start = (-cross_offset).max(0);
+                    let cross_end = cross_side_size.min(cross_side_size - cross_offset);
+                    if cross_start >= cross_end {
                         return None;
                     }
 
-                    dx
-                };
-
-                if min_diff < 0 && min_diff < diff {
-                    min_diff_count.fetch_add(1, Ordering::Relaxed);
-                    return None;
-                }
+                    // 隔行隔列采样即可区分偏移
+                    let mut diff_sum: u64 = 0;
+                    let mut pixel_count: u64 = 0;
+                    for scroll in (scroll_start..scroll_end).step_by(2) {
+                        for cross in (cross_start..cross_end).step_by(2) {
+                            let pixel = get_pixel(luma_image, scroll, cross);
+                            let index_pixel = get_pixel(
+                                index_luma_image,
+                                scroll + scroll_offset,
+                                cross + cross_offset,
+                            );
+
+                            diff_sum += pixel.abs_diff(index_pixel) as u64;
+                            pixel_count += 1;
+                        }
+                    }
 
-                if min_diff > 0 && min_diff > diff {
-                    min_diff_count.fetch_add(1, Ordering::Relaxed);
-                    return None;
-                }
+                    Some((
+                        diff_sum * 1024 / pixel_count,
+                        cross_offset.abs(),
+                        cross_offset,
+                    ))
+                })
+                .min();
+
+        match best_cross_offset {
+            Some((_, _, cross_offset)) => cross_offset,
+            None => 0,
+        }
+    }
 
-                if dist < 0.1 {
-                    Some((diff, index, idx1, i))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /**
+     * 检测固定区域，检测完成后只将滚动内容交给拼接流程
+     * 首帧作为参考帧，出现第一张发生滚动的帧时完成检测
+     * 带有透明区域的图片按 RGBA 处理，导出时保留透明度
+     */
+    pub fn handle_image(
+        &mut self,
+        image: DynamicImage,
+        scroll_image_list: ScrollImageList,
+    ) -> (
+        Option<(i32, Option<ScrollImageList>)>,
+        bool,
+        ScrollImageList,
+    ) {
+        let image = normalize_image_color(image);
 
-        if min_diff_count.load(Ordering::Relaxed) > (image_corners.len() as f32 * 0.72) as usize {
-            return (None, true);
+        if !self.enable_sticky_band {
+            return self.handle_content_image(image, scroll_image_list);
         }
 
-        if offsets.is_empty() {
-            return (None, false);
+        if self.sticky_band_resolved {
+            let image = self.crop_sticky_band_content(image);
+            return self.handle_content_image(image, scroll_image_list);
         }
 
-        // 寻找频率最高的偏移作为主要偏移模式
-        let mut offset_counts: std::collections::HashMap<i32, (i32, &ScrollIndex, usize, usize)> =
-            std::collections::HashMap::new();
-        for (offset, scroll_index, origin_position_index, new_position_index) in offsets {
-            if let Some(value) = offset_counts.get_mut(&offset) {
-                value.0 += 1;
-            } else {
-                offset_counts.insert(
-                    offset,
-                    (1, scroll_index, origin_position_index, new_position_index),
-                );
+        let reference_image = match self.sticky_band_reference.take() {
+            Some(reference_image) => reference_image,
+            None => {
+                if self.top_image_list.is_empty() && self.bottom_image_list.is_empty() {
+                    self.sticky_band_reference = Some(image.clone());
+                }
+
+                return self.handle_content_image(image, scroll_image_list);
             }
-        }
+        };
 
-        // let mut sorted_offsets: Vec<_> = offset_counts.iter().collect();
-        // sorted_offsets.sort_by_key(|(_, (count, _, _, _))| -count);
-        // println!(
-        //     "sorted_offsets: {:?}",
-        //     sorted_offsets[..10.min(sorted_offsets.len())]
-        //         .iter()
-        //         .map(|(offset, (count, _, _, _))| (offset, count))
-        //         .collect::<Vec<_>>()
-        // );
-
-        let mut max_count = 0;
-        let mut second_max_count = 0;
-        let mut max_offset = None;
-
-        for (_, (count, scroll_index, origin_idx, new_idx)) in &offset_counts {
-            if *count > max_count {
-                second_max_count = max_count;
-                max_count = *count;
-                max_offset = Some((scroll_index, origin_idx, new_idx));
-            } else if *count > second_max_count {
-                second_max_count = *count;
+        let sticky_band = match detect_sticky_band(&reference_image, &image, self.current_direction)
+        {
+            Some(sticky_band) => sticky_band,
+            None => {
+                // 未发生滚动，继续等待
+                self.sticky_band_reference = Some(reference_image);
+                return self.handle_content_image(image, scroll_image_list);
             }
+        };
+
+        self.sticky_band_resolved = true;
+        if sticky_band.is_empty() {
+            return self.handle_content_image(image, scroll_image_list);
         }
 
-        let max_offset = match max_offset {
-            Some(offset) => offset,
-            None => return (None, false),
+        let (image_width, image_height) = (reference_image.width(), reference_image.height());
+        let header_region =
+            sticky_band.header_region(image_width, image_height, self.current_direction);
+        let footer_region =
+            sticky_band.footer_region(image_width, image_height, self.current_direction);
+
+        self.sticky_band = sticky_band;
+        self.sticky_header_image = Some(reference_image.crop_imm(
+            header_region.x,
+            header_region.y,
+            header_region.width,
+            header_region.height,
+        ));
+        self.sticky_footer_image = Some(reference_image.crop_imm(
+            footer_region.x,
+            footer_region.y,
+            footer_region.width,
+            footer_region.height,
+        ));
+
+        // 参考帧的特征点包含固定区域，去掉固定区域后重新建立索引
+        self.reset_image_state();
+        let reference_image = self.crop_sticky_band_content(reference_image);
+        self.handle_content_image(reference_image, ScrollImageList::Bottom);
+
+        let image = self.crop_sticky_band_content(image);
+        self.handle_content_image(image, scroll_image_list)
+    }
+
+    fn crop_sticky_band_content(&self, image: DynamicImage) -> DynamicImage {
+        let image_scroll_side_size = if self.current_direction == ScrollDirection::Vertical {
+            image.height()
+        } else {
+            image.width()
         };
 
-        if max_count < (image_corners.len() as i32 / 10) {
-            return (None, false);
+        // 尺寸异常的帧交给后续流程拒绝
+        if self.sticky_band.is_empty()
+            || image_scroll_side_size <= self.sticky_band.header_size + self.sticky_band.footer_size
+        {
+            return image;
         }
 
-        if max_count < second_max_count * 2 {
-            return (None, false);
+        let content_region =
+            self.sticky_band
+                .content_region(image.width(), image.height(), self.current_direction);
+
+        image.crop_imm(
+            content_region.x,
+            content_region.y,
+            content_region.width,
+            content_region.height,
+        )
+    }
+
+    /**
+     * 与上一张匹配成功的图片对比，更新贴边区域
+     * position 和 cross_offset 为图片相对首帧的位置
+     * 返回更新前的参考帧，用于撤销
+     */
+    fn update_scrollbar_band(
+        &mut self,
+        image: &DynamicImage,
+        luma_image: Option<&GrayImage>,
+        position: i32,
+        cross_offset: i32,
+    ) -> Option<ScrollbarReference> {
+        if !self.enable_scrollbar_trim {
+            return None;
         }
 
-        let (dominant_scroll_index, dominant_origin_position_index, dominant_new_position_index) =
-            max_offset;
+        let luma_image = match luma_image {
+            Some(luma_image) => luma_image.clone(),
+            None => image.to_luma8(),
+        };
 
-        (
-            Some((
-                dominant_scroll_index,
-                *dominant_origin_position_index,
-                *dominant_new_position_index,
-            )),
-            false,
-        )
+        if let Some(reference) = self.scrollbar_reference.as_ref() {
+            self.scrollbar_band.merge(detect_scrollbar_band(
+                &reference.luma_image,
+                &luma_image,
+                self.current_direction,
+                position - reference.position,
+                cross_offset - reference.cross_offset,
+            ));
+        }
+
+        self.scrollbar_reference.replace(ScrollbarReference {
+            luma_image,
+            position,
+            cross_offset,
+        })
     }
 
-    pub fn handle_image(
+    fn handle_content_image(
         &mut self,
         image: DynamicImage,
         scroll_image_list: ScrollImageList,
@@ -732,6 +999,8 @@ impl ScrollScreenshotService {
         bool,
         ScrollImageList,
     ) {
+        self.last_match_quality = None;
+
         let image_width = image.width();
         let image_height = image.height();
 
@@ -745,43 +1014,54 @@ impl ScrollScreenshotService {
 
         let gray_image = self.get_gray_image(&image);
 
-        // 提取当前图片的特征点
-        let image_corners = self.get_corners(&gray_image);
-
-        if image_corners.is_empty() {
-            return (None, false, scroll_image_list);
+        // 同一滚动位置的相邻两帧中变化的区域是不稳定的
+        if let Some(last_gray_image) = self.last_gray_image.as_ref() {
+            self.dynamic_mask.update(last_gray_image, &gray_image);
         }
+        self.last_gray_image = Some(gray_image.clone());
+
+        // 提取当前图片的特征
+        let features = match self
+            .matcher
+            .extract_features(&gray_image, &self.dynamic_mask)
+        {
+            Some(features) => features,
+            None => return (None, false, scroll_image_list),
+        };
 
-        let image_descriptors = self.get_descriptors(&gray_image, &image_corners);
+        // 允许偏移时保留原尺寸灰度图，用于校正偏移
+        let luma_image = if self.cross_axis_tolerance > 0 {
+            Some(image.to_luma8())
+        } else {
+            None
+        };
 
         if self.top_image_list.is_empty() && self.bottom_image_list.is_empty() {
+            let scrollbar_band = self.scrollbar_band;
+            let scrollbar_reference = self.update_scrollbar_band(&image, luma_image.as_ref(), 0, 0);
+
+            let top_gray_image = gray_image.clone();
+            let top_features = self
+                .matcher
+                .extract_features(&top_gray_image, &self.dynamic_mask);
+
             let bottom_image = self.push_image(
                 image,
                 gray_image,
-                image_corners.clone(),
+                features,
+                luma_image.clone(),
                 0,
                 ScrollOffset { x: 0, y: 0 },
-                ScrollOffset { x: 0, y: 0 },
+                0,
+                scrollbar_band,
+                scrollbar_reference,
             );
 
-            let mut new_top_image_ann_index = ScrollIndex::new(self.get_descriptor_size());
-            new_top_image_ann_index.descriptors = image_descriptors;
-            new_top_image_ann_index.corners = image_corners;
-            new_top_image_ann_index
-                .descriptors
-                .iter()
-                .enumerate()
-                .for_each(|(i, descriptor)| {
-                    new_top_image_ann_index
-                        .ann_index
-                        .add(descriptor, i)
-                        .unwrap();
-                });
-
-            new_top_image_ann_index
-                .ann_index
-                .build(Metric::Euclidean)
-                .unwrap();
+            let mut new_top_image_ann_index = ScrollIndex::new();
+            new_top_image_ann_index.luma_image = luma_image;
+            new_top_image_ann_index.features =
+                top_features.map(|features| self.matcher.build_index_features(features));
+            new_top_image_ann_index.gray_image = Some(top_gray_image);
 
             self.top_image_ann_index = new_top_image_ann_index;
 
@@ -789,204 +1069,120 @@ impl ScrollScreenshotService {
         }
 
         // 优先从指定方向遍历，如果没有则再从另一个方向遍历
-        let mut result_scroll_image_list;
-        let first_index = if scroll_image_list == ScrollImageList::Top {
-            result_scroll_image_list = ScrollImageList::Top;
-
-            &self.top_image_ann_index
-        } else {
-            result_scroll_image_list = ScrollImageList::Bottom;
-
-            &self.bottom_image_ann_index
-        };
+        let mut result_scroll_image_list = scroll_image_list;
 
         // 从边缘遍历
-        let mut offsets;
-        let (first_offsets, is_origin) = self.get_offsets(
-            first_index,
-            &image_descriptors,
-            &image_corners,
-            scroll_image_list,
-        );
+        let mut match_result = self.match_scroll_index(features.as_ref(), scroll_image_list);
 
-        if is_origin {
+        if match_result == ScrollMatchResult::NoNewArea {
             return (None, true, result_scroll_image_list);
         }
 
-        offsets = first_offsets;
-
         // 如果第一个方向没有找到匹配，尝试另一个方向
-        if offsets.is_none() && self.try_rollback {
-            let second_index = if scroll_image_list == ScrollImageList::Top {
-                &self.bottom_image_ann_index
-            } else {
-                &self.top_image_ann_index
-            };
-
+        if match_result == ScrollMatchResult::NotMatched && self.try_rollback {
             let second_scroll_image_list = if scroll_image_list == ScrollImageList::Top {
                 ScrollImageList::Bottom
             } else {
                 ScrollImageList::Top
             };
 
-            let (second_offsets, is_origin) = self.get_offsets(
-                second_index,
-                &image_descriptors,
-                &image_corners,
-                second_scroll_image_list,
-            );
+            match_result = self.match_scroll_index(features.as_ref(), second_scroll_image_list);
 
-            if is_origin {
+            if match_result == ScrollMatchResult::NoNewArea {
                 return (None, true, result_scroll_image_list);
             }
 
             result_scroll_image_list = second_scroll_image_list;
-
-            offsets = second_offsets;
         }
 
-        if offsets.is_none() {
-            return (None, false, result_scroll_image_list);
-        }
+        let match_offset = match match_result {
+            ScrollMatchResult::Matched(match_offset, match_quality) => {
+                self.last_match_quality = Some(match_quality);
+                match_offset
+            }
+            _ => return (None, false, result_scroll_image_list),
+        };
 
-        let (dominant_scroll_index, dominant_origin_position_index, dominant_new_position_index) =
-            match offsets {
-                Some(offsets) => offsets,
-                None => return (None, false, scroll_image_list),
-            };
+        let match_index = self.get_scroll_index(result_scroll_image_list);
+        let cross_offset = match_index.cross_position
+            + self.get_cross_offset(match_index, luma_image.as_ref(), match_offset);
+        let index_position = match_index.position;
 
-        let origin_position = dominant_scroll_index.corners[dominant_origin_position_index];
-        let new_position = image_corners[dominant_new_position_index];
+        let scroll_position = if self.current_direction == ScrollDirection::Vertical {
+            match_offset.y
+        } else {
+            match_offset.x
+        } + index_position;
+        let scrollbar_band = self.scrollbar_band;
+        let scrollbar_reference =
+            self.update_scrollbar_band(&image, luma_image.as_ref(), scroll_position, cross_offset);
 
         // 将偏移的图片推到列表中
         (
             Some(self.push_image(
                 image,
                 gray_image,
-                image_corners,
-                dominant_scroll_index.position,
-                origin_position,
-                new_position,
+                features,
+                luma_image,
+                index_position,
+                match_offset,
+                cross_offset,
+                scrollbar_band,
+                scrollbar_reference,
             )),
             false,
             result_scroll_image_list,
         )
     }
 
-    pub fn export(&mut self) -> Option<image::DynamicImage> {
-        if self.top_image_list.is_empty() && self.bottom_image_list.is_empty() {
-            return None;
-        }
-
-        // 计算最终图片尺寸
-        let (total_width, total_height) = if self.current_direction == ScrollDirection::Vertical {
-            (
-                self.image_width as usize,
-                (self.top_image_size + self.bottom_image_size) as usize,
-            )
-        } else {
-            (
-                (self.top_image_size + self.bottom_image_size) as usize,
-                self.image_height as usize,
-            )
-        };
-
-        const RGB_CHANNEL_COUNT: usize = 3;
-
-        // 创建最终大小的图片
-        let mut final_image = unsafe {
-            let mut vec = Vec::with_capacity(total_width * total_height * RGB_CHANNEL_COUNT);
-            vec.set_len(total_width * total_height * RGB_CHANNEL_COUNT);
-            vec
-        };
-
-        // 当前位置偏移量
-        let mut offset_x: i32 = 0;
-        let mut offset_y: i32 = 0;
-
-        // top 会覆盖 bottom，优先从 bottom 开始
-        if self.current_direction == ScrollDirection::Vertical {
-            // 垂直方向，从顶部开始
-            offset_y = self.top_image_size as i32;
+    /**
+     * 撤销最后追加的图片，恢复图片尺寸、贴边区域并重建被替换的边缘索引
+     * 返回图片所在的列表，没有可撤销的图片时返回 None
+     */
+    pub fn undo(&mut self) -> Option<ScrollImageList> {
+        let record = self.image_record_list.pop()?;
+
+        if record.scroll_image_list == ScrollImageList::Bottom {
+            self.bottom_image_list.pop();
+            self.bottom_image_size -= record.delta_size;
+            self.bottom_image_index_size -= record.index_delta_size;
         } else {
-            // 水平方向，从左侧开始
-            offset_x = self.top_image_size as i32;
+            self.top_image_list.pop();
+            self.top_image_size += record.delta_size;
+            self.top_image_index_size -= record.index_delta_size;
         }
+        self.scrollbar_band = record.scrollbar_band;
+        self.scrollbar_reference = record.scrollbar_reference;
 
-        for scroll_image in self.bottom_image_list.iter() {
-            let img = &scroll_image.image;
-            let overlay_size = scroll_image.overlay_size;
-
-            if self.current_direction == ScrollDirection::Vertical {
-                // 垂直拼接
-                snow_shot_app_utils::overlay_image(
-                    &mut final_image,
-                    total_width,
-                    img,
-                    0,
-                    (offset_y - overlay_size) as usize,
-                    RGB_CHANNEL_COUNT,
-                );
-
-                offset_y += (img.height() as i32 - overlay_size) as i32;
-            } else {
-                // 水平拼接
-                snow_shot_app_utils::overlay_image(
-                    &mut final_image,
-                    total_width,
-                    img,
-                    (offset_x - overlay_size) as usize,
-                    0,
-                    RGB_CHANNEL_COUNT,
-                );
-                offset_x += (img.width() as i32 - overlay_size) as i32;
+        // 撤销首帧后回到初始状态，参考帧也需要重新选取
+        if self.top_image_list.is_empty() && self.bottom_image_list.is_empty() {
+            self.reset_image_state();
+            if !self.sticky_band_resolved {
+                self.sticky_band_reference = None;
             }
-        }
 
-        // 最先推入的图片优先级最低，所以从尾部开始
-        if self.current_direction == ScrollDirection::Vertical {
-            offset_y = self.top_image_size as i32;
-        } else {
-            offset_x = self.top_image_size as i32;
+            return Some(record.scroll_image_list);
         }
 
-        for scroll_image in self.top_image_list.iter() {
-            let img = &scroll_image.image;
-            let overlay_size = scroll_image.overlay_size;
-
-            if self.current_direction == ScrollDirection::Vertical {
-                // 垂直拼接
-                let actual_height = img.height() as i32 + overlay_size;
-
-                snow_shot_app_utils::overlay_image(
-                    &mut final_image,
-                    total_width,
-                    img,
-                    0,
-                    (offset_y - actual_height) as usize,
-                    RGB_CHANNEL_COUNT,
-                );
+        if let Some(mut replaced_index) = record.replaced_index {
+            self.rebuild_index_features(&mut replaced_index);
 
-                offset_y -= actual_height;
+            if record.scroll_image_list == ScrollImageList::Bottom {
+                self.bottom_image_ann_index = replaced_index;
             } else {
-                let actual_width = img.width() as i32 + overlay_size;
-
-                // 水平拼接
-                snow_shot_app_utils::overlay_image(
-                    &mut final_image,
-                    total_width,
-                    img,
-                    (offset_x - actual_width) as usize,
-                    0,
-                    RGB_CHANNEL_COUNT,
-                );
-                offset_x -= actual_width;
+                self.top_image_ann_index = replaced_index;
             }
         }
 
-        Some(image::DynamicImage::ImageRgb8(
-            image::RgbImage::from_raw(total_width as u32, total_height as u32, final_image)
-                .unwrap(),
-        ))
+        Some(record.scroll_image_list)
+    }
+
+    pub fn export(&mut self) -> Option<image::DynamicImage> {
+        let layout = self.get_export_layout().ok()?;
+        let image = layout
+            .render_region_image(CropRegion::new(0, 0, layout.width, layout.height))
+            .ok()?;
+
+        Some(image)
     }
 }
@@ -0,0 +1,593 @@
+use std::fs;
+use std::path::Path;
+
+use image::{DynamicImage, GrayImage, ImageFormat};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use snow_shot_app_shared::ElementRect;
+
+use crate::scroll_screenshot_frame_store::{ScrollFrameStore, normalize_image_color};
+use crate::scroll_screenshot_matcher::ScrollMatcherType;
+use crate::scroll_screenshot_scrollbar_band::{ScrollbarBand, ScrollbarReference};
+use crate::scroll_screenshot_seam::ScrollSeamMode;
+use crate::scroll_screenshot_service::{
+    ScrollDirection, ScrollImage, ScrollIndex, ScrollScreenshotService,
+};
+use crate::scroll_screenshot_sticky_band::StickyBand;
+
+/// 会话格式版本，格式不兼容时递增
+const SESSION_VERSION: u32 = 2;
+const SESSION_FILE_NAME: &str = "session.json";
+/// 会话写入的图片文件名前缀，保存时清理带有这些前缀但不再使用的文件
+const SESSION_FILE_PREFIX_LIST: [&str; 4] = ["top_", "bottom_", "sticky_", "scrollbar_"];
+
+#[derive(Serialize, Deserialize)]
+struct ScrollSessionImage {
+    file_name: String,
+    overlay_size: i32,
+    cross_offset: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScrollSessionIndex {
+    position: i32,
+    cross_position: i32,
+    /// 特征由匹配器根据灰度图重新提取
+    gray_file_name: Option<String>,
+    luma_file_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScrollSessionScrollbarReference {
+    luma_file_name: String,
+    position: i32,
+    cross_offset: i32,
+}
+
+/// 会话目录中的 session.json，图片以 PNG 文件保存在同一目录
+#[derive(Serialize, Deserialize)]
+struct ScrollSessionMetadata {
+    version: u32,
+    direction: ScrollDirection,
+    sample_rate: f32,
+    min_sample_size: u32,
+    max_sample_size: u32,
+    corner_threshold: u8,
+    descriptor_patch_size: usize,
+    min_size_delta: i32,
+    try_rollback: bool,
+    cross_axis_tolerance: u32,
+    seam_mode: ScrollSeamMode,
+    matcher_type: ScrollMatcherType,
+    image_width: u32,
+    image_height: u32,
+    top_image_size: i32,
+    top_image_index_size: i32,
+    bottom_image_size: i32,
+    bottom_image_index_size: i32,
+    top_image_list: Vec<ScrollSessionImage>,
+    bottom_image_list: Vec<ScrollSessionImage>,
+    top_image_index: ScrollSessionIndex,
+    bottom_image_index: ScrollSessionIndex,
+    enable_sticky_band: bool,
+    sticky_band_resolved: bool,
+    sticky_band: StickyBand,
+    sticky_band_reference_file_name: Option<String>,
+    sticky_header_file_name: Option<String>,
+    sticky_footer_file_name: Option<String>,
+    enable_scrollbar_trim: bool,
+    scrollbar_band: ScrollbarBand,
+    scrollbar_reference: Option<ScrollSessionScrollbarReference>,
+    exclusion_rect_list: Vec<ElementRect>,
+}
+
+impl ScrollSessionMetadata {
+    /**
+     * 会话引用的所有图片文件
+     */
+    fn get_file_name_list(&self) -> Vec<&str> {
+        let image_file_name_list = self
+            .top_image_list
+            .iter()
+            .chain(self.bottom_image_list.iter())
+            .map(|session_image| Some(&session_image.file_name));
+        let other_file_name_list = [
+            self.top_image_index.gray_file_name.as_ref(),
+            self.top_image_index.luma_file_name.as_ref(),
+            self.bottom_image_index.gray_file_name.as_ref(),
+            self.bottom_image_index.luma_file_name.as_ref(),
+            self.sticky_band_reference_file_name.as_ref(),
+            self.sticky_header_file_name.as_ref(),
+            self.sticky_footer_file_name.as_ref(),
+            self.scrollbar_reference
+                .as_ref()
+                .map(|scrollbar_reference| &scrollbar_reference.luma_file_name),
+        ];
+
+        image_file_name_list
+            .chain(other_file_name_list)
+            .flatten()
+            .map(|file_name| file_name.as_str())
+            .collect()
+    }
+}
+
+fn save_png(image: &DynamicImage, directory: &Path, file_name: &str) -> Result<(), String> {
+    image
+        .save_with_format(directory.join(file_name), ImageFormat::Png)
+        .map_err(|e| format!("[save_png] Failed to save image: {} {}", e, file_name))
+}
+
+fn load_image(directory: &Path, file_name: &str) -> Result<DynamicImage, String> {
+    image::open(directory.join(file_name))
+        .map_err(|e| format!("[load_image] Failed to open image: {} {}", e, file_name))
+}
+
+fn save_optional_png(
+    image: Option<&DynamicImage>,
+    directory: &Path,
+    file_name: &str,
+) -> Result<Option<String>, String> {
+    match image {
+        Some(image) => {
+            save_png(image, directory, file_name)?;
+            Ok(Some(file_name.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn load_optional_image(
+    directory: &Path,
+    file_name: &Option<String>,
+) -> Result<Option<DynamicImage>, String> {
+    match file_name {
+        Some(file_name) => Ok(Some(normalize_image_color(load_image(
+            directory, file_name,
+        )?))),
+        None => Ok(None),
+    }
+}
+
+fn save_image_list(
+    image_list: &[ScrollImage],
+    directory: &Path,
+    prefix: &str,
+) -> Result<Vec<ScrollSessionImage>, String> {
+    image_list
+        .par_iter()
+        .enumerate()
+        .map(|(image_index, scroll_image)| {
+            let file_name = format!("{}_{:04}.png", prefix, image_index);
+            save_png(&scroll_image.image.decode()?, directory, &file_name)?;
+
+            Ok(ScrollSessionImage {
+                file_name,
+                overlay_size: scroll_image.overlay_size,
+                cross_offset: scroll_image.cross_offset,
+            })
+        })
+        .collect()
+}
+
+fn load_image_list(
+    image_list: &[ScrollSessionImage],
+    directory: &Path,
+    frame_store: &mut ScrollFrameStore,
+) -> Result<Vec<ScrollImage>, String> {
+    let loaded_image_list = image_list
+        .par_iter()
+        .map(|session_image| {
+            // 与拼接流程一致，带有透明区域的图片保留透明度
+            Ok(normalize_image_color(load_image(
+                directory,
+                &session_image.file_name,
+            )?))
+        })
+        .collect::<Result<Vec<DynamicImage>, String>>()?;
+
+    Ok(image_list
+        .iter()
+        .zip(loaded_image_list.iter())
+        .map(|(session_image, image)| ScrollImage {
+            image: frame_store.create_frame(image),
+            overlay_size: session_image.overlay_size,
+            cross_offset: session_image.cross_offset,
+        })
+        .collect())
+}
+
+fn save_gray_png(
+    image: Option<&GrayImage>,
+    directory: &Path,
+    file_name: &str,
+) -> Result<Option<String>, String> {
+    match image {
+        Some(image) => {
+            image
+                .save_with_format(directory.join(file_name), ImageFormat::Png)
+                .map_err(|e| {
+                    format!("[save_gray_png] Failed to save image: {} {}", e, file_name)
+                })?;
+            Ok(Some(file_name.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn load_gray_image(
+    directory: &Path,
+    file_name: &Option<String>,
+) -> Result<Option<GrayImage>, String> {
+    match file_name {
+        Some(file_name) => Ok(Some(load_image(directory, file_name)?.to_luma8())),
+        None => Ok(None),
+    }
+}
+
+fn save_index(
+    index: &ScrollIndex,
+    directory: &Path,
+    prefix: &str,
+) -> Result<ScrollSessionIndex, String> {
+    Ok(ScrollSessionIndex {
+        position: index.position,
+        cross_position: index.cross_position,
+        gray_file_name: save_gray_png(
+            index.gray_image.as_ref(),
+            directory,
+            &format!("{}_index_gray.png", prefix),
+        )?,
+        luma_file_name: save_gray_png(
+            index.luma_image.as_ref(),
+            directory,
+            &format!("{}_index_luma.png", prefix),
+        )?,
+    })
+}
+
+fn save_scrollbar_reference(
+    scrollbar_reference: Option<&ScrollbarReference>,
+    directory: &Path,
+) -> Result<Option<ScrollSessionScrollbarReference>, String> {
+    let scrollbar_reference = match scrollbar_reference {
+        Some(scrollbar_reference) => scrollbar_reference,
+        None => return Ok(None),
+    };
+
+    let luma_file_name = String::from("scrollbar_reference.png");
+    save_gray_png(
+        Some(&scrollbar_reference.luma_image),
+        directory,
+        &luma_file_name,
+    )?;
+
+    Ok(Some(ScrollSessionScrollbarReference {
+        luma_file_name,
+        position: scrollbar_reference.position,
+        cross_offset: scrollbar_reference.cross_offset,
+    }))
+}
+
+fn load_scrollbar_reference(
+    session_scrollbar_reference: Option<ScrollSessionScrollbarReference>,
+    directory: &Path,
+) -> Result<Option<ScrollbarReference>, String> {
+    let session_scrollbar_reference = match session_scrollbar_reference {
+        Some(session_scrollbar_reference) => session_scrollbar_reference,
+        None => return Ok(None),
+    };
+
+    Ok(Some(ScrollbarReference {
+        luma_image: load_image(directory, &session_scrollbar_reference.luma_file_name)?.to_luma8(),
+        position: session_scrollbar_reference.position,
+        cross_offset: session_scrollbar_reference.cross_offset,
+    }))
+}
+
+/**
+ * 删除上一次保存留下、但不在当前会话中的图片文件
+ * 只处理会话使用的文件名前缀，目录中的其他文件保持不变
+ */
+fn remove_stale_files(directory: &Path, metadata: &ScrollSessionMetadata) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("[remove_stale_files] Failed to read directory: {}", e);
+            return;
+        }
+    };
+
+    let file_name_list = metadata.get_file_name_list();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let file_name = match file_name.to_str() {
+            Some(file_name) => file_name,
+            None => continue,
+        };
+
+        if !file_name.ends_with(".png")
+            || !SESSION_FILE_PREFIX_LIST
+                .iter()
+                .any(|prefix| file_name.starts_with(prefix))
+            || file_name_list.contains(&file_name)
+        {
+            continue;
+        }
+
+        if let Err(e) = fs::remove_file(entry.path()) {
+            log::warn!(
+                "[remove_stale_files] Failed to remove file: {} {}",
+                e,
+                file_name
+            );
+        }
+    }
+}
+
+/**
+ * 读取索引的灰度图，特征在初始化匹配器后重新提取
+ */
+fn load_index(session_index: ScrollSessionIndex, directory: &Path) -> Result<ScrollIndex, String> {
+    let mut index = ScrollIndex::new();
+    index.position = session_index.position;
+    index.cross_position = session_index.cross_position;
+    index.gray_image = load_gray_image(directory, &session_index.gray_file_name)?;
+    index.luma_image = load_gray_image(directory, &session_index.luma_file_name)?;
+
+    Ok(index)
+}
+
+impl ScrollScreenshotService {
+    /**
+     * 将当前会话保存到目录，包括拼接参数、图片列表和索引
+     * 目录中已有的会话会被覆盖，会话描述文件在所有图片写入后才替换，之后删除不再使用的图片
+     */
+    pub fn save_session(&self, directory: &Path) -> Result<(), String> {
+        fs::create_dir_all(directory).map_err(|e| {
+            format!(
+                "[ScrollScreenshotService::save_session] Failed to create directory: {} {}",
+                e,
+                directory.display()
+            )
+        })?;
+
+        let metadata = ScrollSessionMetadata {
+            version: SESSION_VERSION,
+            direction: self.current_direction,
+            sample_rate: self.sample_rate,
+            min_sample_size: self.min_sample_size,
+            max_sample_size: self.max_sample_size,
+            corner_threshold: self.corner_threshold,
+            descriptor_patch_size: self.descriptor_patch_size,
+            min_size_delta: self.min_size_delta,
+            try_rollback: self.try_rollback,
+            cross_axis_tolerance: self.cross_axis_tolerance,
+            seam_mode: self.seam_mode,
+            matcher_type: self.matcher_type,
+            image_width: self.image_width,
+            image_height: self.image_height,
+            top_image_size: self.top_image_size,
+            top_image_index_size: self.top_image_index_size,
+            bottom_image_size: self.bottom_image_size,
+            bottom_image_index_size: self.bottom_image_index_size,
+            top_image_list: save_image_list(&self.top_image_list, directory, "top")?,
+            bottom_image_list: save_image_list(&self.bottom_image_list, directory, "bottom")?,
+            top_image_index: save_index(&self.top_image_ann_index, directory, "top")?,
+            bottom_image_index: save_index(&self.bottom_image_ann_index, directory, "bottom")?,
+            enable_sticky_band: self.enable_sticky_band,
+            sticky_band_resolved: self.sticky_band_resolved,
+            sticky_band: self.sticky_band,
+            sticky_band_reference_file_name: save_optional_png(
+                self.sticky_band_reference.as_ref(),
+                directory,
+                "sticky_band_reference.png",
+            )?,
+            sticky_header_file_name: save_optional_png(
+                self.sticky_header_image.as_ref(),
+                directory,
+                "sticky_header.png",
+            )?,
+            sticky_footer_file_name: save_optional_png(
+                self.sticky_footer_image.as_ref(),
+                directory,
+                "sticky_footer.png",
+            )?,
+            enable_scrollbar_trim: self.enable_scrollbar_trim,
+            scrollbar_band: self.scrollbar_band,
+            scrollbar_reference: save_scrollbar_reference(
+                self.scrollbar_reference.as_ref(),
+                directory,
+            )?,
+            exclusion_rect_list: self.exclusion_rect_list.clone(),
+        };
+
+        let metadata_content = serde_json::to_string(&metadata).map_err(|e| {
+            format!(
+                "[ScrollScreenshotService::save_session] Failed to serialize session: {}",
+                e
+            )
+        })?;
+
+        let temp_file_path = directory.join(format!("{}.tmp", SESSION_FILE_NAME));
+        fs::write(&temp_file_path, metadata_content).map_err(|e| {
+            format!(
+                "[ScrollScreenshotService::save_session] Failed to write session: {}",
+                e
+            )
+        })?;
+        fs::rename(&temp_file_path, directory.join(SESSION_FILE_NAME)).map_err(|e| {
+            format!(
+                "[ScrollScreenshotService::save_session] Failed to write session: {}",
+                e
+            )
+        })?;
+
+        remove_stale_files(directory, &metadata);
+
+        Ok(())
+    }
+
+    /**
+     * 从目录恢复会话，恢复后可以继续处理新的图片
+     * 恢复失败时当前会话保持不变
+     */
+    pub fn load_session(&mut self, directory: &Path) -> Result<(), String> {
+        let metadata_content =
+            fs::read_to_string(directory.join(SESSION_FILE_NAME)).map_err(|e| {
+                format!(
+                    "[ScrollScreenshotService::load_session] Failed to read session: {} {}",
+                    e,
+                    directory.display()
+                )
+            })?;
+        let metadata: ScrollSessionMetadata =
+            serde_json::from_str(&metadata_content).map_err(|e| {
+                format!(
+                    "[ScrollScreenshotService::load_session] Failed to parse session: {}",
+                    e
+                )
+            })?;
+
+        if metadata.version != SESSION_VERSION {
+            return Err(format!(
+                "[ScrollScreenshotService::load_session] Unsupported session version: {}",
+                metadata.version
+            ));
+        }
+
+        // 先读取所有文件，全部成功后再替换当前会话
+        let top_image_list =
+            load_image_list(&metadata.top_image_list, directory, &mut self.frame_store)?;
+        let bottom_image_list = load_image_list(
+            &metadata.bottom_image_list,
+            directory,
+            &mut self.frame_store,
+        )?;
+        let mut top_image_index = load_index(metadata.top_image_index, directory)?;
+        let mut bottom_image_index = load_index(metadata.bottom_image_index, directory)?;
+        let sticky_band_reference =
+            load_optional_image(directory, &metadata.sticky_band_reference_file_name)?;
+        let sticky_header_image =
+            load_optional_image(directory, &metadata.sticky_header_file_name)?;
+        let sticky_footer_image =
+            load_optional_image(directory, &metadata.sticky_footer_file_name)?;
+        let scrollbar_reference =
+            load_scrollbar_reference(metadata.scrollbar_reference, directory)?;
+
+        self.init(
+            metadata.direction,
+            metadata.sample_rate,
+            metadata.min_sample_size,
+            metadata.max_sample_size,
+            metadata.corner_threshold,
+            metadata.descriptor_patch_size,
+            metadata.min_size_delta,
+            metadata.try_rollback,
+            metadata.cross_axis_tolerance,
+            metadata.seam_mode,
+            metadata.matcher_type,
+        );
+
+        // 缩放比例等由图片尺寸计算得到
+        if metadata.image_width > 0 && metadata.image_height > 0 {
+            self.init_image_size(metadata.image_width, metadata.image_height);
+        }
+
+        self.rebuild_index_features(&mut top_image_index);
+        self.rebuild_index_features(&mut bottom_image_index);
+        self.top_image_size = metadata.top_image_size;
+        self.top_image_index_size = metadata.top_image_index_size;
+        self.bottom_image_size = metadata.bottom_image_size;
+        self.bottom_image_index_size = metadata.bottom_image_index_size;
+        self.top_image_list = top_image_list;
+        self.bottom_image_list = bottom_image_list;
+        self.enforce_frame_memory_budget();
+        self.top_image_ann_index = top_image_index;
+        self.bottom_image_ann_index = bottom_image_index;
+        self.enable_sticky_band = metadata.enable_sticky_band;
+        self.sticky_band_resolved = metadata.sticky_band_resolved;
+        self.sticky_band = metadata.sticky_band;
+        self.sticky_band_reference = sticky_band_reference;
+        self.sticky_header_image = sticky_header_image;
+        self.sticky_footer_image = sticky_footer_image;
+        self.enable_scrollbar_trim = metadata.enable_scrollbar_trim;
+        self.scrollbar_band = metadata.scrollbar_band;
+        self.scrollbar_reference = scrollbar_reference;
+        // 缩放后的排除区域依赖贴边区域，最后设置
+        self.set_exclusion_rects(metadata.exclusion_rect_list);
+        // 撤销记录不随会话保存，恢复后只能撤销之后追加的图片
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_service::ScrollImageList;
+    use crate::scroll_screenshot_stitch_service::{ScrollStitchFrameStatus, ScrollStitchOptions};
+    use crate::scroll_screenshot_test_utils::{TempDirectory, create_page, create_scroll_frames};
+
+    #[test]
+    fn test_save_and_load_session() {
+        let page = create_page(320, 1200);
+        let frames = create_scroll_frames(&page, 300, 100);
+
+        // 只拼接前半部分后保存
+        let mut service = ScrollScreenshotService::new();
+        service
+            .stitch_frames(&ScrollStitchOptions::default(), frames[..5].to_vec())
+            .unwrap();
+
+        service.set_exclusion_rects(vec![ElementRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: 16,
+            max_y: 16,
+        }]);
+
+        let temp_directory = TempDirectory::new();
+        let session_dir = temp_directory.path();
+        // 上一次保存留下的图片会被删除，其他文件保持不变
+        fs::write(session_dir.join("bottom_0099.png"), b"").unwrap();
+        fs::write(session_dir.join("other.png"), b"").unwrap();
+        service.save_session(session_dir).unwrap();
+        assert!(!session_dir.join("bottom_0099.png").exists());
+        assert!(session_dir.join("other.png").exists());
+
+        // 恢复后继续拼接剩余部分
+        let mut restored_service = ScrollScreenshotService::new();
+        restored_service.load_session(session_dir).unwrap();
+        assert_eq!(
+            restored_service.bottom_image_size,
+            service.bottom_image_size
+        );
+        assert_eq!(
+            restored_service.exclusion_rect_list,
+            service.exclusion_rect_list
+        );
+        assert!(service.scrollbar_reference.is_some());
+        assert_eq!(
+            restored_service
+                .scrollbar_reference
+                .as_ref()
+                .map(|scrollbar_reference| scrollbar_reference.position),
+            service
+                .scrollbar_reference
+                .as_ref()
+                .map(|scrollbar_reference| scrollbar_reference.position)
+        );
+
+        for frame in frames[5..].iter() {
+            assert_ne!(
+                restored_service.handle_frame(frame.clone(), ScrollImageList::Bottom),
+                ScrollStitchFrameStatus::NotMatched
+            );
+        }
+
+        let image = restored_service.export().unwrap();
+        assert_eq!(image.height(), 1200);
+        assert_eq!(image.to_rgb8().as_raw(), page.as_raw());
+    }
+}
@@ -0,0 +1,242 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_service::{CropRegion, ScrollDirection};
+
+/// 固定区域（吸顶导航栏、吸底横幅等）在滚动方向上的尺寸
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StickyBand {
+    /// 头部固定区域尺寸（上或左）
+    pub header_size: u32,
+    /// 尾部固定区域尺寸（下或右）
+    pub footer_size: u32,
+}
+
+impl StickyBand {
+    pub fn is_empty(&self) -> bool {
+        self.header_size == 0 && self.footer_size == 0
+    }
+
+    /// 头部固定区域的裁剪区域
+    pub fn header_region(&self, width: u32, height: u32, direction: ScrollDirection) -> CropRegion {
+        if direction == ScrollDirection::Vertical {
+            CropRegion::new(0, 0, width, self.header_size)
+        } else {
+            CropRegion::new(0, 0, self.header_size, height)
+        }
+    }
+
+    /// 尾部固定区域的裁剪区域
+    pub fn footer_region(&self, width: u32, height: u32, direction: ScrollDirection) -> CropRegion {
+        if direction == ScrollDirection::Vertical {
+            CropRegion::new(0, height - self.footer_size, width, self.footer_size)
+        } else {
+            CropRegion::new(width - self.footer_size, 0, self.footer_size, height)
+        }
+    }
+
+    /// 去掉固定区域后，滚动内容的裁剪区域
+    pub fn content_region(
+        &self,
+        width: u32,
+        height: u32,
+        direction: ScrollDirection,
+    ) -> CropRegion {
+        let band_size = self.header_size + self.footer_size;
+
+        if direction == ScrollDirection::Vertical {
+            CropRegion::new(0, self.header_size, width, height - band_size)
+        } else {
+            CropRegion::new(self.header_size, 0, width - band_size, height)
+        }
+    }
+}
+
+/// 按滚动方向逐行（垂直）或逐列（水平）读取像素
+struct ImageLines<'a> {
+    pixels: &'a [u8],
+    width: usize,
+    height: usize,
+    channel_count: usize,
+    direction: ScrollDirection,
+}
+
+impl<'a> ImageLines<'a> {
+    fn new(image: &'a DynamicImage, direction: ScrollDirection) -> Self {
+        Self {
+            pixels: image.as_bytes(),
+            width: image.width() as usize,
+            height: image.height() as usize,
+            channel_count: image.color().bytes_per_pixel() as usize,
+            direction,
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        if self.direction == ScrollDirection::Vertical {
+            self.height
+        } else {
+            self.width
+        }
+    }
+
+    fn pixel_count(&self) -> usize {
+        if self.direction == ScrollDirection::Vertical {
+            self.width
+        } else {
+            self.height
+        }
+    }
+
+    fn pixel(&self, line: usize, index: usize) -> &[u8] {
+        let (x, y) = if self.direction == ScrollDirection::Vertical {
+            (index, line)
+        } else {
+            (line, index)
+        };
+
+        let start = (y * self.width + x) * self.channel_count;
+        &self.pixels[start..start + self.channel_count]
+    }
+
+    fn line_equals(&self, other: &ImageLines, line: usize) -> bool {
+        if self.direction == ScrollDirection::Vertical {
+            let row_size = self.width * self.channel_count;
+            let start = line * row_size;
+            return self.pixels[start..start + row_size] == other.pixels[start..start + row_size];
+        }
+
+        (0..self.pixel_count()).all(|index| self.pixel(line, index) == other.pixel(line, index))
+    }
+
+    /// 整行（列）颜色一致，通常是留白，无法区分是否属于固定区域
+    fn line_is_uniform(&self, line: usize) -> bool {
+        let first_pixel = self.pixel(line, 0);
+        (1..self.pixel_count()).all(|index| self.pixel(line, index) == first_pixel)
+    }
+}
+
+/**
+ * 对比同一滚动会话中的两帧，找出内容移动时保持不变的头部和尾部区域
+ * 两帧完全一致时无法判断，返回 None
+ */
+pub fn detect_sticky_band(
+    reference_image: &DynamicImage,
+    image: &DynamicImage,
+    direction: ScrollDirection,
+) -> Option<StickyBand> {
+    if reference_image.width() != image.width()
+        || reference_image.height() != image.height()
+        || reference_image.color() != image.color()
+    {
+        return None;
+    }
+
+    let reference_lines = ImageLines::new(reference_image, direction);
+    let image_lines = ImageLines::new(image, direction);
+    let line_count = image_lines.line_count();
+
+    if line_count == 0 || image_lines.pixel_count() == 0 {
+        return None;
+    }
+
+    let leading_size = (0..line_count)
+        .take_while(|line| image_lines.line_equals(&reference_lines, *line))
+        .count();
+
+    // 没有发生滚动
+    if leading_size == line_count {
+        return None;
+    }
+
+    let trailing_size = (0..line_count)
+        .rev()
+        .take_while(|line| image_lines.line_equals(&reference_lines, *line))
+        .count();
+
+    // 固定区域不应超过三分之一，超过时大概率是滚动距离太小导致的大片相同内容
+    let max_band_size = line_count / 3;
+
+    // 靠近滚动内容一侧的纯色行可能是恰好相同的留白，不计入固定区域
+    let header_size = (0..leading_size)
+        .rev()
+        .find(|line| !image_lines.line_is_uniform(*line))
+        .map(|line| line + 1)
+        .unwrap_or(0);
+    let footer_size = (line_count - trailing_size..line_count)
+        .find(|line| !image_lines.line_is_uniform(*line))
+        .map(|line| line_count - line)
+        .unwrap_or(0);
+
+    Some(StickyBand {
+        header_size: if header_size <= max_band_size {
+            header_size as u32
+        } else {
+            0
+        },
+        footer_size: if footer_size <= max_band_size {
+            footer_size as u32
+        } else {
+            0
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{create_page, create_window};
+    use snow_shot_app_shared::ElementRect;
+
+    /// 顶部 16 像素为吸顶导航栏，底部 10 像素为吸底横幅，中间为滚动 scroll_offset 后的页面
+    fn create_frame(scroll_offset: u32) -> DynamicImage {
+        let page = create_page(64, 200);
+        let content_rect = ElementRect {
+            min_x: 0,
+            min_y: 16,
+            max_x: 64,
+            max_y: 110,
+        };
+
+        DynamicImage::ImageRgb8(create_window(
+            &page,
+            scroll_offset,
+            64,
+            120,
+            content_rect,
+            |x, y| {
+                if y < 16 {
+                    image::Rgb([(x * 4) as u8, 32, 64])
+                } else {
+                    image::Rgb([200, (x * 3) as u8, 16])
+                }
+            },
+        ))
+    }
+
+    #[test]
+    fn test_detect_sticky_band() {
+        let band = detect_sticky_band(
+            &create_frame(0),
+            &create_frame(24),
+            ScrollDirection::Vertical,
+        );
+
+        assert_eq!(
+            band,
+            Some(StickyBand {
+                header_size: 16,
+                footer_size: 10,
+            })
+        );
+
+        assert_eq!(
+            detect_sticky_band(
+                &create_frame(0),
+                &create_frame(0),
+                ScrollDirection::Vertical
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use std::path::Path;
+
+use image::DynamicImage;
+use serde::Serialize;
+
+use crate::scroll_screenshot_matcher::ScrollMatcherType;
+use crate::scroll_screenshot_seam::ScrollSeamMode;
+use crate::scroll_screenshot_service::{ScrollDirection, ScrollImageList, ScrollScreenshotService};
+
+/// 离线拼接参数，含义与 `ScrollScreenshotService::init` 的参数一致
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollStitchOptions {
+    /// 滚动方向
+    pub direction: ScrollDirection,
+    /// 帧序列追加到哪个图片列表，正向滚动的录制使用 Bottom
+    pub scroll_image_list: ScrollImageList,
+    /// 采样率
+    pub sample_rate: f32,
+    /// 最小采样尺寸
+    pub min_sample_size: u32,
+    /// 最大采样尺寸
+    pub max_sample_size: u32,
+    /// 特征点阈值
+    pub corner_threshold: u8,
+    /// 描述符块大小
+    pub descriptor_patch_size: usize,
+    /// 最小变化量，为空时取首帧滚动方向尺寸的 80%，与前端保持一致
+    pub min_size_delta: Option<i32>,
+    /// 是否尝试回滚
+    pub try_rollback: bool,
+    /// 滚动垂直方向上允许的最大偏移
+    pub cross_axis_tolerance: u32,
+    /// 重叠区域的接缝处理方式
+    pub seam_mode: ScrollSeamMode,
+    /// 帧匹配方式
+    pub matcher_type: ScrollMatcherType,
+    /// 是否裁剪滚动条等与滚动不同步的贴边区域
+    pub trim_scrollbar: bool,
+}
+
+impl Default for ScrollStitchOptions {
+    fn default() -> Self {
+        // 与前端滚动截图设置的默认值保持一致
+        Self {
+            direction: ScrollDirection::Vertical,
+            scroll_image_list: ScrollImageList::Bottom,
+            sample_rate: 1.0,
+            min_sample_size: 128,
+            max_sample_size: 128,
+            corner_threshold: 24,
+            descriptor_patch_size: 28,
+            min_size_delta: None,
+            try_rollback: true,
+            cross_axis_tolerance: 4,
+            seam_mode: ScrollSeamMode::Overwrite,
+            matcher_type: ScrollMatcherType::Corner,
+            trim_scrollbar: true,
+        }
+    }
+}
+
+/// 单帧的处理结果
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub enum ScrollStitchFrameStatus {
+    /// 帧带来了新的区域，已追加到图片列表
+    Appended,
+    /// 帧与已有内容一致，没有新增区域
+    Unchanged,
+    /// 没有匹配到已有内容
+    NotMatched,
+}
+
+pub struct ScrollStitchResult {
+    /// 拼接后的图片
+    pub image: DynamicImage,
+    /// 按输入顺序排列的每帧处理结果
+    pub frame_status_list: Vec<ScrollStitchFrameStatus>,
+    /// 按输入顺序排列的每帧相对首帧在滚动方向上的位置，未匹配或没有滚动的帧为 None
+    pub frame_position_list: Vec<Option<i32>>,
+}
+
+impl ScrollStitchResult {
+    pub fn count(&self, status: ScrollStitchFrameStatus) -> usize {
+        self.frame_status_list
+            .iter()
+            .filter(|frame_status| **frame_status == status)
+            .count()
+    }
+}
+
+impl ScrollScreenshotService {
+    /**
+     * 处理一帧图片，并将 handle_image 的返回值归类为帧处理结果
+     */
+    pub fn handle_frame(
+        &mut self,
+        image: DynamicImage,
+        scroll_image_list: ScrollImageList,
+    ) -> ScrollStitchFrameStatus {
+        self.handle_frame_with_position(image, scroll_image_list).0
+    }
+
+    /**
+     * 处理一帧图片，同时返回帧相对首帧在滚动方向上的位置
+     */
+    pub fn handle_frame_with_position(
+        &mut self,
+        image: DynamicImage,
+        scroll_image_list: ScrollImageList,
+    ) -> (ScrollStitchFrameStatus, Option<i32>) {
+        let (result, is_unchanged, _) = self.handle_image(image, scroll_image_list);
+        if is_unchanged {
+            return (ScrollStitchFrameStatus::Unchanged, None);
+        }
+
+        let (edge_position, scroll_image_list) = match result {
+            Some(result) => result,
+            None => return (ScrollStitchFrameStatus::NotMatched, None),
+        };
+
+        // 边缘位置为正时是图片的结束边，否则是图片的起始边
+        let image_scroll_side_size = if self.current_direction == ScrollDirection::Vertical {
+            self.image_height
+        } else {
+            self.image_width
+        } as i32;
+        let position = if edge_position >= 0 {
+            edge_position - image_scroll_side_size
+        } else {
+            edge_position
+        };
+
+        let frame_status = if scroll_image_list.is_some() {
+            ScrollStitchFrameStatus::Appended
+        } else {
+            ScrollStitchFrameStatus::Unchanged
+        };
+
+        (frame_status, Some(position))
+    }
+
+    /**
+     * 将按顺序截取好的帧序列拼接为一张图片，不依赖屏幕、窗口和 Tauri 状态
+     * 会重新初始化当前服务，之前的拼接结果会被清空
+     */
+    pub fn stitch_frames<I>(
+        &mut self,
+        options: &ScrollStitchOptions,
+        frames: I,
+    ) -> Result<ScrollStitchResult, String>
+    where
+        I: IntoIterator<Item = DynamicImage>,
+    {
+        self.stitch_core(options, frames.into_iter().map(Ok))
+    }
+
+    /**
+     * 从文件读取帧序列并拼接，文件按传入顺序处理
+     */
+    pub fn stitch_files<P>(
+        &mut self,
+        options: &ScrollStitchOptions,
+        file_paths: &[P],
+    ) -> Result<ScrollStitchResult, String>
+    where
+        P: AsRef<Path>,
+    {
+        self.stitch_core(
+            options,
+            file_paths.iter().map(|file_path| {
+                image::open(file_path.as_ref()).map_err(|e| {
+                    format!(
+                        "[ScrollScreenshotService::stitch_files] Failed to open frame: {} {}",
+                        e,
+                        file_path.as_ref().display()
+                    )
+                })
+            }),
+        )
+    }
+
+    fn stitch_core<I>(
+        &mut self,
+        options: &ScrollStitchOptions,
+        frames: I,
+    ) -> Result<ScrollStitchResult, String>
+    where
+        I: Iterator<Item = Result<DynamicImage, String>>,
+    {
+        let mut frame_status_list = Vec::new();
+        let mut frame_position_list = Vec::new();
+
+        for (frame_index, frame) in frames.enumerate() {
+            // 图片格式由拼接流程统一转换，带有透明区域的帧保留透明度
+            let frame = frame?;
+
+            // 首帧确定尺寸后再初始化，最小变化量依赖帧尺寸
+            if frame_index == 0 {
+                let min_size_delta = options.min_size_delta.unwrap_or_else(|| {
+                    let scroll_side_size = if options.direction == ScrollDirection::Vertical {
+                        frame.height()
+                    } else {
+                        frame.width()
+                    };
+
+                    (scroll_side_size as f32 * 0.8).ceil() as i32
+                });
+
+                self.init(
+                    options.direction,
+                    options.sample_rate,
+                    options.min_sample_size,
+                    options.max_sample_size,
+                    options.corner_threshold,
+                    options.descriptor_patch_size,
+                    min_size_delta,
+                    options.try_rollback,
+                    options.cross_axis_tolerance,
+                    options.seam_mode,
+                    options.matcher_type,
+                );
+                self.set_scrollbar_trim(options.trim_scrollbar);
+            }
+
+            let (frame_status, frame_position) =
+                self.handle_frame_with_position(frame, options.scroll_image_list);
+            frame_status_list.push(frame_status);
+            frame_position_list.push(frame_position);
+        }
+
+        if frame_status_list.is_empty() {
+            return Err(String::from(
+                "[ScrollScreenshotService::stitch_frames] No frames to stitch",
+            ));
+        }
+
+        let image = match self.export() {
+            Some(image) => image,
+            None => {
+                return Err(String::from(
+                    "[ScrollScreenshotService::stitch_frames] Failed to export image",
+                ));
+            }
+        };
+
+        Ok(ScrollStitchResult {
+            image,
+            frame_status_list,
+            frame_position_list,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll_screenshot_test_utils::{
+        create_page, create_scroll_frames, create_text_page, create_window, crop_frame,
+    };
+    use snow_shot_app_shared::ElementRect;
+
+    #[test]
+    fn test_stitch_frames() {
+        let page = create_page(320, 1200);
+        let frames = create_scroll_frames(&page, 300, 100);
+
+        // 帧之间完全对齐时，任何接缝处理方式都应还原页面
+        for seam_mode in [
+            ScrollSeamMode::Overwrite,
+            ScrollSeamMode::Feather,
+            ScrollSeamMode::MinDifference,
+        ] {
+            let options = ScrollStitchOptions {
+                seam_mode,
+                ..Default::default()
+            };
+
+            let mut service = ScrollScreenshotService::new();
+            let result = service.stitch_frames(&options, frames.clone()).unwrap();
+
+            assert_eq!(result.count(ScrollStitchFrameStatus::NotMatched), 0);
+            assert_eq!(result.image.width(), 320);
+            assert_eq!(result.image.height(), 1200);
+            assert_eq!(result.image.to_rgb8().as_raw(), page.as_raw());
+        }
+    }
+
+    #[test]
+    fn test_stitch_frames_with_matchers() {
+        let page = create_page(320, 1200);
+        let text_page = create_text_page(320, 1200);
+        for (matcher_type, page) in [
+            (ScrollMatcherType::Corner, &page),
+            (ScrollMatcherType::PhaseCorrelation, &page),
+            (ScrollMatcherType::RowHash, &page),
+            (ScrollMatcherType::PhaseCorrelation, &text_page),
+            (ScrollMatcherType::RowHash, &text_page),
+        ] {
+            let options = ScrollStitchOptions {
+                matcher_type,
+                ..Default::default()
+            };
+
+            let mut service = ScrollScreenshotService::new();
+            let result = service
+                .stitch_frames(&options, create_scroll_frames(page, 300, 100))
+                .unwrap();
+
+            assert_eq!(
+                result.count(ScrollStitchFrameStatus::NotMatched),
+                0,
+                "{:?}",
+                matcher_type
+            );
+            assert_eq!(result.image.height(), 1200, "{:?}", matcher_type);
+
+            let match_quality = service.last_match_quality.unwrap();
+            assert!(match_quality.inlier_count > 0, "{:?}", matcher_type);
+            assert!(
+                match_quality.confidence > 0.0 && match_quality.confidence <= 1.0,
+                "{:?}",
+                matcher_type
+            );
+            assert_eq!(
+                result.image.to_rgb8().as_raw(),
+                page.as_raw(),
+                "{:?}",
+                matcher_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_undo_frame() {
+        let page = create_page(320, 1200);
+        let frames = create_scroll_frames(&page, 300, 100);
+
+        let mut service = ScrollScreenshotService::new();
+        service
+            .stitch_frames(&ScrollStitchOptions::default(), frames[..6].to_vec())
+            .unwrap();
+        let bottom_image_size = service.bottom_image_size;
+        let image = service.export().unwrap();
+
+        // 撤销后恢复到追加之前的状态
+        assert_eq!(
+            service.handle_frame(frames[6].clone(), ScrollImageList::Bottom),
+            ScrollStitchFrameStatus::Appended
+        );
+        assert_eq!(service.undo(), Some(ScrollImageList::Bottom));
+        assert_eq!(service.bottom_image_size, bottom_image_size);
+        assert_eq!(service.export().unwrap().as_bytes(), image.as_bytes());
+
+        // 撤销后可以继续追加
+        for frame in frames[6..].iter() {
+            assert_eq!(
+                service.handle_frame(frame.clone(), ScrollImageList::Bottom),
+                ScrollStitchFrameStatus::Appended
+            );
+        }
+        assert_eq!(service.export().unwrap().to_rgb8().as_raw(), page.as_raw());
+
+        // 撤销所有图片后回到初始状态
+        while service.undo().is_some() {}
+        assert!(service.export().is_none());
+        assert_eq!(service.bottom_image_size, 0);
+    }
+
+    #[test]
+    fn test_stitch_frames_with_cross_axis_drift() {
+        let page = create_page(360, 1200);
+        let frame_height = 300;
+        // 每帧在水平方向上的位置，模拟页面滚动时的横向抖动
+        let frame_x_list = [20, 21, 23, 22, 20, 18, 19, 21, 24, 22];
+
+        let frames = frame_x_list.iter().enumerate().map(|(index, frame_x)| {
+            crop_frame(&page, *frame_x, index as u32 * 100, 320, frame_height)
+        });
+
+        let mut service = ScrollScreenshotService::new();
+        let result = service
+            .stitch_frames(&ScrollStitchOptions::default(), frames)
+            .unwrap();
+
+        // 只保留所有帧共同覆盖的区域
+        let expected_page = image::imageops::crop_imm(&page, 24, 0, 320 - 6, 1200).to_image();
+
+        assert_eq!(result.count(ScrollStitchFrameStatus::NotMatched), 0);
+        assert_eq!(result.image.width(), expected_page.width());
+        assert_eq!(result.image.height(), 1200);
+        assert_eq!(result.image.to_rgb8().as_raw(), expected_page.as_raw());
+    }
+
+    #[test]
+    fn test_stitch_frames_with_sticky_band() {
+        let page = create_page(320, 1200);
+        let header = image::RgbImage::from_fn(320, 40, |x, _| image::Rgb([x as u8, 80, 160]));
+
+        let frames = create_scroll_frames(&page, 300, 100)
+            .into_iter()
+            .map(|frame| {
+                let mut frame = frame.into_rgb8();
+                image::imageops::replace(&mut frame, &header, 0, 0);
+                DynamicImage::ImageRgb8(frame)
+            });
+
+        let mut service = ScrollScreenshotService::new();
+        let result = service
+            .stitch_frames(&ScrollStitchOptions::default(), frames)
+            .unwrap();
+
+        // 导航栏只保留一份，后续内容不再被导航栏遮挡
+        let mut expected_page = page.clone();
+        image::imageops::replace(&mut expected_page, &header, 0, 0);
+
+        assert_eq!(service.sticky_band.header_size, 40);
+        assert_eq!(result.count(ScrollStitchFrameStatus::NotMatched), 0);
+        assert_eq!(result.image.height(), 1200);
+        assert_eq!(result.image.to_rgb8().as_raw(), expected_page.as_raw());
+    }
+
+    #[test]
+    fn test_stitch_frames_with_dynamic_content() {
+        let page = create_page(320, 1200);
+
+        // 每个滚动位置截取两次，选区中固定位置的动画每次都不同
+        let frames = create_scroll_frames(&page, 300, 100)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, frame)| {
+                (0..2).map(move |capture_index| {
+                    let mut frame = frame.to_rgb8();
+                    let seed = index as u32 * 2 + capture_index;
+                    for y in 120..180 {
+                        for x in 200..260 {
+                            let value =
+                                (((x + seed * 7) / 5 + (y + seed * 11) / 5) % 2 * 255) as u8;
+                            frame.put_pixel(x, y, image::Rgb([value, 255 - value, value]));
+                        }
+                    }
+
+                    DynamicImage::ImageRgb8(frame)
+                })
+            });
+
+        let mut service = ScrollScreenshotService::new();
+        let result = service
+            .stitch_frames(&ScrollStitchOptions::default(), frames)
+            .unwrap();
+
+        assert_eq!(result.count(ScrollStitchFrameStatus::NotMatched), 0);
+        assert_eq!(result.image.height(), 1200);
+        assert!(
+            service
+                .dynamic_mask
+                .contains((230.0 * service.image_scale) as i32, 150)
+        );
+        assert!(
+            !service
+                .dynamic_mask
+                .contains((60.0 * service.image_scale) as i32, 40)
+        );
+    }
+
+    /// 右侧 12 像素为滚动条的帧，滑块随滚动移动
+    fn create_scrollbar_frames(page: &image::RgbImage, frame_height: u32) -> Vec<DynamicImage> {
+        let content_rect = ElementRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: 308,
+            max_y: frame_height as i32,
+        };
+
+        (0..=(page.height() - frame_height) / 100)
+            .map(|index| {
+                let thumb_start = index * 25;
+                DynamicImage::ImageRgb8(create_window(
+                    page,
+                    index * 100,
+                    320,
+                    frame_height,
+                    content_rect,
+                    |x, y| {
+                        if (310..318).contains(&x) && (thumb_start..thumb_start + 75).contains(&y) {
+                            image::Rgb([120, 120, 120])
+                        } else {
+                            image::Rgb([235, 235, 235])
+                        }
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stitch_frames_with_scrollbar() {
+        let page = create_page(308, 1200);
+        let frames = create_scrollbar_frames(&page, 300);
+
+        let mut service = ScrollScreenshotService::new();
+        let result = service
+            .stitch_frames(&ScrollStitchOptions::default(), frames.clone())
+            .unwrap();
+
+        // 滑块两侧没有变化的轨道也属于贴边区域
+        assert_eq!(service.scrollbar_band.start_size, 0);
+        assert_eq!(service.scrollbar_band.end_size, 12);
+        assert_eq!(result.count(ScrollStitchFrameStatus::NotMatched), 0);
+        assert_eq!(result.image.to_rgb8().as_raw(), page.as_raw());
+
+        // 关闭裁剪时保留滚动条
+        let options = ScrollStitchOptions {
+            trim_scrollbar: false,
+            ..Default::default()
+        };
+        let mut service = ScrollScreenshotService::new();
+        let result = service.stitch_frames(&options, frames).unwrap();
+        assert_eq!(result.image.width(), 320);
+        assert_eq!(result.image.height(), 1200);
+    }
+
+    #[test]
+    fn test_undo_frame_with_scrollbar() {
+        let page = create_page(308, 1200);
+        let frames = create_scrollbar_frames(&page, 300);
+
+        let mut service = ScrollScreenshotService::new();
+        service
+            .stitch_frames(&ScrollStitchOptions::default(), frames[..1].to_vec())
+            .unwrap();
+        assert!(service.scrollbar_band.is_empty());
+
+        assert_eq!(
+            service.handle_frame(frames[1].clone(), ScrollImageList::Bottom),
+            ScrollStitchFrameStatus::Appended
+        );
+        let scrollbar_band = service.scrollbar_band;
+        assert!(!scrollbar_band.is_empty());
+
+        // 撤销后贴边区域和参考帧回到追加之前
+        assert_eq!(service.undo(), Some(ScrollImageList::Bottom));
+        assert!(service.scrollbar_band.is_empty());
+        assert_eq!(
+            service
+                .scrollbar_reference
+                .as_ref()
+                .map(|reference| reference.position),
+            Some(0)
+        );
+
+        // 重新追加时与首帧比较，得到相同的贴边区域
+        assert_eq!(
+            service.handle_frame(frames[1].clone(), ScrollImageList::Bottom),
+            ScrollStitchFrameStatus::Appended
+        );
+        assert_eq!(service.scrollbar_band, scrollbar_band);
+    }
+}
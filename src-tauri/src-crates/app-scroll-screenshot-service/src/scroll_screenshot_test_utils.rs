@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use image::{DynamicImage, ImageBuffer, Pixel, Rgb, RgbImage};
+use snow_shot_app_shared::ElementRect;
+
+/// 用于区分同一进程内不同测试的临时目录
+static TEMP_DIRECTORY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// xorshift 伪随机数，固定种子保证测试图片不变
+fn create_random(mut seed: u32) -> impl FnMut() -> u32 {
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    }
+}
+
+/// 生成带有随机色块的页面，保证每帧都有足够的特征点
+pub(crate) fn create_page(width: u32, height: u32) -> RgbImage {
+    let mut next_random = create_random(0x9e3779b9);
+
+    let mut page = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+    for _ in 0..(width * height / 400) {
+        let block_x = next_random() % width;
+        let block_y = next_random() % height;
+        let block_width = 4 + next_random() % 24;
+        let block_height = 4 + next_random() % 12;
+        let color = Rgb([
+            (next_random() % 200) as u8,
+            (next_random() % 200) as u8,
+            (next_random() % 200) as u8,
+        ]);
+
+        for y in block_y..(block_y + block_height).min(height) {
+            for x in block_x..(block_x + block_width).min(width) {
+                page.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    page
+}
+
+/// 生成大面积留白、只有稀疏文字行的页面，特征点较少
+pub(crate) fn create_text_page(width: u32, height: u32) -> RgbImage {
+    let mut next_random = create_random(0x2545f491);
+
+    let mut page = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+    let mut line_y = 6;
+    while line_y + 12 < height {
+        let line_width = width / 3 + next_random() % (width / 2);
+        for y in line_y..line_y + 12 {
+            for x in 16..16 + line_width {
+                if next_random().is_multiple_of(3) {
+                    page.put_pixel(x, y, Rgb([40, 40, 40]));
+                }
+            }
+        }
+
+        line_y += 28 + next_random() % 24;
+    }
+
+    page
+}
+
+/// 截取页面中从 (x, y) 开始的一帧
+pub(crate) fn crop_frame(page: &RgbImage, x: u32, y: u32, width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(image::imageops::crop_imm(page, x, y, width, height).to_image())
+}
+
+/// 从页面顶部开始每次向下滚动 step 像素截取一帧，直到页面底部
+pub(crate) fn create_scroll_frames(
+    page: &RgbImage,
+    frame_height: u32,
+    step: u32,
+) -> Vec<DynamicImage> {
+    (0..=(page.height() - frame_height) / step)
+        .map(|index| crop_frame(page, 0, index * step, page.width(), frame_height))
+        .collect()
+}
+
+/**
+ * 模拟滚动到 scroll_position 的窗口
+ * 页面显示在 content_rect 内，其余位置（侧边栏、滚动条、边框等）由 decorate 绘制
+ */
+pub(crate) fn create_window<P: Pixel>(
+    page: &ImageBuffer<P, Vec<P::Subpixel>>,
+    scroll_position: u32,
+    width: u32,
+    height: u32,
+    content_rect: ElementRect,
+    decorate: impl Fn(u32, u32) -> P,
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let (window_x, window_y) = (x as i32, y as i32);
+        if window_x >= content_rect.min_x
+            && window_x < content_rect.max_x
+            && window_y >= content_rect.min_y
+            && window_y < content_rect.max_y
+        {
+            *page.get_pixel(
+                (window_x - content_rect.min_x) as u32,
+                (window_y - content_rect.min_y) as u32 + scroll_position,
+            )
+        } else {
+            decorate(x, y)
+        }
+    })
+}
+
+/**
+ * 测试使用的临时目录，按进程和序号命名，并行运行的测试互不干扰
+ * 离开作用域时删除，断言失败时也会清理
+ */
+pub(crate) struct TempDirectory(PathBuf);
+
+impl TempDirectory {
+    pub(crate) fn new() -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "snow_shot_scroll_screenshot_test_{}_{}",
+            std::process::id(),
+            TEMP_DIRECTORY_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,243 @@
+use image::RgbImage;
+use image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+
+use crate::scroll_screenshot_service::ScrollDirection;
+
+/// 视频开始和结束时停留的时长（秒）
+const VIDEO_HOLD_DURATION: f32 = 1.0;
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ScrollVideoFormat {
+    Mp4 = 0,
+    WebM = 1,
+    Gif = 2,
+}
+
+impl ScrollVideoFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            ScrollVideoFormat::Mp4 => "mp4",
+            ScrollVideoFormat::WebM => "webm",
+            ScrollVideoFormat::Gif => "gif",
+        }
+    }
+
+    /**
+     * 编码参数，输入为 rgb24 原始帧
+     */
+    pub fn get_ffmpeg_output_args(&self) -> Vec<&'static str> {
+        match self {
+            ScrollVideoFormat::Mp4 => vec![
+                "-c:v",
+                "libx264",
+                "-crf",
+                "23",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "+faststart",
+            ],
+            ScrollVideoFormat::WebM => vec![
+                "-c:v",
+                "libvpx-vp9",
+                "-crf",
+                "32",
+                "-b:v",
+                "0",
+                "-pix_fmt",
+                "yuv420p",
+            ],
+            ScrollVideoFormat::Gif => vec![
+                "-vf",
+                "split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
+                "-loop",
+                "0",
+            ],
+        }
+    }
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ScrollVideoOptions {
+    /// 视频宽度
+    pub viewport_width: u32,
+    /// 视频高度
+    pub viewport_height: u32,
+    /// 滚动速度（视频像素/秒）
+    pub speed: f32,
+    /// 帧率
+    pub frame_rate: u32,
+}
+
+/**
+ * 将长截图渲染为逐帧滚动的画面
+ * 图片在滚动垂直方向上缩放到视频尺寸，每帧只缩放可见的部分
+ */
+pub struct ScrollVideoRenderer<'a> {
+    image: &'a RgbImage,
+    direction: ScrollDirection,
+    /// 视频宽度
+    pub width: u32,
+    /// 视频高度
+    pub height: u32,
+    /// 视频像素到原图像素的比例
+    source_scale: f32,
+    /// 视频可见范围在滚动方向上的最大起点（视频像素）
+    scroll_distance: f32,
+    hold_frame_count: usize,
+    scroll_frame_count: usize,
+    frame_index: usize,
+}
+
+impl<'a> ScrollVideoRenderer<'a> {
+    pub fn new(
+        image: &'a RgbImage,
+        direction: ScrollDirection,
+        options: &ScrollVideoOptions,
+    ) -> Result<Self, String> {
+        if image.width() == 0 || image.height() == 0 {
+            return Err(String::from("[ScrollVideoRenderer::new] Image is empty"));
+        }
+
+        let (image_cross_size, image_scroll_size) = if direction == ScrollDirection::Vertical {
+            (image.width(), image.height())
+        } else {
+            (image.height(), image.width())
+        };
+        let (viewport_cross_size, viewport_scroll_size) = if direction == ScrollDirection::Vertical
+        {
+            (options.viewport_width, options.viewport_height)
+        } else {
+            (options.viewport_height, options.viewport_width)
+        };
+
+        // 编码器要求尺寸为偶数
+        let cross_size = viewport_cross_size.max(2) & !1;
+        let source_scale = image_cross_size as f32 / cross_size as f32;
+        let scaled_scroll_size = (image_scroll_size as f32 / source_scale).floor() as u32;
+        let scroll_size = viewport_scroll_size.min(scaled_scroll_size).max(2) & !1;
+
+        let (width, height) = if direction == ScrollDirection::Vertical {
+            (cross_size, scroll_size)
+        } else {
+            (scroll_size, cross_size)
+        };
+
+        let frame_rate = options.frame_rate.max(1) as f32;
+        let scroll_distance = scaled_scroll_size.saturating_sub(scroll_size) as f32;
+        let scroll_frame_count =
+            (scroll_distance / options.speed.max(1.0) * frame_rate).ceil() as usize + 1;
+
+        Ok(Self {
+            image,
+            direction,
+            width,
+            height,
+            source_scale,
+            scroll_distance,
+            hold_frame_count: (VIDEO_HOLD_DURATION * frame_rate).round() as usize,
+            scroll_frame_count,
+            frame_index: 0,
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.hold_frame_count * 2 + self.scroll_frame_count
+    }
+
+    /**
+     * 渲染可见范围起点为 position（视频像素）的一帧
+     */
+    fn render_frame(&self, position: f32) -> RgbImage {
+        let is_vertical = self.direction == ScrollDirection::Vertical;
+        let (viewport_cross_size, viewport_scroll_size) = if is_vertical {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        let (image_cross_size, image_scroll_size) = if is_vertical {
+            (self.image.width(), self.image.height())
+        } else {
+            (self.image.height(), self.image.width())
+        };
+
+        let source_start = ((position * self.source_scale).round() as u32)
+            .min(image_scroll_size.saturating_sub(1));
+        let source_size = ((viewport_scroll_size as f32 * self.source_scale).round() as u32)
+            .clamp(1, image_scroll_size - source_start);
+
+        let source = if is_vertical {
+            imageops::crop_imm(self.image, 0, source_start, image_cross_size, source_size)
+        } else {
+            imageops::crop_imm(self.image, source_start, 0, source_size, image_cross_size)
+        };
+
+        let (frame_width, frame_height) = if is_vertical {
+            (viewport_cross_size, viewport_scroll_size)
+        } else {
+            (viewport_scroll_size, viewport_cross_size)
+        };
+        imageops::resize(&*source, frame_width, frame_height, FilterType::Triangle)
+    }
+}
+
+impl Iterator for ScrollVideoRenderer<'_> {
+    /// rgb24 像素
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frame_index >= self.frame_count() {
+            return None;
+        }
+
+        let scroll_frame_index = self
+            .frame_index
+            .saturating_sub(self.hold_frame_count)
+            .min(self.scroll_frame_count - 1);
+        let progress = if self.scroll_frame_count > 1 {
+            scroll_frame_index as f32 / (self.scroll_frame_count - 1) as f32
+        } else {
+            0.0
+        };
+        self.frame_index += 1;
+
+        Some(
+            self.render_frame(self.scroll_distance * progress)
+                .into_raw(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn test_render_scroll_video_frames() {
+        let image = RgbImage::from_fn(400, 2000, |_, y| Rgb([(y / 8) as u8, 0, 0]));
+        let options = ScrollVideoOptions {
+            viewport_width: 201,
+            viewport_height: 300,
+            speed: 250.0,
+            frame_rate: 10,
+        };
+
+        let renderer =
+            ScrollVideoRenderer::new(&image, ScrollDirection::Vertical, &options).unwrap();
+        // 宽度缩放到偶数尺寸，图片缩放后高 1000，滚动 700 像素
+        assert_eq!((renderer.width, renderer.height), (200, 300));
+        assert_eq!(renderer.frame_count(), 10 * 2 + 28 + 1);
+
+        let frame_size = (renderer.width * renderer.height * 3) as usize;
+        let frames = renderer.collect::<Vec<_>>();
+        assert!(frames.iter().all(|frame| frame.len() == frame_size));
+
+        // 开始停留在顶部，结束停留在底部
+        assert_eq!(frames[0], frames[9]);
+        assert_eq!(frames[0][0], 0);
+        assert_eq!(frames.last().unwrap(), &frames[frames.len() - 10]);
+        assert!(frames.last().unwrap()[0] > 150);
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+struct ScrollWorkerSignal {
+    /// 每次启动时递增，旧的工作线程发现不一致时退出
+    generation: u64,
+    running: bool,
+    /// 是否有尚未处理的图片
+    pending: bool,
+}
+
+struct ScrollWorkerState {
+    signal: Mutex<ScrollWorkerSignal>,
+    condvar: Condvar,
+}
+
+impl ScrollWorkerState {
+    fn lock(&self) -> MutexGuard<'_, ScrollWorkerSignal> {
+        self.signal
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/**
+ * 后台拼接线程的启动、唤醒和停止
+ * 截图加入队列后通过 notify 唤醒工作线程，工作线程处理完队列中的图片后继续等待
+ */
+pub struct ScrollScreenshotWorkerService {
+    state: Arc<ScrollWorkerState>,
+}
+
+/**
+ * 工作线程持有的句柄，重新启动或停止后失效
+ */
+pub struct ScrollScreenshotWorkerHandle {
+    state: Arc<ScrollWorkerState>,
+    generation: u64,
+}
+
+impl ScrollScreenshotWorkerService {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(ScrollWorkerState {
+                signal: Mutex::new(ScrollWorkerSignal {
+                    generation: 0,
+                    running: false,
+                    pending: false,
+                }),
+                condvar: Condvar::new(),
+            }),
+        }
+    }
+
+    /**
+     * 启动新的工作线程句柄，已有的工作线程会在下次检查时退出
+     * 启动后会立即处理队列中已有的图片
+     */
+    pub fn start(&mut self) -> ScrollScreenshotWorkerHandle {
+        let generation = {
+            let mut signal = self.state.lock();
+            signal.generation += 1;
+            signal.running = true;
+            signal.pending = true;
+            signal.generation
+        };
+        self.state.condvar.notify_all();
+
+        ScrollScreenshotWorkerHandle {
+            state: self.state.clone(),
+            generation,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.lock().running
+    }
+
+    /**
+     * 通知工作线程有新的图片
+     */
+    pub fn notify(&self) {
+        self.state.lock().pending = true;
+        self.state.condvar.notify_all();
+    }
+
+    pub fn stop(&mut self) {
+        {
+            let mut signal = self.state.lock();
+            signal.running = false;
+            signal.pending = false;
+        }
+        self.state.condvar.notify_all();
+    }
+}
+
+impl ScrollScreenshotWorkerHandle {
+    /**
+     * 句柄是否仍然有效，处理图片的间隙检查，失效后应尽快退出
+     */
+    pub fn is_current(&self) -> bool {
+        let signal = self.state.lock();
+        signal.running && signal.generation == self.generation
+    }
+
+    /**
+     * 阻塞等待新的图片，返回 false 时工作线程应退出
+     */
+    pub fn wait(&self) -> bool {
+        let mut signal = self.state.lock();
+        loop {
+            if !signal.running || signal.generation != self.generation {
+                return false;
+            }
+
+            if signal.pending {
+                signal.pending = false;
+                return true;
+            }
+
+            signal = self
+                .state
+                .condvar
+                .wait(signal)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_wait_and_stop() {
+        let mut service = ScrollScreenshotWorkerService::new();
+        assert!(!service.is_running());
+
+        let handle = service.start();
+        // 启动后处理队列中已有的图片
+        assert!(handle.wait());
+
+        service.notify();
+        assert!(handle.wait());
+
+        // 重新启动后旧的句柄失效
+        let new_handle = service.start();
+        assert!(!handle.is_current());
+        assert!(!handle.wait());
+        assert!(new_handle.wait());
+
+        // 停止时唤醒等待中的线程
+        let wait_thread = std::thread::spawn(move || new_handle.wait());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        service.stop();
+        assert!(!wait_thread.join().unwrap());
+        assert!(!service.is_running());
+    }
+}
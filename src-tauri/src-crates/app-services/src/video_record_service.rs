@@ -3,7 +3,10 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 #[cfg(target_os = "macos")]
 use snow_shot_app_utils::monitor_info::MonitorList;
-use std::{io::Result, path::PathBuf};
+use std::{
+    io::Result,
+    path::{Path, PathBuf},
+};
 use tauri::{Manager, path::BaseDirectory};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Copy)]
@@ -137,6 +140,92 @@ impl VideoRecordService {
         )
     }
 
+    /**
+     * 获取 ffmpeg 的路径，需要先调用 init
+     */
+    pub fn get_ffmpeg_path(&self) -> Option<PathBuf> {
+        self.ffmpeg_path.clone()
+    }
+
+    /**
+     * 将 rgb24 原始帧通过管道写入 ffmpeg 编码为视频文件
+     * output_args 为输出的编码参数
+     * 编码耗时较长且不依赖录制状态，调用方可以在释放服务的锁后执行
+     */
+    pub fn encode_raw_frames(
+        ffmpeg_path: &Path,
+        width: u32,
+        height: u32,
+        frame_rate: u32,
+        output_args: &[&str],
+        output_file: &str,
+        frames: impl Iterator<Item = Vec<u8>>,
+    ) -> Result<()> {
+        if let Some(parent_dir) = std::path::Path::new(output_file).parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+
+        let mut command = FfmpegCommand::new_with_path(ffmpeg_path);
+        command
+            .arg("-f")
+            .arg("rawvideo")
+            .arg("-pix_fmt")
+            .arg("rgb24")
+            .arg("-s")
+            .arg(format!("{}x{}", width, height))
+            .arg("-r")
+            .arg(frame_rate.to_string())
+            .arg("-i")
+            .arg("-")
+            .args(output_args)
+            .arg("-y")
+            .arg(output_file);
+
+        let mut child = command.spawn()?;
+        let mut stdin = child.take_stdin().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "[VideoRecordService::encode_raw_frames] Failed to open ffmpeg stdin",
+            )
+        })?;
+
+        // 持续读取日志，避免 ffmpeg 因输出缓冲区写满而阻塞
+        let stderr_thread = child.take_stderr().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut log = String::new();
+                let _ = std::io::Read::read_to_string(&mut stderr, &mut log);
+                log
+            })
+        });
+
+        let mut write_result = Ok(());
+        for frame in frames {
+            write_result = std::io::Write::write_all(&mut stdin, &frame);
+            if write_result.is_err() {
+                break;
+            }
+        }
+        drop(stdin);
+
+        let status = child.wait()?;
+        let log = stderr_thread
+            .and_then(|stderr_thread| stderr_thread.join().ok())
+            .unwrap_or_default();
+
+        if !status.success() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!(
+                    "[VideoRecordService::encode_raw_frames] ffmpeg exited with {}: {}",
+                    status,
+                    log.lines().last().unwrap_or_default()
+                ),
+            ));
+        }
+
+        write_result
+    }
+
     pub fn start(
         &mut self,
         min_x: i32,
@@ -19,6 +19,8 @@ use zune_jpegxl::JxlSimpleEncoder;
 use crate::monitor_info::MonitorList;
 
 pub mod monitor_info;
+#[cfg(target_os = "linux")]
+pub mod portal_screenshot;
 
 pub fn get_device_state() -> Result<DeviceState, String> {
     #[cfg(target_os = "macos")]
@@ -166,7 +168,7 @@ pub fn get_capture_monitor_list(
         return Ok(MonitorList::get_by_region(region));
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
     {
         Ok(MonitorList::all())
     }
@@ -227,25 +229,52 @@ pub fn check_monitor_scale_factors_consistent() -> (bool, Vec<f32>) {
 }
 
 pub fn capture_target_monitor(
+    monitor: &Monitor,
+    crop_area: Option<ElementRect>,
+    exclude_window: Option<&tauri::Window>,
+) -> Option<image::DynamicImage> {
+    capture_target_monitor_core(monitor, crop_area, exclude_window, false)
+}
+
+/// 截取显示器
+///
+/// @param preserve_alpha 为 true 时返回 RGBA 图片，保留截图接口提供的透明度
+pub fn capture_target_monitor_core(
     monitor: &Monitor,
     crop_area: Option<ElementRect>,
     #[allow(unused_variables)] exclude_window: Option<&tauri::Window>,
+    preserve_alpha: bool,
 ) -> Option<image::DynamicImage> {
     #[cfg(not(target_os = "macos"))]
     {
-        let image = if let Some(crop_area) = crop_area {
-            monitor.capture_region_rgb(
-                crop_area.min_x as u32,
-                crop_area.min_y as u32,
-                (crop_area.max_x - crop_area.min_x) as u32,
-                (crop_area.max_y - crop_area.min_y) as u32,
-            )
+        let image = if preserve_alpha {
+            if let Some(crop_area) = crop_area {
+                monitor.capture_region(
+                    crop_area.min_x as u32,
+                    crop_area.min_y as u32,
+                    (crop_area.max_x - crop_area.min_x) as u32,
+                    (crop_area.max_y - crop_area.min_y) as u32,
+                )
+            } else {
+                monitor.capture_image()
+            }
+            .map(image::DynamicImage::ImageRgba8)
         } else {
-            monitor.capture_image_rgb()
+            if let Some(crop_area) = crop_area {
+                monitor.capture_region_rgb(
+                    crop_area.min_x as u32,
+                    crop_area.min_y as u32,
+                    (crop_area.max_x - crop_area.min_x) as u32,
+                    (crop_area.max_y - crop_area.min_y) as u32,
+                )
+            } else {
+                monitor.capture_image_rgb()
+            }
+            .map(image::DynamicImage::ImageRgb8)
         };
 
         return match image {
-            Ok(image) => Some(image::DynamicImage::ImageRgb8(image)),
+            Ok(image) => Some(image),
             Err(error) => {
                 log::error!(
                     "[capture_target_monitor] failed to capture image: {:?}",
@@ -274,7 +303,11 @@ pub fn capture_target_monitor(
             .eq("DeskPad Display")
         {
             log::warn!("[capture_current_monitor_with_scap] skip DeskPad Display");
-            return Some(image::DynamicImage::ImageRgb8(image::RgbImage::new(1, 1)));
+            return Some(if preserve_alpha {
+                image::DynamicImage::ImageRgba8(image::RgbaImage::new(1, 1))
+            } else {
+                image::DynamicImage::ImageRgb8(image::RgbImage::new(1, 1))
+            });
         }
 
         let monitor_id = match monitor.id() {
@@ -378,12 +411,24 @@ pub fn capture_target_monitor(
         };
         capturer.stop_capture();
 
-        match image::RgbImage::from_raw(
-            frame.width as u32,
-            frame.height as u32,
-            bgra_to_rgb(&frame.data),
-        ) {
-            Some(rgb_image) => Some(image::DynamicImage::ImageRgb8(rgb_image)),
+        let image = if preserve_alpha {
+            image::RgbaImage::from_raw(
+                frame.width as u32,
+                frame.height as u32,
+                bgra_to_rgba(&frame.data),
+            )
+            .map(image::DynamicImage::ImageRgba8)
+        } else {
+            image::RgbImage::from_raw(
+                frame.width as u32,
+                frame.height as u32,
+                bgra_to_rgb(&frame.data),
+            )
+            .map(image::DynamicImage::ImageRgb8)
+        };
+
+        match image {
+            Some(image) => Some(image),
             None => {
                 log::error!("[capture_current_monitor_with_scap] failed to create image");
                 return None;
@@ -416,6 +461,16 @@ pub fn bgra_to_rgb(bgra_data: &[u8]) -> Vec<u8> {
     rgb_data
 }
 
+#[cfg(target_os = "macos")]
+pub fn bgra_to_rgba(bgra_data: &[u8]) -> Vec<u8> {
+    let mut rgba_data = bgra_data.to_vec();
+    rgba_data
+        .chunks_exact_mut(4)
+        .for_each(|pixel| pixel.swap(0, 2));
+
+    rgba_data
+}
+
 pub enum ImageEncoder {
     Webp,
     Png,
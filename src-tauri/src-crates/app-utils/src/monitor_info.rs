@@ -37,6 +37,19 @@ impl MonitorInfo {
             }
         }
 
+        #[cfg(target_os = "linux")]
+        {
+            // X11 下显示器位置和尺寸均为物理像素
+            let x = monitor.x().unwrap_or(0);
+            let y = monitor.y().unwrap_or(0);
+            monitor_rect = ElementRect {
+                min_x: x,
+                min_y: y,
+                max_x: x + monitor.width().unwrap_or(0) as i32,
+                max_y: y + monitor.height().unwrap_or(0) as i32,
+            }
+        }
+
         MonitorInfo {
             monitor: monitor.clone(),
             rect: monitor_rect,
@@ -133,19 +146,87 @@ impl MonitorList {
     }
 
     /// 捕获所有显示器，拼接为一个完整的图像
+    /// Linux 下直接截取显示器失败时（如 Wayland），使用 xdg-desktop-portal 截图
     ///
     /// @param crop_region 显示器的裁剪区域
+    /// @param preserve_alpha 为 true 时返回 RGBA 图像
     fn capture_core(
         &self,
         crop_region: Option<ElementRect>,
         exclude_window: Option<&tauri::Window>,
+        preserve_alpha: bool,
+    ) -> Result<image::DynamicImage, String> {
+        let capture_result = self.capture_monitors(crop_region, exclude_window, preserve_alpha);
+
+        #[cfg(target_os = "linux")]
+        {
+            if let Err(error) = capture_result {
+                return self.capture_with_portal(crop_region, preserve_alpha, error);
+            }
+        }
+
+        capture_result
+    }
+
+    /// 通过 xdg-desktop-portal 截取整个桌面，再裁剪出需要的区域
+    ///
+    /// @param error 直接截取显示器时的错误，portal 也失败时一并返回
+    #[cfg(target_os = "linux")]
+    fn capture_with_portal(
+        &self,
+        crop_region: Option<ElementRect>,
+        preserve_alpha: bool,
+        error: String,
+    ) -> Result<image::DynamicImage, String> {
+        log::warn!(
+            "[MonitorInfoList::capture_with_portal] Failed to capture monitors, fallback to portal: {}",
+            error
+        );
+
+        let desktop_image = match crate::portal_screenshot::capture_portal_screenshot() {
+            Ok(desktop_image) => desktop_image,
+            Err(portal_error) => {
+                return Err(format!("{} {}", error, portal_error));
+            }
+        };
+
+        // portal 返回的截图包含所有显示器，需要按所有显示器的最小矩形换算坐标
+        let desktop_rect = MonitorList::all().get_monitors_bounding_box();
+        let crop_region = crop_region.unwrap_or(self.get_monitors_bounding_box());
+        let capture_image = match crate::portal_screenshot::crop_desktop_image(
+            &desktop_image,
+            desktop_rect,
+            crop_region,
+        ) {
+            Some(capture_image) => capture_image,
+            None => {
+                return Err(format!(
+                    "[MonitorInfoList::capture_with_portal] Crop region is out of desktop, crop_region: {:?}, desktop_rect: {:?}",
+                    crop_region, desktop_rect
+                ));
+            }
+        };
+
+        Ok(if preserve_alpha {
+            image::DynamicImage::ImageRgba8(capture_image.to_rgba8())
+        } else {
+            image::DynamicImage::ImageRgb8(capture_image.to_rgb8())
+        })
+    }
+
+    /// 分别截取每个显示器，拼接为一个完整的图像
+    fn capture_monitors(
+        &self,
+        crop_region: Option<ElementRect>,
+        exclude_window: Option<&tauri::Window>,
+        preserve_alpha: bool,
     ) -> Result<image::DynamicImage, String> {
         let monitors = &self.0;
 
         // 特殊情况，只有一个显示器，直接返回
         if monitors.len() == 1 {
             let first_monitor = monitors.first().unwrap();
-            let capture_image = super::capture_target_monitor(
+            let capture_image = super::capture_target_monitor_core(
                 &first_monitor.monitor,
                 if let Some(crop_region) = crop_region {
                     Some(first_monitor.get_monitor_crop_region(crop_region))
@@ -153,16 +234,20 @@ impl MonitorList {
                     None
                 },
                 exclude_window,
+                preserve_alpha,
             );
 
             // 有些捕获失败的显示器，返回一个空图像，这里需要特殊处理
             if capture_image.is_some() {
                 let capture_image = capture_image.as_ref().unwrap();
                 if capture_image.width() == 1 && capture_image.height() == 1 {
-                    return Ok(image::DynamicImage::new_rgb8(
-                        (first_monitor.rect.max_x - first_monitor.rect.min_x) as u32,
-                        (first_monitor.rect.max_y - first_monitor.rect.min_y) as u32,
-                    ));
+                    let width = (first_monitor.rect.max_x - first_monitor.rect.min_x) as u32;
+                    let height = (first_monitor.rect.max_y - first_monitor.rect.min_y) as u32;
+                    return Ok(if preserve_alpha {
+                        image::DynamicImage::new_rgba8(width, height)
+                    } else {
+                        image::DynamicImage::new_rgb8(width, height)
+                    });
                 }
             }
 
@@ -193,7 +278,7 @@ impl MonitorList {
                     None
                 };
 
-                let capture_image = super::capture_target_monitor(&monitor.monitor, monitor_crop_region, exclude_window);
+                let capture_image = super::capture_target_monitor_core(&monitor.monitor, monitor_crop_region, exclude_window, preserve_alpha);
 
                 match capture_image {
                     Some(image) => Some((image, monitor_crop_region)),
@@ -236,11 +321,11 @@ impl MonitorList {
             )
         };
 
-        const RGB_CHANNEL_COUNT: usize = 3;
+        let channel_count: usize = if preserve_alpha { 4 } else { 3 };
         let mut capture_image_pixels: Vec<u8> = unsafe {
             let mut vec =
-                Vec::with_capacity(capture_image_width * capture_image_height * RGB_CHANNEL_COUNT);
-            vec.set_len(capture_image_width * capture_image_height * RGB_CHANNEL_COUNT);
+                Vec::with_capacity(capture_image_width * capture_image_height * channel_count);
+            vec.set_len(capture_image_width * capture_image_height * channel_count);
             vec
         };
 
@@ -273,18 +358,29 @@ impl MonitorList {
                 monitor_image,
                 offset_x as usize,
                 offset_y as usize,
-                RGB_CHANNEL_COUNT,
+                channel_count,
             );
         }
 
-        let capture_image = image::DynamicImage::ImageRgb8(
-            image::RgbImage::from_raw(
-                capture_image_width as u32,
-                capture_image_height as u32,
-                capture_image_pixels,
+        let capture_image = if preserve_alpha {
+            image::DynamicImage::ImageRgba8(
+                image::RgbaImage::from_raw(
+                    capture_image_width as u32,
+                    capture_image_height as u32,
+                    capture_image_pixels,
+                )
+                .unwrap(),
             )
-            .unwrap(),
-        );
+        } else {
+            image::DynamicImage::ImageRgb8(
+                image::RgbImage::from_raw(
+                    capture_image_width as u32,
+                    capture_image_height as u32,
+                    capture_image_pixels,
+                )
+                .unwrap(),
+            )
+        };
 
         Ok(capture_image)
     }
@@ -293,7 +389,7 @@ impl MonitorList {
         &self,
         exclude_window: Option<&tauri::Window>,
     ) -> Result<image::DynamicImage, String> {
-        self.capture_core(None, exclude_window)
+        self.capture_core(None, exclude_window, false)
     }
 
     pub fn capture_region(
@@ -301,7 +397,16 @@ impl MonitorList {
         region: ElementRect,
         exclude_window: Option<&tauri::Window>,
     ) -> Result<image::DynamicImage, String> {
-        self.capture_core(Some(region), exclude_window)
+        self.capture_core(Some(region), exclude_window, false)
+    }
+
+    /// 截取指定区域，保留截图接口提供的透明度，返回 RGBA 图像
+    pub fn capture_region_with_alpha(
+        &self,
+        region: ElementRect,
+        exclude_window: Option<&tauri::Window>,
+    ) -> Result<image::DynamicImage, String> {
+        self.capture_core(Some(region), exclude_window, true)
     }
 
     pub fn monitor_rect_list(&self) -> Vec<ElementRect> {
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use image::DynamicImage;
+use snow_shot_app_shared::ElementRect;
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{Connection, Proxy};
+use zbus::names::BusName;
+use zbus::zvariant::{OwnedValue, Value};
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_SCREENSHOT_INTERFACE: &str = "org.freedesktop.portal.Screenshot";
+const PORTAL_REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+/// 非交互式截图不弹出选择窗口，portal 正常时很快响应，超时后尽快返回错误
+const PORTAL_RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * 根据连接的唯一名称和 handle_token 计算 Request 对象路径
+ * 需要在调用前订阅该路径的 Response 信号，否则可能错过响应
+ */
+fn get_request_path(unique_name: &str, handle_token: &str) -> String {
+    format!(
+        "{}/request/{}/{}",
+        PORTAL_OBJECT_PATH,
+        unique_name.trim_start_matches(':').replace('.', "_"),
+        handle_token
+    )
+}
+
+/**
+ * portal 服务正在运行或可以通过 D-Bus 激活
+ * 没有 portal 服务时调用 Screenshot 会一直等到超时，提前检查以便立即返回错误
+ */
+fn is_portal_available(connection: &Connection) -> Result<bool, String> {
+    let dbus_proxy = DBusProxy::new(connection)
+        .map_err(|e| format!("[is_portal_available] Failed to create D-Bus proxy: {}", e))?;
+
+    let has_owner = dbus_proxy
+        .name_has_owner(BusName::from_static_str(PORTAL_BUS_NAME).unwrap())
+        .map_err(|e| format!("[is_portal_available] Failed to call NameHasOwner: {}", e))?;
+    if has_owner {
+        return Ok(true);
+    }
+
+    let activatable_name_list = dbus_proxy.list_activatable_names().map_err(|e| {
+        format!(
+            "[is_portal_available] Failed to call ListActivatableNames: {}",
+            e
+        )
+    })?;
+
+    Ok(activatable_name_list
+        .iter()
+        .any(|name| name.as_str() == PORTAL_BUS_NAME))
+}
+
+/**
+ * 通过 org.freedesktop.portal.Screenshot 截取整个桌面
+ * 用于 Wayland 等无法直接截取显示器的环境，返回的图片包含所有显示器
+ */
+pub fn capture_portal_screenshot() -> Result<DynamicImage, String> {
+    let connection = Connection::session().map_err(|e| {
+        format!(
+            "[capture_portal_screenshot] Failed to connect session bus: {}",
+            e
+        )
+    })?;
+    if !is_portal_available(&connection)? {
+        return Err(format!(
+            "[capture_portal_screenshot] {} is not running",
+            PORTAL_BUS_NAME
+        ));
+    }
+
+    let unique_name = match connection.unique_name() {
+        Some(unique_name) => unique_name.to_string(),
+        None => {
+            return Err(String::from(
+                "[capture_portal_screenshot] Session bus connection has no unique name",
+            ));
+        }
+    };
+
+    let handle_token = format!("snow_shot_{}", std::process::id());
+    let request_path = get_request_path(&unique_name, &handle_token);
+
+    let request_proxy = Proxy::new(
+        &connection,
+        PORTAL_BUS_NAME,
+        request_path.as_str(),
+        PORTAL_REQUEST_INTERFACE,
+    )
+    .map_err(|e| {
+        format!(
+            "[capture_portal_screenshot] Failed to create request proxy: {}",
+            e
+        )
+    })?;
+    let mut response_iterator = request_proxy.receive_signal("Response").map_err(|e| {
+        format!(
+            "[capture_portal_screenshot] Failed to subscribe response: {}",
+            e
+        )
+    })?;
+
+    let screenshot_proxy = Proxy::new(
+        &connection,
+        PORTAL_BUS_NAME,
+        PORTAL_OBJECT_PATH,
+        PORTAL_SCREENSHOT_INTERFACE,
+    )
+    .map_err(|e| {
+        format!(
+            "[capture_portal_screenshot] Failed to create screenshot proxy: {}",
+            e
+        )
+    })?;
+
+    let mut options: HashMap<&str, Value> = HashMap::new();
+    options.insert("handle_token", Value::from(handle_token.as_str()));
+    options.insert("modal", Value::from(false));
+    options.insert("interactive", Value::from(false));
+    screenshot_proxy
+        .call_method("Screenshot", &("", options))
+        .map_err(|e| {
+            format!(
+                "[capture_portal_screenshot] Failed to call Screenshot: {}",
+                e
+            )
+        })?;
+
+    // 阻塞的信号迭代器不支持超时，在单独的线程中等待
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(response_iterator.next());
+    });
+
+    let message = match receiver.recv_timeout(PORTAL_RESPONSE_TIMEOUT) {
+        Ok(Some(message)) => message,
+        _ => {
+            // 关闭连接后信号迭代器随之结束，等待的线程也会退出
+            let _ = connection.close();
+            return Err(String::from(
+                "[capture_portal_screenshot] No response from portal",
+            ));
+        }
+    };
+
+    let (response, results): (u32, HashMap<String, OwnedValue>) =
+        message.body().deserialize().map_err(|e| {
+            format!(
+                "[capture_portal_screenshot] Failed to parse response: {}",
+                e
+            )
+        })?;
+    // 0 为成功，1 为用户取消，2 为其他错误
+    if response != 0 {
+        return Err(format!(
+            "[capture_portal_screenshot] Screenshot request failed: {}",
+            response
+        ));
+    }
+
+    let uri = match results.get("uri").map(|uri| String::try_from(uri.clone())) {
+        Some(Ok(uri)) => uri,
+        _ => {
+            return Err(String::from(
+                "[capture_portal_screenshot] Response has no uri",
+            ));
+        }
+    };
+
+    take_uri_image(&uri)
+}
+
+/**
+ * 读取 file:// URI 指向的图片，读取成功后删除文件
+ * portal 会将截图保存到用户目录（如 GNOME 保存到 ~/Pictures/Screenshots），不删除会一直累积
+ */
+pub fn take_uri_image(uri: &str) -> Result<DynamicImage, String> {
+    let file_path = url::Url::parse(uri)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .ok_or_else(|| format!("[take_uri_image] Invalid file uri: {}", uri))?;
+
+    let image = image::open(&file_path).map_err(|e| {
+        format!(
+            "[take_uri_image] Failed to open image: {} {}",
+            e,
+            file_path.display()
+        )
+    })?;
+
+    if let Err(e) = std::fs::remove_file(&file_path) {
+        log::warn!(
+            "[take_uri_image] Failed to remove file: {} {}",
+            e,
+            file_path.display()
+        );
+    }
+
+    Ok(image)
+}
+
+/**
+ * 从整个桌面的截图中裁剪出指定区域
+ * desktop_rect 为所有显示器的最小矩形，截图尺寸与其不一致时（如按逻辑像素截图）按比例换算
+ */
+pub fn crop_desktop_image(
+    image: &DynamicImage,
+    desktop_rect: ElementRect,
+    crop_region: ElementRect,
+) -> Option<DynamicImage> {
+    let desktop_width = desktop_rect.max_x - desktop_rect.min_x;
+    let desktop_height = desktop_rect.max_y - desktop_rect.min_y;
+    let (scale_x, scale_y) = if desktop_width > 0 && desktop_height > 0 {
+        (
+            image.width() as f64 / desktop_width as f64,
+            image.height() as f64 / desktop_height as f64,
+        )
+    } else {
+        (1.0, 1.0)
+    };
+
+    let to_image_x = |x: i32| -> u32 {
+        (((x - desktop_rect.min_x) as f64 * scale_x).round() as i64).clamp(0, image.width() as i64)
+            as u32
+    };
+    let to_image_y = |y: i32| -> u32 {
+        (((y - desktop_rect.min_y) as f64 * scale_y).round() as i64).clamp(0, image.height() as i64)
+            as u32
+    };
+
+    let (min_x, max_x) = (to_image_x(crop_region.min_x), to_image_x(crop_region.max_x));
+    let (min_y, max_y) = (to_image_y(crop_region.min_y), to_image_y(crop_region.max_y));
+    if min_x >= max_x || min_y >= max_y {
+        return None;
+    }
+
+    Some(image.crop_imm(min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use zbus::message::Header;
+    use zbus::zvariant::OwnedObjectPath;
+
+    /// 按进程和序号区分的临时文件，测试失败时也会在离开作用域时删除
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(extension: &str) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            Self(std::env::temp_dir().join(format!(
+                "snow_shot_test_{}_{}.{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                extension
+            )))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    struct MockPortal {
+        uri: String,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.portal.Screenshot")]
+    impl MockPortal {
+        async fn screenshot(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] connection: &zbus::Connection,
+            _parent_window: &str,
+            options: HashMap<&str, Value<'_>>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let sender = header.sender().unwrap().to_string();
+            let handle_token = match options.get("handle_token") {
+                Some(Value::Str(handle_token)) => handle_token.to_string(),
+                _ => String::from("default"),
+            };
+            let request_path = get_request_path(&sender, &handle_token);
+
+            let mut results: HashMap<&str, Value> = HashMap::new();
+            results.insert("uri", Value::from(self.uri.as_str()));
+            connection
+                .emit_signal(
+                    Some(sender.as_str()),
+                    request_path.as_str(),
+                    PORTAL_REQUEST_INTERFACE,
+                    "Response",
+                    &(0u32, results),
+                )
+                .await?;
+
+            Ok(OwnedObjectPath::try_from(request_path).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_crop_desktop_image() {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_fn(400, 200, |x, y| {
+            image::Rgb([(x / 2) as u8, y as u8, 0])
+        }));
+        let desktop_rect = ElementRect {
+            min_x: -200,
+            min_y: 0,
+            max_x: 200,
+            max_y: 200,
+        };
+
+        let cropped = crop_desktop_image(
+            &image,
+            desktop_rect,
+            ElementRect {
+                min_x: 0,
+                min_y: 50,
+                max_x: 300,
+                max_y: 100,
+            },
+        )
+        .unwrap();
+        assert_eq!(cropped.width(), 200);
+        assert_eq!(cropped.height(), 50);
+        assert_eq!(cropped.to_rgb8().get_pixel(0, 0), &image::Rgb([100, 50, 0]));
+
+        // 截图按逻辑像素保存时，按比例换算
+        let half_image = image.resize_exact(200, 100, image::imageops::FilterType::Nearest);
+        let cropped = crop_desktop_image(
+            &half_image,
+            desktop_rect,
+            ElementRect {
+                min_x: 0,
+                min_y: 50,
+                max_x: 200,
+                max_y: 100,
+            },
+        )
+        .unwrap();
+        assert_eq!(cropped.width(), 100);
+        assert_eq!(cropped.height(), 25);
+
+        assert!(
+            crop_desktop_image(
+                &image,
+                desktop_rect,
+                ElementRect {
+                    min_x: 300,
+                    min_y: 0,
+                    max_x: 400,
+                    max_y: 100,
+                },
+            )
+            .is_none()
+        );
+    }
+
+    /// 由测试代替 portal 服务，总线上已有 portal 服务时失败
+    #[test]
+    #[ignore = "需要独立的会话总线，使用 dbus-run-session cargo test -- --ignored 运行"]
+    fn test_capture_portal_screenshot() {
+        let file = TempFile::new("png");
+        image::RgbImage::from_pixel(64, 32, image::Rgb([10, 20, 30]))
+            .save(&file.0)
+            .unwrap();
+
+        // 总线上没有 portal 服务时立即返回错误，不等待超时
+        let start_time = std::time::Instant::now();
+        assert!(
+            capture_portal_screenshot()
+                .unwrap_err()
+                .contains("is not running")
+        );
+        assert!(start_time.elapsed() < PORTAL_RESPONSE_TIMEOUT);
+
+        let mock_portal = MockPortal {
+            uri: url::Url::from_file_path(&file.0).unwrap().to_string(),
+        };
+        let _connection = zbus::blocking::connection::Builder::session()
+            .and_then(|builder| builder.name(PORTAL_BUS_NAME))
+            .and_then(|builder| builder.serve_at(PORTAL_OBJECT_PATH, mock_portal))
+            .and_then(|builder| builder.build())
+            .expect("Mock portal is not available");
+
+        let image = capture_portal_screenshot().unwrap();
+        assert_eq!(image.width(), 64);
+        assert_eq!(image.height(), 32);
+        assert_eq!(image.to_rgb8().get_pixel(0, 0), &image::Rgb([10, 20, 30]));
+
+        // 读取后删除 portal 保存的文件
+        assert!(!file.0.exists());
+    }
+}
@@ -9,6 +9,7 @@ use tauri::Manager;
 use tokio::{sync::Mutex, time::Duration};
 
 use snow_shot_app_os::notification;
+use snow_shot_app_os::{NotificationAction, NotificationOptions};
 use snow_shot_app_services::free_drag_window_service::FreeDragWindowService;
 use snow_shot_app_shared::{ElementRect, EnigoManager};
 use snow_shot_app_utils::get_target_monitor;
@@ -314,8 +315,71 @@ pub async fn get_monitors_bounding_box(
     })
 }
 
-pub async fn send_new_version_notification(title: String, body: String) {
-    notification::send_new_version_notification(title, body);
+pub async fn send_new_version_notification(title: String, body: String, action_label: String) {
+    notification::send_new_version_notification(title, body, action_label);
+}
+
+pub const NOTIFICATION_ACTION_INVOKED_EMIT_KEY: &str = "notification:action-invoked";
+
+#[derive(Serialize, Clone)]
+struct NotificationActionInvokedEvent {
+    notification_id: u32,
+    action_id: String,
+}
+
+/// 发送系统通知，点击按钮后通过 NOTIFICATION_ACTION_INVOKED_EMIT_KEY 事件通知前端
+///
+/// @param image_path 通知中显示的图片，如截图文件
+pub async fn send_notification(
+    app: tauri::AppHandle,
+    title: String,
+    body: String,
+    image_path: Option<PathBuf>,
+    action_list: Vec<NotificationAction>,
+) -> Result<u32, String> {
+    // 读取图片和 D-Bus 调用都是阻塞的，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || {
+        let image = match image_path {
+            Some(image_path) => match image::open(&image_path) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    return Err(format!(
+                        "[send_notification] Failed to open image: {} {}",
+                        e,
+                        image_path.display()
+                    ));
+                }
+            },
+            None => None,
+        };
+
+        notification::send_notification(
+            NotificationOptions {
+                title,
+                body,
+                image,
+                action_list,
+            },
+            move |notification_id, action_id| {
+                if let Err(e) = app.emit(
+                    NOTIFICATION_ACTION_INVOKED_EMIT_KEY,
+                    NotificationActionInvokedEvent {
+                        notification_id,
+                        action_id,
+                    },
+                ) {
+                    log::error!("[send_notification] Failed to emit action event: {:?}", e);
+                }
+            },
+        )
+    })
+    .await
+    .map_err(|e| {
+        format!(
+            "[send_notification] Failed to join notification task: {}",
+            e
+        )
+    })?
 }
 
 #[derive(Serialize, Clone, Copy)]
@@ -7,6 +7,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::Manager;
 use tauri::ipc::Response;
 use tokio::sync::Mutex;
+#[cfg(not(target_os = "linux"))]
 use xcap::Window;
 
 pub async fn capture_current_monitor(
@@ -85,14 +86,37 @@ where
 
     #[cfg(target_os = "linux")]
     {
-        let (_, _, monitor) = snow_shot_app_utils::get_target_monitor();
+        // 按包含边框的窗口区域截取，窗口超出显示器的部分会被裁剪
+        let window_image = snow_shot_app_os::utils::get_focused_window().and_then(|window| {
+            let monitor_list = snow_shot_app_utils::monitor_info::MonitorList::all();
+            let window_rect = window
+                .rect
+                .clip_rect(&monitor_list.get_monitors_bounding_box());
+            if window_rect.min_x >= window_rect.max_x || window_rect.min_y >= window_rect.max_y {
+                return None;
+            }
 
-        image = match monitor.capture_image() {
-            Ok(image) => image,
-            Err(_) => {
-                return Err(String::from(
-                    "[capture_focused_window] Failed to capture image",
-                ));
+            snow_shot_app_utils::monitor_info::MonitorList::get_by_region(window_rect)
+                .capture_region_with_alpha(window_rect, None)
+                .ok()
+        });
+
+        image = match window_image {
+            Some(image) => image.into_rgba8(),
+            None => {
+                log::warn!("[capture_focused_window] Failed to capture focused window");
+                // 改成捕获当前显示器
+
+                let (_, _, monitor) = snow_shot_app_utils::get_target_monitor()?;
+
+                match monitor.capture_image() {
+                    Ok(image) => image,
+                    Err(_) => {
+                        return Err(String::from(
+                            "[capture_focused_window] Failed to capture image",
+                        ));
+                    }
+                }
             }
         };
     }
@@ -196,6 +220,21 @@ pub struct WindowElement {
     window_id: u32,
 }
 
+#[cfg(target_os = "linux")]
+pub async fn get_window_elements(
+    #[allow(unused_variables)] window: tauri::Window,
+) -> Result<Vec<WindowElement>, ()> {
+    // 通过 _NET_CLIENT_LIST_STACKING 获取窗口，区域包含窗口管理器绘制的边框
+    Ok(snow_shot_app_os::utils::get_window_list()
+        .into_iter()
+        .map(|window| WindowElement {
+            element_rect: window.rect,
+            window_id: window.window_id,
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
 pub async fn get_window_elements(
     #[allow(unused_variables)] window: tauri::Window,
 ) -> Result<Vec<WindowElement>, ()> {
@@ -322,13 +361,23 @@ pub async fn get_window_elements(
     Ok(rect_list)
 }
 
-pub async fn switch_always_on_top(#[allow(unused_variables)] window_id: u32) -> bool {
+/**
+ * 切换窗口置顶状态，返回切换后窗口是否置顶
+ */
+pub async fn switch_always_on_top(window_id: u32) -> bool {
+    if window_id == 0 {
+        return false;
+    }
+
+    // 切换后可能需要等待窗口管理器处理，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || switch_always_on_top_core(window_id))
+        .await
+        .unwrap_or(false)
+}
+
+fn switch_always_on_top_core(#[allow(unused_variables)] window_id: u32) -> bool {
     #[cfg(target_os = "windows")]
     {
-        if window_id == 0 {
-            return false;
-        }
-
         let window_list = Window::all().unwrap_or_default();
         let window = window_list
             .iter()
@@ -346,20 +395,21 @@ pub async fn switch_always_on_top(#[allow(unused_variables)] window_id: u32) ->
             Err(_) => return false,
         };
 
-        snow_shot_app_os::utils::switch_always_on_top(window_hwnd);
+        snow_shot_app_os::utils::switch_always_on_top(window_hwnd)
     }
 
     #[cfg(target_os = "linux")]
     {
-        snow_shot_app_os::utils::switch_always_on_top();
+        snow_shot_app_os::utils::switch_always_on_top(window_id)
     }
 
+    // macOS 暂未实现，窗口状态不变
     #[cfg(target_os = "macos")]
     {
         snow_shot_app_os::utils::switch_always_on_top();
-    }
 
-    true
+        false
+    }
 }
 
 pub async fn get_element_from_position(
@@ -1,15 +1,40 @@
+use base64::prelude::*;
+use enigo::{Axis, Coordinate, Mouse};
+use image::DynamicImage;
 use image::codecs::png::{self, CompressionType, PngEncoder};
 use image::imageops::FilterType;
 use serde::Serialize;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_capture_service::ScrollScreenshotCaptureService;
-use snow_shot_app_shared::ElementRect;
+use snow_shot_app_services::video_record_service::VideoRecordService;
+use snow_shot_app_shared::{ElementRect, EnigoManager};
 use std::path::PathBuf;
 use tauri::ipc::Response;
+use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
+use tokio::time::Duration;
 
-use snow_shot_app_scroll_screenshot_service::scroll_screenshot_image_service::ScrollScreenshotImageService;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_area_detection::{
+    ScrollAreaDetection, detect_scroll_area,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_auto_scroll_service::{
+    ScrollAutoScrollOptions, ScrollAutoScrollProgress, ScrollAutoScrollService,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_export::ScrollExportFormat;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_image_service::{
+    ScrollImagePushResult, ScrollScreenshotImage, ScrollScreenshotImageService,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_matcher::ScrollMatcherType;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_panorama_service::ScrollPanoramaService;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_seam::ScrollSeamMode;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_service::{
-    ScrollDirection, ScrollImageList, ScrollScreenshotService,
+    ScrollDirection, ScrollImageList, ScrollOffset, ScrollScreenshotService,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_stitch_service::ScrollStitchFrameStatus;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_video::{
+    ScrollVideoFormat, ScrollVideoOptions, ScrollVideoRenderer,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_worker_service::{
+    ScrollScreenshotWorkerHandle, ScrollScreenshotWorkerService,
 };
 use snow_shot_app_utils::{self, save_image_to_file};
 
@@ -23,6 +48,11 @@ pub async fn scroll_screenshot_init(
     descriptor_patch_size: usize,
     min_size_delta: i32,
     try_rollback: bool,
+    cross_axis_tolerance: u32,
+    seam_mode: ScrollSeamMode,
+    matcher_type: ScrollMatcherType,
+    frame_memory_budget: usize,
+    trim_scrollbar: bool,
 ) -> Result<(), ()> {
     let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
 
@@ -35,109 +65,555 @@ pub async fn scroll_screenshot_init(
         descriptor_patch_size,
         min_size_delta,
         try_rollback,
+        cross_axis_tolerance,
+        seam_mode,
+        matcher_type,
     );
+    // 前端以 MB 为单位
+    scroll_screenshot_service.set_frame_memory_budget(frame_memory_budget * 1024 * 1024);
+    scroll_screenshot_service.set_scrollbar_trim(trim_scrollbar);
 
     Ok(())
 }
 
+/**
+ * 设置排除区域，坐标相对截取到的图片，区域内的内容不参与匹配
+ * 适合视频、动画广告等持续变化的内容
+ */
+pub async fn scroll_screenshot_set_exclusion_rects(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    exclusion_rect_list: Vec<ElementRect>,
+) -> Result<(), ()> {
+    scroll_screenshot_service
+        .lock()
+        .await
+        .set_exclusion_rects(exclusion_rect_list);
+
+    Ok(())
+}
+
+/**
+ * 截取选区图片
+ */
+async fn capture_scroll_image(
+    window: &tauri::Window,
+    scroll_screenshot_capture_service: &Mutex<ScrollScreenshotCaptureService>,
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+) -> Result<DynamicImage, String> {
+    #[cfg(target_os = "macos")]
+    let rect_scale;
+    #[cfg(not(target_os = "macos"))]
+    let rect_scale = 1.0f64;
+
+    // macOS 下截图区域是基于逻辑像素
+    #[cfg(target_os = "macos")]
+    {
+        rect_scale = (1.0 / window.scale_factor().unwrap_or(1.0)) as f64;
+    }
+
+    let min_x = min_x as f64 * rect_scale;
+    let min_y = min_y as f64 * rect_scale;
+    let max_x = max_x as f64 * rect_scale;
+    let max_y = max_y as f64 * rect_scale;
+
+    let crop_region = ElementRect {
+        min_x: min_x.round() as i32,
+        min_y: min_y.round() as i32,
+        max_x: max_x.round() as i32,
+        max_y: max_y.round() as i32,
+    };
+    let mut monitor_list_service = scroll_screenshot_capture_service.lock().await;
+    monitor_list_service.init(crop_region);
+
+    let monitor_list = monitor_list_service.get();
+
+    // 保留透明度，没有透明区域的图片由拼接流程转为 RGB
+    monitor_list.capture_region_with_alpha(crop_region, Some(window))
+}
+
+/**
+ * 截取选区图片并加入截图队列，后台拼接线程运行时唤醒它处理
+ */
 pub async fn scroll_screenshot_capture(
     window: tauri::Window,
     scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
     scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
     scroll_image_list: ScrollImageList,
     min_x: i32,
     min_y: i32,
     max_x: i32,
     max_y: i32,
-) -> Result<(), String> {
+) -> Result<ScrollImagePushResult, String> {
     // 区域截图
-    let image = {
-        #[cfg(target_os = "macos")]
-        let rect_scale;
-        #[cfg(not(target_os = "macos"))]
-        let rect_scale = 1.0f64;
-
-        // macOS 下截图区域是基于逻辑像素
-        #[cfg(target_os = "macos")]
-        {
-            rect_scale = (1.0 / window.scale_factor().unwrap_or(1.0)) as f64;
+    let image = capture_scroll_image(
+        &window,
+        &scroll_screenshot_capture_service,
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+    )
+    .await?;
+
+    let push_result = scroll_screenshot_image_service
+        .lock()
+        .await
+        .push_image(image, scroll_image_list);
+
+    if push_result == ScrollImagePushResult::Queued {
+        scroll_screenshot_worker_service.lock().await.notify();
+    }
+
+    Ok(push_result)
+}
+
+/// 自动滚动截图的进度事件
+pub const SCROLL_SCREENSHOT_AUTO_SCROLL_PROGRESS_EMIT_KEY: &str =
+    "scroll-screenshot:auto-scroll-progress";
+
+/**
+ * 自动滚动并截图，直到到达内容末尾或满足停止条件
+ * 鼠标移动到选区中心后发送滚轮事件，每次滚动后截图加入队列，由后台拼接线程处理并发送进度事件
+ * 需要先启动后台拼接线程
+ */
+pub async fn scroll_screenshot_auto_scroll(
+    window: tauri::Window,
+    enigo_manager: tauri::State<'_, Mutex<EnigoManager>>,
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+    scroll_auto_scroll_service: tauri::State<'_, Mutex<ScrollAutoScrollService>>,
+    scroll_image_list: ScrollImageList,
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+    options: ScrollAutoScrollOptions,
+) -> Result<ScrollAutoScrollProgress, String> {
+    scroll_auto_scroll_service.lock().await.start(options)?;
+
+    // 滚轮事件需要穿透当前窗口
+    let result = match window.set_ignore_cursor_events(true) {
+        Ok(_) => {
+            auto_scroll_core(
+                &window,
+                &enigo_manager,
+                &scroll_screenshot_service,
+                &scroll_screenshot_capture_service,
+                &scroll_screenshot_image_service,
+                &scroll_screenshot_worker_service,
+                &scroll_auto_scroll_service,
+                scroll_image_list,
+                ElementRect {
+                    min_x,
+                    min_y,
+                    max_x,
+                    max_y,
+                },
+            )
+            .await
         }
+        Err(e) => Err(format!(
+            "[scroll_screenshot_auto_scroll] Failed to set ignore cursor events: {}",
+            e
+        )),
+    };
 
-        let min_x = min_x as f64 * rect_scale;
-        let min_y = min_y as f64 * rect_scale;
-        let max_x = max_x as f64 * rect_scale;
-        let max_y = max_y as f64 * rect_scale;
+    let _ = window.set_ignore_cursor_events(false);
+    scroll_auto_scroll_service.lock().await.finish();
 
-        let crop_region = ElementRect {
-            min_x: min_x.round() as i32,
-            min_y: min_y.round() as i32,
-            max_x: max_x.round() as i32,
-            max_y: max_y.round() as i32,
-        };
-        let mut monitor_list_service = scroll_screenshot_capture_service.lock().await;
-        monitor_list_service.init(crop_region);
+    result
+}
+
+async fn auto_scroll_core(
+    window: &tauri::Window,
+    enigo_manager: &Mutex<EnigoManager>,
+    scroll_screenshot_service: &Mutex<ScrollScreenshotService>,
+    scroll_screenshot_capture_service: &Mutex<ScrollScreenshotCaptureService>,
+    scroll_screenshot_image_service: &Mutex<ScrollScreenshotImageService>,
+    scroll_screenshot_worker_service: &Mutex<ScrollScreenshotWorkerService>,
+    scroll_auto_scroll_service: &Mutex<ScrollAutoScrollService>,
+    scroll_image_list: ScrollImageList,
+    region: ElementRect,
+) -> Result<ScrollAutoScrollProgress, String> {
+    if !scroll_screenshot_worker_service.lock().await.is_running() {
+        return Err(String::from(
+            "[scroll_screenshot_auto_scroll] Worker is not running",
+        ));
+    }
 
-        let monitor_list = monitor_list_service.get();
+    let options = *scroll_auto_scroll_service.lock().await.options();
 
-        monitor_list.capture_region(crop_region, Some(&window))?
+    let axis =
+        if scroll_screenshot_service.lock().await.current_direction == ScrollDirection::Vertical {
+            Axis::Vertical
+        } else {
+            Axis::Horizontal
+        };
+    let scroll_length = if scroll_image_list == ScrollImageList::Top {
+        -options.scroll_length.abs()
+    } else {
+        options.scroll_length.abs()
     };
 
-    scroll_screenshot_image_service
-        .lock()
-        .await
-        .push_image(image, scroll_image_list);
+    // macOS 下鼠标坐标是基于逻辑像素
+    #[cfg(target_os = "macos")]
+    let mouse_scale = 1.0 / window.scale_factor().unwrap_or(1.0);
+    #[cfg(not(target_os = "macos"))]
+    let mouse_scale = 1.0f64;
 
-    Ok(())
+    let mouse_x = ((region.min_x + region.max_x) as f64 / 2.0 * mouse_scale).round() as i32;
+    let mouse_y = ((region.min_y + region.max_y) as f64 / 2.0 * mouse_scale).round() as i32;
+
+    loop {
+        let image = capture_scroll_image(
+            window,
+            scroll_screenshot_capture_service,
+            region.min_x,
+            region.min_y,
+            region.max_x,
+            region.max_y,
+        )
+        .await?;
+
+        let push_result = scroll_screenshot_image_service
+            .lock()
+            .await
+            .push_image(image, scroll_image_list);
+
+        if push_result == ScrollImagePushResult::Queued {
+            scroll_screenshot_worker_service.lock().await.notify();
+        }
+
+        // 等待拼接线程处理完该帧，再决定是否继续滚动
+        let progress =
+            wait_auto_scroll_progress(scroll_screenshot_worker_service, scroll_auto_scroll_service)
+                .await?;
+
+        if progress.stop_reason.is_some() {
+            return Ok(progress);
+        }
+
+        {
+            let mut enigo = enigo_manager.lock().await;
+            let enigo = enigo.get_enigo()?;
+
+            // 每次都移动鼠标，避免用户移动鼠标后滚动其他区域
+            enigo
+                .move_mouse(mouse_x, mouse_y, Coordinate::Abs)
+                .map_err(|e| {
+                    format!(
+                        "[scroll_screenshot_auto_scroll] Failed to move mouse: {}",
+                        e
+                    )
+                })?;
+            enigo
+                .scroll(scroll_length, axis)
+                .map_err(|e| format!("[scroll_screenshot_auto_scroll] Failed to scroll: {}", e))?;
+        }
+
+        tokio::time::sleep(Duration::from_millis(options.settle_delay)).await;
+    }
+}
+
+async fn wait_auto_scroll_progress(
+    scroll_screenshot_worker_service: &Mutex<ScrollScreenshotWorkerService>,
+    scroll_auto_scroll_service: &Mutex<ScrollAutoScrollService>,
+) -> Result<ScrollAutoScrollProgress, String> {
+    loop {
+        if let Some(progress) = scroll_auto_scroll_service.lock().await.take_progress() {
+            return Ok(progress);
+        }
+
+        if !scroll_screenshot_worker_service.lock().await.is_running() {
+            return Err(String::from(
+                "[scroll_screenshot_auto_scroll] Worker stopped before the frame was handled",
+            ));
+        }
+
+        tokio::time::sleep(Duration::from_millis(16)).await;
+    }
 }
 
 /**
- * 处理目前截取到的所有图片
+ * 检测选区内实际滚动的区域和方向
+ * 在滚动前后各截取一次选区，先尝试垂直滚动，没有检测到再尝试水平滚动，检测后滚动回原位置
+ * 返回的区域和偏移与传入的选区使用相同的坐标
  */
-pub async fn scroll_screenshot_handle_image(
-    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
-    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
-    thumbnail_size: u32,
-) -> Result<Response, ()> {
-    let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
+pub async fn scroll_screenshot_detect_area(
+    window: tauri::Window,
+    enigo_manager: tauri::State<'_, Mutex<EnigoManager>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+    scroll_length: i32,
+    settle_delay: u64,
+) -> Result<Option<ScrollAreaDetection>, String> {
+    let region = ElementRect {
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+    };
 
-    // 把 scroll_screenshot_image_service.lock 后置，降低阻塞截图的概率，让截图堆积在截图队列中
-    let has_left_image;
-    let scroll_image = {
-        let mut scroll_screenshot_image_service = scroll_screenshot_image_service.lock().await;
+    // 滚轮事件需要穿透当前窗口
+    window.set_ignore_cursor_events(true).map_err(|e| {
+        format!(
+            "[scroll_screenshot_detect_area] Failed to set ignore cursor events: {}",
+            e
+        )
+    })?;
 
-        // 下面 pop 了，所以需要大于 1
-        has_left_image = scroll_screenshot_image_service.image_count() > 1;
+    let mut result = Ok(None);
+    for axis in [Axis::Vertical, Axis::Horizontal] {
+        result = detect_area_core(
+            &window,
+            &enigo_manager,
+            &scroll_screenshot_capture_service,
+            region,
+            scroll_length,
+            settle_delay,
+            axis,
+        )
+        .await;
 
-        match scroll_screenshot_image_service.pop_image() {
-            Some(scroll_image) => scroll_image,
-            None => return Ok(Response::new(vec![2])), // 特殊标记，表示没有图片
+        if !matches!(result, Ok(None)) {
+            break;
         }
+    }
+
+    let _ = window.set_ignore_cursor_events(false);
+
+    result
+}
+
+async fn scroll_mouse_wheel(
+    enigo_manager: &Mutex<EnigoManager>,
+    mouse_x: i32,
+    mouse_y: i32,
+    scroll_length: i32,
+    axis: Axis,
+) -> Result<(), String> {
+    let mut enigo = enigo_manager.lock().await;
+    let enigo = enigo.get_enigo()?;
+
+    enigo
+        .move_mouse(mouse_x, mouse_y, Coordinate::Abs)
+        .map_err(|e| format!("[scroll_mouse_wheel] Failed to move mouse: {}", e))?;
+    enigo
+        .scroll(scroll_length, axis)
+        .map_err(|e| format!("[scroll_mouse_wheel] Failed to scroll: {}", e))?;
+
+    Ok(())
+}
+
+async fn detect_area_core(
+    window: &tauri::Window,
+    enigo_manager: &Mutex<EnigoManager>,
+    scroll_screenshot_capture_service: &Mutex<ScrollScreenshotCaptureService>,
+    region: ElementRect,
+    scroll_length: i32,
+    settle_delay: u64,
+    axis: Axis,
+) -> Result<Option<ScrollAreaDetection>, String> {
+    // macOS 下鼠标坐标是基于逻辑像素
+    #[cfg(target_os = "macos")]
+    let mouse_scale = 1.0 / window.scale_factor().unwrap_or(1.0);
+    #[cfg(not(target_os = "macos"))]
+    let mouse_scale = 1.0f64;
+
+    let mouse_x = ((region.min_x + region.max_x) as f64 / 2.0 * mouse_scale).round() as i32;
+    let mouse_y = ((region.min_y + region.max_y) as f64 / 2.0 * mouse_scale).round() as i32;
+
+    let before_image = capture_scroll_image(
+        window,
+        scroll_screenshot_capture_service,
+        region.min_x,
+        region.min_y,
+        region.max_x,
+        region.max_y,
+    )
+    .await?;
+    scroll_mouse_wheel(enigo_manager, mouse_x, mouse_y, scroll_length, axis).await?;
+    tokio::time::sleep(Duration::from_millis(settle_delay)).await;
+
+    let after_image = capture_scroll_image(
+        window,
+        scroll_screenshot_capture_service,
+        region.min_x,
+        region.min_y,
+        region.max_x,
+        region.max_y,
+    )
+    .await?;
+    // 滚动回原位置
+    scroll_mouse_wheel(enigo_manager, mouse_x, mouse_y, -scroll_length, axis).await?;
+    tokio::time::sleep(Duration::from_millis(settle_delay)).await;
+
+    let detection = match detect_scroll_area(&before_image, &after_image) {
+        Some(detection) => detection,
+        None => return Ok(None),
     };
 
-    let (handle_result, is_origin, result_scroll_image_list) =
-        scroll_screenshot_service.handle_image(scroll_image.image, scroll_image.direction);
+    // 截图可能是物理像素，转换回选区的坐标
+    let scale_x = (region.max_x - region.min_x) as f64 / before_image.width().max(1) as f64;
+    let scale_y = (region.max_y - region.min_y) as f64 / before_image.height().max(1) as f64;
+    let offset_scale = if detection.direction == ScrollDirection::Vertical {
+        scale_y
+    } else {
+        scale_x
+    };
+
+    Ok(Some(ScrollAreaDetection {
+        rect: ElementRect {
+            min_x: region.min_x + (detection.rect.min_x as f64 * scale_x).round() as i32,
+            min_y: region.min_y + (detection.rect.min_y as f64 * scale_y).round() as i32,
+            max_x: region.min_x + (detection.rect.max_x as f64 * scale_x).round() as i32,
+            max_y: region.min_y + (detection.rect.max_y as f64 * scale_y).round() as i32,
+        },
+        direction: detection.direction,
+        offset: (detection.offset as f64 * offset_scale).round() as i32,
+    }))
+}
+
+pub async fn scroll_screenshot_stop_auto_scroll(
+    scroll_auto_scroll_service: tauri::State<'_, Mutex<ScrollAutoScrollService>>,
+) -> Result<(), ()> {
+    scroll_auto_scroll_service.lock().await.request_stop();
+
+    Ok(())
+}
+
+/// scroll_screenshot_handle_image 响应格式版本，格式不兼容时递增
+pub const SCROLL_SCREENSHOT_HANDLE_IMAGE_RESULT_VERSION: u32 = 1;
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+pub enum ScrollScreenshotHandleImageStatus {
+    /// 截图队列中没有图片
+    NoImage,
+    /// 图片没有带来新的区域
+    Unchanged,
+    /// 没有匹配到已有内容
+    NotMatched,
+    /// 图片已追加到图片列表
+    Appended,
+}
+
+/**
+ * scroll_screenshot_handle_image 的处理结果
+ * 响应依次为：结果长度（u32 小端）、结果（UTF-8 JSON）、缩略图（PNG，仅追加图片时存在）
+ */
+#[derive(Serialize, Debug)]
+pub struct ScrollScreenshotHandleImageResult {
+    /// 响应格式版本
+    pub version: u32,
+    pub status: ScrollScreenshotHandleImageStatus,
+    /// 截图队列中是否还有待处理的图片
+    pub has_pending_image: bool,
+    /// 匹配置信度，首帧或未匹配时为空
+    pub confidence: Option<f32>,
+    /// 支持匹配偏移的特征数量，首帧或未匹配时为空
+    pub inlier_count: Option<usize>,
+    /// 图片边缘相对首帧的位置（方向边），匹配成功时存在
+    pub edge_position: Option<i32>,
+    /// 缩略图中与已有图片重叠的尺寸，追加图片时存在
+    pub overlay_size: Option<i32>,
+    pub top_image_size: i32,
+    pub bottom_image_size: i32,
+    /// 图片匹配到的列表
+    pub current_direction: ScrollImageList,
+    /// 缩略图字节数
+    pub thumbnail_size: usize,
+}
+
+impl ScrollScreenshotHandleImageResult {
+    fn new(
+        status: ScrollScreenshotHandleImageStatus,
+        scroll_screenshot_service: &ScrollScreenshotService,
+        current_direction: ScrollImageList,
+    ) -> Self {
+        let match_quality = scroll_screenshot_service.last_match_quality;
 
-    if is_origin {
-        return Ok(Response::new(vec![1])); // 特殊标记，表示是未变化
+        Self {
+            version: SCROLL_SCREENSHOT_HANDLE_IMAGE_RESULT_VERSION,
+            status,
+            has_pending_image: false,
+            confidence: match_quality.map(|match_quality| match_quality.confidence),
+            inlier_count: match_quality.map(|match_quality| match_quality.inlier_count),
+            edge_position: None,
+            overlay_size: None,
+            top_image_size: scroll_screenshot_service.top_image_size,
+            bottom_image_size: scroll_screenshot_service.bottom_image_size,
+            current_direction,
+            thumbnail_size: 0,
+        }
     }
 
-    let handle_result = match handle_result {
-        Some(result) => result,
-        None => {
-            return if has_left_image {
-                Ok(Response::new(vec![1])) // 还有剩余图片，这里就返回未变化，不提示用户未识别到
-            } else {
-                Ok(Response::new(vec![]))
-            };
+    fn into_response(mut self, thumbnail: Vec<u8>) -> Result<Response, String> {
+        self.thumbnail_size = thumbnail.len();
+
+        let result = serde_json::to_vec(&self).map_err(|e| {
+            format!(
+                "[ScrollScreenshotHandleImageResult::into_response] Failed to serialize result: {}",
+                e
+            )
+        })?;
+
+        let mut buf = Vec::with_capacity(4 + result.len() + thumbnail.len());
+        buf.extend_from_slice(&(result.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&result);
+        buf.extend_from_slice(&thumbnail);
+
+        Ok(Response::new(buf))
+    }
+}
+
+/**
+ * 处理一张截图，返回处理结果和缩略图（PNG，仅追加图片时存在）
+ */
+fn handle_scroll_image(
+    scroll_screenshot_service: &mut ScrollScreenshotService,
+    scroll_image: ScrollScreenshotImage,
+    has_pending_image: bool,
+    thumbnail_size: u32,
+) -> Result<(ScrollScreenshotHandleImageResult, Vec<u8>), String> {
+    let (handle_result, is_origin, result_scroll_image_list) =
+        scroll_screenshot_service.handle_image(scroll_image.image, scroll_image.direction);
+
+    let status = if is_origin {
+        ScrollScreenshotHandleImageStatus::Unchanged
+    } else {
+        match handle_result {
+            Some((_, Some(_))) => ScrollScreenshotHandleImageStatus::Appended,
+            Some((_, None)) => ScrollScreenshotHandleImageStatus::Unchanged,
+            None => ScrollScreenshotHandleImageStatus::NotMatched,
         }
     };
 
-    let crop_image = match handle_result {
-        (edge_position, None) => {
-            return Ok(Response::new(edge_position.to_le_bytes().to_vec()));
-        }
-        (_, Some(ScrollImageList::Top)) => scroll_screenshot_service.top_image_list.last().unwrap(),
-        (_, Some(ScrollImageList::Bottom)) => {
+    let mut result = ScrollScreenshotHandleImageResult::new(
+        status,
+        scroll_screenshot_service,
+        result_scroll_image_list,
+    );
+    result.has_pending_image = has_pending_image;
+
+    let (edge_position, scroll_image_list) = match handle_result {
+        Some(handle_result) => handle_result,
+        None => return Ok((result, vec![])),
+    };
+    result.edge_position = Some(edge_position);
+
+    let crop_image = match scroll_image_list {
+        None => return Ok((result, vec![])),
+        Some(ScrollImageList::Top) => scroll_screenshot_service.top_image_list.last().unwrap(),
+        Some(ScrollImageList::Bottom) => {
             scroll_screenshot_service.bottom_image_list.last().unwrap()
         }
     };
@@ -152,7 +628,7 @@ pub async fn scroll_screenshot_handle_image(
         thumbnail_size as f32 / image_height as f32
     };
 
-    let thumbnail = crop_image.image.resize(
+    let thumbnail = crop_image.image.decode()?.resize(
         ((image_width as f32 * scale) as u32).max(1), // 防止图片某一边为 0
         ((image_height as f32 * scale) as u32).max(1),
         FilterType::Triangle,
@@ -164,16 +640,194 @@ pub async fn scroll_screenshot_handle_image(
             CompressionType::Fast,
             png::FilterType::Paeth,
         ))
-        .unwrap();
+        .map_err(|e| format!("[handle_scroll_image] Failed to encode thumbnail: {}", e))?;
 
-    // 添加边缘位置信息到缓冲区末尾
-    buf.extend_from_slice(&handle_result.0.to_le_bytes());
-    buf.extend_from_slice(&((crop_image.overlay_size as f32 * scale) as i32).to_le_bytes());
-    buf.extend_from_slice(&scroll_screenshot_service.top_image_size.to_le_bytes());
-    buf.extend_from_slice(&scroll_screenshot_service.bottom_image_size.to_le_bytes());
-    buf.extend_from_slice(&(result_scroll_image_list as i32).to_le_bytes());
+    result.overlay_size = Some((crop_image.overlay_size as f32 * scale) as i32);
 
-    Ok(Response::new(buf))
+    Ok((result, buf))
+}
+
+/**
+ * 处理目前截取到的所有图片
+ */
+pub async fn scroll_screenshot_handle_image(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    thumbnail_size: u32,
+) -> Result<Response, String> {
+    let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
+
+    // 把 scroll_screenshot_image_service.lock 后置，降低阻塞截图的概率，让截图堆积在截图队列中
+    let has_pending_image;
+    let scroll_image = {
+        let mut scroll_screenshot_image_service = scroll_screenshot_image_service.lock().await;
+
+        // 下面 pop 了，所以需要大于 1
+        has_pending_image = scroll_screenshot_image_service.image_count() > 1;
+
+        match scroll_screenshot_image_service.pop_image() {
+            Some(scroll_image) => scroll_image,
+            None => {
+                return ScrollScreenshotHandleImageResult::new(
+                    ScrollScreenshotHandleImageStatus::NoImage,
+                    &scroll_screenshot_service,
+                    ScrollImageList::Bottom,
+                )
+                .into_response(vec![]);
+            }
+        }
+    };
+
+    let (result, thumbnail) = handle_scroll_image(
+        &mut scroll_screenshot_service,
+        scroll_image,
+        has_pending_image,
+        thumbnail_size,
+    )?;
+
+    result.into_response(thumbnail)
+}
+
+/// 后台拼接线程处理完一张截图后发送的事件
+pub const SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY: &str = "scroll-screenshot:handle-image";
+
+/**
+ * 后台拼接线程发送的处理结果，缩略图使用 base64 编码
+ */
+#[derive(Serialize, Debug)]
+pub struct ScrollScreenshotHandleImageEvent {
+    #[serde(flatten)]
+    pub result: ScrollScreenshotHandleImageResult,
+    /// 缩略图（PNG），仅追加图片时存在
+    pub thumbnail: Option<String>,
+}
+
+/**
+ * 启动后台拼接线程，截图加入队列后由该线程处理，并通过事件发送结果
+ * 重复启动时旧的线程会在处理完当前图片后退出
+ */
+pub async fn scroll_screenshot_start_worker(
+    window: tauri::Window,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+    thumbnail_size: u32,
+) -> Result<(), String> {
+    let worker_handle = scroll_screenshot_worker_service.lock().await.start();
+
+    std::thread::Builder::new()
+        .name(String::from("scroll-screenshot-worker"))
+        .spawn(move || scroll_screenshot_worker(window, worker_handle, thumbnail_size))
+        .map_err(|e| {
+            format!(
+                "[scroll_screenshot_start_worker] Failed to spawn worker: {}",
+                e
+            )
+        })?;
+
+    Ok(())
+}
+
+fn scroll_screenshot_worker(
+    window: tauri::Window,
+    worker_handle: ScrollScreenshotWorkerHandle,
+    thumbnail_size: u32,
+) {
+    let scroll_screenshot_service = window.state::<Mutex<ScrollScreenshotService>>();
+    let scroll_screenshot_image_service = window.state::<Mutex<ScrollScreenshotImageService>>();
+    let scroll_auto_scroll_service = window.state::<Mutex<ScrollAutoScrollService>>();
+
+    while worker_handle.wait() {
+        // 处理完队列中的所有图片后再等待
+        while worker_handle.is_current() {
+            let mut scroll_screenshot_service = scroll_screenshot_service.blocking_lock();
+
+            let (scroll_image, has_pending_image) = {
+                let mut scroll_screenshot_image_service =
+                    scroll_screenshot_image_service.blocking_lock();
+
+                (
+                    scroll_screenshot_image_service.pop_image(),
+                    scroll_screenshot_image_service.has_image(),
+                )
+            };
+
+            let scroll_image = match scroll_image {
+                Some(scroll_image) => scroll_image,
+                None => break,
+            };
+
+            let handle_result = handle_scroll_image(
+                &mut scroll_screenshot_service,
+                scroll_image,
+                has_pending_image,
+                thumbnail_size,
+            );
+            let (frame_status, top_image_size, bottom_image_size) = (
+                match &handle_result {
+                    Ok((result, _)) => match result.status {
+                        ScrollScreenshotHandleImageStatus::Appended => {
+                            ScrollStitchFrameStatus::Appended
+                        }
+                        ScrollScreenshotHandleImageStatus::Unchanged => {
+                            ScrollStitchFrameStatus::Unchanged
+                        }
+                        _ => ScrollStitchFrameStatus::NotMatched,
+                    },
+                    Err(_) => ScrollStitchFrameStatus::NotMatched,
+                },
+                scroll_screenshot_service.top_image_size,
+                scroll_screenshot_service.bottom_image_size,
+            );
+            drop(scroll_screenshot_service);
+
+            // 自动滚动时记录每帧的处理结果，处理失败也算作一帧，避免滚动方一直等待
+            {
+                let mut scroll_auto_scroll_service = scroll_auto_scroll_service.blocking_lock();
+                if scroll_auto_scroll_service.is_running() {
+                    let progress = scroll_auto_scroll_service.record_frame(
+                        frame_status,
+                        top_image_size,
+                        bottom_image_size,
+                    );
+
+                    if let Err(e) =
+                        window.emit(SCROLL_SCREENSHOT_AUTO_SCROLL_PROGRESS_EMIT_KEY, progress)
+                    {
+                        log::error!("[scroll_screenshot_worker] Failed to emit progress: {}", e);
+                    }
+                }
+            }
+
+            let (mut result, thumbnail) = match handle_result {
+                Ok(handle_result) => handle_result,
+                Err(e) => {
+                    log::error!("[scroll_screenshot_worker] {}", e);
+                    continue;
+                }
+            };
+
+            result.thumbnail_size = thumbnail.len();
+            let event = ScrollScreenshotHandleImageEvent {
+                result,
+                thumbnail: if thumbnail.is_empty() {
+                    None
+                } else {
+                    Some(BASE64_STANDARD.encode(&thumbnail))
+                },
+            };
+
+            if let Err(e) = window.emit(SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY, event) {
+                log::error!("[scroll_screenshot_worker] Failed to emit result: {}", e);
+            }
+        }
+    }
+}
+
+pub async fn scroll_screenshot_stop_worker(
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+) -> Result<(), ()> {
+    scroll_screenshot_worker_service.lock().await.stop();
+
+    Ok(())
 }
 
 #[derive(Serialize)]
@@ -199,6 +853,19 @@ pub async fn scroll_screenshot_save_to_file(
 ) -> Result<(), String> {
     let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
 
+    let file_path = PathBuf::from(file_path);
+
+    // PNG 直接逐行写入文件，避免很长的截图在内存中生成整张图片
+    let is_png = file_path
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("png"))
+        .unwrap_or(false);
+    if is_png {
+        scroll_screenshot_service.export_to_file(&file_path, ScrollExportFormat::Png, 0)?;
+
+        return Ok(());
+    }
+
     let image = scroll_screenshot_service.export();
     let image = match image {
         Some(image) => image,
@@ -209,11 +876,103 @@ pub async fn scroll_screenshot_save_to_file(
         }
     };
 
-    save_image_to_file(&image, PathBuf::from(file_path)).await?;
+    save_image_to_file(&image, file_path).await?;
 
     Ok(())
 }
 
+/**
+ * 按指定格式导出，分块或分页导出时 max_page_size 为每块在滚动方向上的最大尺寸，为 0 时不分块
+ * 返回写入的文件列表
+ */
+pub async fn scroll_screenshot_export_to_file(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    file_path: String,
+    export_format: ScrollExportFormat,
+    max_page_size: u32,
+) -> Result<Vec<String>, String> {
+    let scroll_screenshot_service = scroll_screenshot_service.lock().await;
+
+    let file_path_list = scroll_screenshot_service.export_to_file(
+        &PathBuf::from(file_path),
+        export_format,
+        max_page_size,
+    )?;
+
+    Ok(file_path_list
+        .into_iter()
+        .map(|file_path| file_path.to_string_lossy().to_string())
+        .collect())
+}
+
+/**
+ * 将拼接结果渲染为滚动播放的视频或 GIF
+ */
+pub async fn scroll_screenshot_export_video(
+    app: tauri::AppHandle,
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    video_record_service: tauri::State<'_, Mutex<VideoRecordService>>,
+    file_path: String,
+    format: ScrollVideoFormat,
+    options: ScrollVideoOptions,
+) -> Result<(), String> {
+    let (image, direction) = {
+        let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
+        let image = match scroll_screenshot_service.export() {
+            Some(image) => image.to_rgb8(),
+            None => {
+                return Err(String::from(
+                    "[scroll_screenshot_export_video] Failed to export image",
+                ));
+            }
+        };
+
+        (image, scroll_screenshot_service.current_direction)
+    };
+
+    // 只在锁内取出 ffmpeg 路径，渲染和编码耗时较长，放到阻塞线程中执行
+    let ffmpeg_path = {
+        let mut video_record_service = video_record_service.lock().await;
+        video_record_service.init(&app);
+        video_record_service.get_ffmpeg_path()
+    };
+    let ffmpeg_path = match ffmpeg_path {
+        Some(ffmpeg_path) => ffmpeg_path,
+        None => {
+            return Err(String::from(
+                "[scroll_screenshot_export_video] Failed to get ffmpeg path",
+            ));
+        }
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let renderer = ScrollVideoRenderer::new(&image, direction, &options)?;
+
+        VideoRecordService::encode_raw_frames(
+            &ffmpeg_path,
+            renderer.width,
+            renderer.height,
+            options.frame_rate.max(1),
+            &format.get_ffmpeg_output_args(),
+            &file_path,
+            renderer,
+        )
+        .map_err(|e| {
+            format!(
+                "[scroll_screenshot_export_video] Failed to encode video: {}",
+                e
+            )
+        })
+    })
+    .await
+    .map_err(|e| {
+        format!(
+            "[scroll_screenshot_export_video] Failed to join encode task: {}",
+            e
+        )
+    })?
+}
+
 pub async fn scroll_screenshot_save_to_clipboard<F>(
     write_image_to_clipboard: F,
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
@@ -245,7 +1004,11 @@ pub async fn scroll_screenshot_clear(
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
     scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
     scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
 ) -> Result<(), ()> {
+    // 先停止后台拼接线程，避免清空后继续处理
+    scroll_screenshot_worker_service.lock().await.stop();
+
     let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
     let mut scroll_screenshot_image_service = scroll_screenshot_image_service.lock().await;
     let mut scroll_screenshot_capture_service = scroll_screenshot_capture_service.lock().await;
@@ -257,6 +1020,57 @@ pub async fn scroll_screenshot_clear(
     Ok(())
 }
 
+/**
+ * 撤销最后追加的图片，返回撤销后的截图尺寸
+ */
+pub async fn scroll_screenshot_undo(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+) -> Result<ScrollScreenshotCaptureSize, String> {
+    let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
+
+    if scroll_screenshot_service.undo().is_none() {
+        return Err(String::from("[scroll_screenshot_undo] No image to undo"));
+    }
+
+    Ok(ScrollScreenshotCaptureSize {
+        top_image_size: scroll_screenshot_service.top_image_size,
+        bottom_image_size: scroll_screenshot_service.bottom_image_size,
+    })
+}
+
+/**
+ * 将当前滚动截图会话保存到目录，用于之后恢复
+ */
+pub async fn scroll_screenshot_save_session(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    directory: String,
+) -> Result<(), String> {
+    let scroll_screenshot_service = scroll_screenshot_service.lock().await;
+
+    scroll_screenshot_service.save_session(&PathBuf::from(directory))
+}
+
+/**
+ * 从目录恢复滚动截图会话，恢复后可以继续截取并追加图片
+ */
+pub async fn scroll_screenshot_restore_session(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    directory: String,
+) -> Result<ScrollScreenshotCaptureSize, String> {
+    let mut scroll_screenshot_service = scroll_screenshot_service.lock().await;
+    let mut scroll_screenshot_image_service = scroll_screenshot_image_service.lock().await;
+
+    scroll_screenshot_service.load_session(&PathBuf::from(directory))?;
+    // 丢弃恢复前尚未处理的图片
+    scroll_screenshot_image_service.clear();
+
+    Ok(ScrollScreenshotCaptureSize {
+        top_image_size: scroll_screenshot_service.top_image_size,
+        bottom_image_size: scroll_screenshot_service.bottom_image_size,
+    })
+}
+
 pub async fn scroll_screenshot_get_image_data(
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
 ) -> Result<Response, ()> {
@@ -280,3 +1094,102 @@ pub async fn scroll_screenshot_get_image_data(
 
     Ok(Response::new(buf))
 }
+
+pub async fn scroll_screenshot_panorama_init(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    sample_rate: f32,
+    min_sample_size: u32,
+    max_sample_size: u32,
+    corner_threshold: u8,
+    descriptor_patch_size: usize,
+    tile_size: u32,
+    frame_memory_budget: usize,
+) -> Result<(), ()> {
+    let mut scroll_panorama_service = scroll_panorama_service.lock().await;
+
+    scroll_panorama_service.init(
+        sample_rate,
+        min_sample_size,
+        max_sample_size,
+        corner_threshold,
+        descriptor_patch_size,
+        tile_size,
+    );
+    // 前端以 MB 为单位
+    scroll_panorama_service.set_frame_memory_budget(frame_memory_budget * 1024 * 1024);
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct ScrollPanoramaHandleResult {
+    /// 为空时表示没有待处理的图片
+    pub status: Option<ScrollStitchFrameStatus>,
+    /// 帧在画布上的位置（相对首帧）
+    pub position: Option<ScrollOffset>,
+    /// 画布外接矩形（相对首帧）
+    pub bounds: ElementRect,
+    /// 尚未覆盖的网格，坐标相对画布左上角
+    pub uncovered_tile_list: Vec<ElementRect>,
+}
+
+/**
+ * 以二维全景的方式处理截图队列中的下一张图片
+ * 截图仍通过 scroll_screenshot_capture 加入队列
+ */
+pub async fn scroll_screenshot_panorama_handle_image(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+) -> Result<ScrollPanoramaHandleResult, ()> {
+    let mut scroll_panorama_service = scroll_panorama_service.lock().await;
+
+    let scroll_image = scroll_screenshot_image_service.lock().await.pop_image();
+
+    let (status, position) = match scroll_image {
+        Some(scroll_image) => {
+            let (status, position) = scroll_panorama_service.handle_image(scroll_image.image);
+            (Some(status), position)
+        }
+        None => (None, None),
+    };
+
+    Ok(ScrollPanoramaHandleResult {
+        status,
+        position,
+        bounds: scroll_panorama_service.bounds,
+        uncovered_tile_list: scroll_panorama_service.get_uncovered_tiles(),
+    })
+}
+
+/**
+ * 保存全景图，背景色为空时未覆盖的区域保持透明
+ */
+pub async fn scroll_screenshot_panorama_save_to_file(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    file_path: String,
+    background_color: Option<[u8; 4]>,
+) -> Result<(), String> {
+    let scroll_panorama_service = scroll_panorama_service.lock().await;
+
+    let image = scroll_panorama_service.export(background_color)?;
+
+    save_image_to_file(&image, PathBuf::from(file_path)).await?;
+
+    Ok(())
+}
+
+pub async fn scroll_screenshot_panorama_clear(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+) -> Result<(), ()> {
+    let mut scroll_panorama_service = scroll_panorama_service.lock().await;
+    let mut scroll_screenshot_image_service = scroll_screenshot_image_service.lock().await;
+    let mut scroll_screenshot_capture_service = scroll_screenshot_capture_service.lock().await;
+
+    scroll_panorama_service.clear();
+    scroll_screenshot_image_service.clear();
+    scroll_screenshot_capture_service.clear();
+
+    Ok(())
+}
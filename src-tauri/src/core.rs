@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+
+use snow_shot_app_os::NotificationAction;
 use snow_shot_app_shared::{ElementRect, EnigoManager};
 use snow_shot_tauri_commands_core::MonitorsBoundingBox;
 use tauri::{Manager, command, ipc::Response};
@@ -78,8 +81,20 @@ pub async fn get_monitors_bounding_box(
 }
 
 #[command]
-pub async fn send_new_version_notification(title: String, body: String) {
-    snow_shot_tauri_commands_core::send_new_version_notification(title, body).await;
+pub async fn send_new_version_notification(title: String, body: String, action_label: String) {
+    snow_shot_tauri_commands_core::send_new_version_notification(title, body, action_label).await;
+}
+
+#[command]
+pub async fn send_notification(
+    app: tauri::AppHandle,
+    title: String,
+    body: String,
+    image_path: Option<PathBuf>,
+    action_list: Vec<NotificationAction>,
+) -> Result<u32, String> {
+    snow_shot_tauri_commands_core::send_notification(app, title, body, image_path, action_list)
+        .await
 }
 
 /// 创建屏幕录制窗口
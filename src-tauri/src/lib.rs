@@ -15,9 +15,12 @@ use tauri::Manager;
 use tauri_plugin_log::{Target, TargetKind};
 
 use snow_shot_app_os::ui_automation::UIElements;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_auto_scroll_service;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_capture_service;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_image_service;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_panorama_service;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_service;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_worker_service;
 use snow_shot_app_services::file_cache_service;
 use snow_shot_app_services::free_drag_window_service;
 use snow_shot_app_services::listen_key_service;
@@ -44,6 +47,11 @@ pub fn run() {
         Mutex::new(scroll_screenshot_image_service::ScrollScreenshotImageService::new());
     let scroll_screenshot_capture_service =
         Mutex::new(scroll_screenshot_capture_service::ScrollScreenshotCaptureService::new());
+    let scroll_panorama_service = Mutex::new(scroll_panorama_service::ScrollPanoramaService::new());
+    let scroll_auto_scroll_service =
+        Mutex::new(scroll_screenshot_auto_scroll_service::ScrollAutoScrollService::new());
+    let scroll_screenshot_worker_service =
+        Mutex::new(scroll_screenshot_worker_service::ScrollScreenshotWorkerService::new());
 
     let free_drag_window_service =
         Mutex::new(free_drag_window_service::FreeDragWindowService::new());
@@ -140,6 +148,9 @@ pub fn run() {
         .manage(scroll_screenshot_service)
         .manage(scroll_screenshot_image_service)
         .manage(scroll_screenshot_capture_service)
+        .manage(scroll_panorama_service)
+        .manage(scroll_auto_scroll_service)
+        .manage(scroll_screenshot_worker_service)
         .manage(video_record_service)
         .manage(free_drag_window_service)
         .manage(listen_key_service)
@@ -174,16 +185,32 @@ pub fn run() {
             core::get_current_monitor_info,
             core::get_monitors_bounding_box,
             core::send_new_version_notification,
+            core::send_notification,
             core::create_video_record_window,
             core::set_current_window_always_on_top,
             scroll_screenshot::scroll_screenshot_get_image_data,
             scroll_screenshot::scroll_screenshot_init,
+            scroll_screenshot::scroll_screenshot_set_exclusion_rects,
             scroll_screenshot::scroll_screenshot_capture,
+            scroll_screenshot::scroll_screenshot_auto_scroll,
+            scroll_screenshot::scroll_screenshot_stop_auto_scroll,
+            scroll_screenshot::scroll_screenshot_detect_area,
             scroll_screenshot::scroll_screenshot_handle_image,
+            scroll_screenshot::scroll_screenshot_start_worker,
+            scroll_screenshot::scroll_screenshot_stop_worker,
             scroll_screenshot::scroll_screenshot_save_to_file,
+            scroll_screenshot::scroll_screenshot_export_to_file,
+            scroll_screenshot::scroll_screenshot_export_video,
             scroll_screenshot::scroll_screenshot_save_to_clipboard,
             scroll_screenshot::scroll_screenshot_get_size,
             scroll_screenshot::scroll_screenshot_clear,
+            scroll_screenshot::scroll_screenshot_undo,
+            scroll_screenshot::scroll_screenshot_save_session,
+            scroll_screenshot::scroll_screenshot_restore_session,
+            scroll_screenshot::scroll_screenshot_panorama_init,
+            scroll_screenshot::scroll_screenshot_panorama_handle_image,
+            scroll_screenshot::scroll_screenshot_panorama_save_to_file,
+            scroll_screenshot::scroll_screenshot_panorama_clear,
             video_record::video_record_start,
             video_record::video_record_stop,
             video_record::video_record_pause,
@@ -214,6 +241,22 @@ pub fn run() {
                 }
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(
+            |#[allow(unused_variables)] app_handle, #[allow(unused_variables)] event| {
+                // 退出时托管的状态不会被释放，需要主动恢复截图时开启的辅助功能支持
+                #[cfg(target_os = "linux")]
+                if let tauri::RunEvent::Exit = event {
+                    match app_handle.state::<Mutex<UIElements>>().try_lock() {
+                        Ok(mut ui_elements) => ui_elements.restore_accessibility(),
+                        Err(_) => {
+                            log::warn!(
+                                "[lib::run] Failed to restore accessibility: ui elements are in use"
+                            );
+                        }
+                    }
+                }
+            },
+        );
 }
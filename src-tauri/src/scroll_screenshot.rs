@@ -3,11 +3,28 @@ use tauri::ipc::Response;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::sync::Mutex;
 
+use snow_shot_app_shared::{ElementRect, EnigoManager};
+
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_area_detection::ScrollAreaDetection;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_auto_scroll_service::{
+    ScrollAutoScrollOptions, ScrollAutoScrollProgress, ScrollAutoScrollService,
+};
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_capture_service::ScrollScreenshotCaptureService;
-use snow_shot_app_scroll_screenshot_service::scroll_screenshot_image_service::ScrollScreenshotImageService;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_export::ScrollExportFormat;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_image_service::{
+    ScrollImagePushResult, ScrollScreenshotImageService,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_matcher::ScrollMatcherType;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_panorama_service::ScrollPanoramaService;
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_seam::ScrollSeamMode;
 use snow_shot_app_scroll_screenshot_service::scroll_screenshot_service::{
     ScrollDirection, ScrollImageList, ScrollScreenshotService,
 };
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_video::{
+    ScrollVideoFormat, ScrollVideoOptions,
+};
+use snow_shot_app_scroll_screenshot_service::scroll_screenshot_worker_service::ScrollScreenshotWorkerService;
+use snow_shot_app_services::video_record_service::VideoRecordService;
 
 #[command]
 pub async fn scroll_screenshot_init(
@@ -20,6 +37,11 @@ pub async fn scroll_screenshot_init(
     descriptor_patch_size: usize,
     min_size_delta: i32,
     try_rollback: bool,
+    cross_axis_tolerance: u32,
+    seam_mode: ScrollSeamMode,
+    matcher_type: ScrollMatcherType,
+    frame_memory_budget: usize,
+    trim_scrollbar: bool,
 ) -> Result<(), ()> {
     snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_init(
         scroll_screenshot_service,
@@ -31,6 +53,23 @@ pub async fn scroll_screenshot_init(
         descriptor_patch_size,
         min_size_delta,
         try_rollback,
+        cross_axis_tolerance,
+        seam_mode,
+        matcher_type,
+        frame_memory_budget,
+        trim_scrollbar,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_set_exclusion_rects(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    exclusion_rect_list: Vec<ElementRect>,
+) -> Result<(), ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_set_exclusion_rects(
+        scroll_screenshot_service,
+        exclusion_rect_list,
     )
     .await
 }
@@ -40,16 +79,18 @@ pub async fn scroll_screenshot_capture(
     window: tauri::Window,
     scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
     scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
     scroll_image_list: ScrollImageList,
     min_x: i32,
     min_y: i32,
     max_x: i32,
     max_y: i32,
-) -> Result<(), String> {
+) -> Result<ScrollImagePushResult, String> {
     snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_capture(
         window,
         scroll_screenshot_image_service,
         scroll_screenshot_capture_service,
+        scroll_screenshot_worker_service,
         scroll_image_list,
         min_x,
         min_y,
@@ -59,6 +100,79 @@ pub async fn scroll_screenshot_capture(
     .await
 }
 
+/**
+ * 自动滚动并截图，直到到达内容末尾或满足停止条件
+ */
+#[command]
+pub async fn scroll_screenshot_auto_scroll(
+    window: tauri::Window,
+    enigo_manager: tauri::State<'_, Mutex<EnigoManager>>,
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+    scroll_auto_scroll_service: tauri::State<'_, Mutex<ScrollAutoScrollService>>,
+    scroll_image_list: ScrollImageList,
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+    options: ScrollAutoScrollOptions,
+) -> Result<ScrollAutoScrollProgress, String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_auto_scroll(
+        window,
+        enigo_manager,
+        scroll_screenshot_service,
+        scroll_screenshot_capture_service,
+        scroll_screenshot_image_service,
+        scroll_screenshot_worker_service,
+        scroll_auto_scroll_service,
+        scroll_image_list,
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+        options,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_detect_area(
+    window: tauri::Window,
+    enigo_manager: tauri::State<'_, Mutex<EnigoManager>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+    scroll_length: i32,
+    settle_delay: u64,
+) -> Result<Option<ScrollAreaDetection>, String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_detect_area(
+        window,
+        enigo_manager,
+        scroll_screenshot_capture_service,
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+        scroll_length,
+        settle_delay,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_stop_auto_scroll(
+    scroll_auto_scroll_service: tauri::State<'_, Mutex<ScrollAutoScrollService>>,
+) -> Result<(), ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_stop_auto_scroll(
+        scroll_auto_scroll_service,
+    )
+    .await
+}
+
 /**
  * 处理目前截取到的所有图片
  */
@@ -67,7 +181,7 @@ pub async fn scroll_screenshot_handle_image(
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
     scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
     thumbnail_size: u32,
-) -> Result<Response, ()> {
+) -> Result<Response, String> {
     snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_handle_image(
         scroll_screenshot_service,
         scroll_screenshot_image_service,
@@ -76,6 +190,33 @@ pub async fn scroll_screenshot_handle_image(
     .await
 }
 
+/**
+ * 启动后台拼接线程，处理结果通过事件发送
+ */
+#[command]
+pub async fn scroll_screenshot_start_worker(
+    window: tauri::Window,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+    thumbnail_size: u32,
+) -> Result<(), String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_start_worker(
+        window,
+        scroll_screenshot_worker_service,
+        thumbnail_size,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_stop_worker(
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
+) -> Result<(), ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_stop_worker(
+        scroll_screenshot_worker_service,
+    )
+    .await
+}
+
 #[command]
 pub async fn scroll_screenshot_get_size(
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
@@ -98,24 +239,58 @@ pub async fn scroll_screenshot_save_to_file(
     .await
 }
 
+#[command]
+pub async fn scroll_screenshot_export_to_file(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    file_path: String,
+    export_format: ScrollExportFormat,
+    max_page_size: u32,
+) -> Result<Vec<String>, String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_export_to_file(
+        scroll_screenshot_service,
+        file_path,
+        export_format,
+        max_page_size,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_export_video(
+    app: tauri::AppHandle,
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    video_record_service: tauri::State<'_, Mutex<VideoRecordService>>,
+    file_path: String,
+    format: ScrollVideoFormat,
+    options: ScrollVideoOptions,
+) -> Result<(), String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_export_video(
+        app,
+        scroll_screenshot_service,
+        video_record_service,
+        file_path,
+        format,
+        options,
+    )
+    .await
+}
+
 #[command]
 pub async fn scroll_screenshot_save_to_clipboard(
     app: tauri::AppHandle,
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
 ) -> Result<(), String> {
     snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_save_to_clipboard(
-        |image| {
-            match app.clipboard().write_image(&tauri::image::Image::new(
-                image.to_rgba8().as_raw(),
-                image.width(),
-                image.height(),
-            )) {
-                Ok(_) => Ok(()),
-                Err(e) => Err(format!(
-                    "[scroll_screenshot_save_to_clipboard] Failed to write image to clipboard: {}",
-                    e
-                )),
-            }
+        |image| match app.clipboard().write_image(&tauri::image::Image::new(
+            image.to_rgba8().as_raw(),
+            image.width(),
+            image.height(),
+        )) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!(
+                "[scroll_screenshot_save_to_clipboard] Failed to write image to clipboard: {}",
+                e
+            )),
         },
         scroll_screenshot_service,
     )
@@ -127,11 +302,47 @@ pub async fn scroll_screenshot_clear(
     scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
     scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
     scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+    scroll_screenshot_worker_service: tauri::State<'_, Mutex<ScrollScreenshotWorkerService>>,
 ) -> Result<(), ()> {
     snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_clear(
         scroll_screenshot_service,
         scroll_screenshot_image_service,
         scroll_screenshot_capture_service,
+        scroll_screenshot_worker_service,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_undo(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+) -> Result<snow_shot_tauri_commands_scroll_screenshot::ScrollScreenshotCaptureSize, String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_undo(scroll_screenshot_service)
+        .await
+}
+
+#[command]
+pub async fn scroll_screenshot_save_session(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    directory: String,
+) -> Result<(), String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_save_session(
+        scroll_screenshot_service,
+        directory,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_restore_session(
+    scroll_screenshot_service: tauri::State<'_, Mutex<ScrollScreenshotService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    directory: String,
+) -> Result<snow_shot_tauri_commands_scroll_screenshot::ScrollScreenshotCaptureSize, String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_restore_session(
+        scroll_screenshot_service,
+        scroll_screenshot_image_service,
+        directory,
     )
     .await
 }
@@ -145,3 +356,67 @@ pub async fn scroll_screenshot_get_image_data(
     )
     .await
 }
+
+#[command]
+pub async fn scroll_screenshot_panorama_init(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    sample_rate: f32,
+    min_sample_size: u32,
+    max_sample_size: u32,
+    corner_threshold: u8,
+    descriptor_patch_size: usize,
+    tile_size: u32,
+    frame_memory_budget: usize,
+) -> Result<(), ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_panorama_init(
+        scroll_panorama_service,
+        sample_rate,
+        min_sample_size,
+        max_sample_size,
+        corner_threshold,
+        descriptor_patch_size,
+        tile_size,
+        frame_memory_budget,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_panorama_handle_image(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+) -> Result<snow_shot_tauri_commands_scroll_screenshot::ScrollPanoramaHandleResult, ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_panorama_handle_image(
+        scroll_panorama_service,
+        scroll_screenshot_image_service,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_panorama_save_to_file(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    file_path: String,
+    background_color: Option<[u8; 4]>,
+) -> Result<(), String> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_panorama_save_to_file(
+        scroll_panorama_service,
+        file_path,
+        background_color,
+    )
+    .await
+}
+
+#[command]
+pub async fn scroll_screenshot_panorama_clear(
+    scroll_panorama_service: tauri::State<'_, Mutex<ScrollPanoramaService>>,
+    scroll_screenshot_image_service: tauri::State<'_, Mutex<ScrollScreenshotImageService>>,
+    scroll_screenshot_capture_service: tauri::State<'_, Mutex<ScrollScreenshotCaptureService>>,
+) -> Result<(), ()> {
+    snow_shot_tauri_commands_scroll_screenshot::scroll_screenshot_panorama_clear(
+        scroll_panorama_service,
+        scroll_screenshot_image_service,
+        scroll_screenshot_capture_service,
+    )
+    .await
+}
@@ -33,6 +33,7 @@ import { join as joinPath } from '@tauri-apps/api/path';
 import { DrawState } from './fullScreenDraw/components/drawCore/extra';
 import { OcrDetectAfterAction } from './fixedContent/components/ocrResult';
 import { OcrModel } from '@/commands/ocr';
+import { ScrollMatcherType, ScrollSeamMode } from '@/commands/scrollScreenshot';
 import { HistoryValidDuration } from '@/utils/captureHistory';
 import { getPlatformValue } from '@/utils';
 import { VideoMaxSize } from '@/commands/videoRecord';
@@ -258,6 +259,13 @@ export type AppSettingsData = {
         sampleRate: number;
         imageFeatureDescriptionLength: number;
         imageFeatureThreshold: number;
+        crossAxisTolerance: number;
+        seamMode: ScrollSeamMode;
+        matcherType: ScrollMatcherType;
+        /** 已拼接图片占用的内存上限（MB），为 0 时不限制 */
+        frameMemoryBudget: number;
+        /** 是否裁剪滚动条等与滚动不同步的贴边区域 */
+        trimScrollbar: boolean;
     };
     [AppSettingsGroup.FunctionTrayIcon]: {
         /** 托盘点击后 */
@@ -392,6 +400,11 @@ export const defaultAppSettingsData: AppSettingsData = {
         maxSide: 128,
         sampleRate: 1,
         imageFeatureDescriptionLength: 28,
+        crossAxisTolerance: 4,
+        seamMode: ScrollSeamMode.Overwrite,
+        matcherType: ScrollMatcherType.Corner,
+        frameMemoryBudget: 1024,
+        trimScrollbar: true,
     },
     [AppSettingsGroup.FunctionFixedContent]: {
         zoomWithMouse: true,
@@ -1171,6 +1184,30 @@ const ContextWrapCore: React.FC<{ children: React.ReactNode }> = ({ children })
                             ? newSettings.tryRollback
                             : (prevSettings?.tryRollback ??
                               defaultAppSettingsData[group].tryRollback),
+                    crossAxisTolerance:
+                        typeof newSettings?.crossAxisTolerance === 'number'
+                            ? Math.min(Math.max(newSettings.crossAxisTolerance, 0), 32)
+                            : (prevSettings?.crossAxisTolerance ??
+                              defaultAppSettingsData[group].crossAxisTolerance),
+                    seamMode:
+                        typeof newSettings?.seamMode === 'string'
+                            ? (newSettings.seamMode as ScrollSeamMode)
+                            : (prevSettings?.seamMode ?? defaultAppSettingsData[group].seamMode),
+                    matcherType:
+                        typeof newSettings?.matcherType === 'string'
+                            ? (newSettings.matcherType as ScrollMatcherType)
+                            : (prevSettings?.matcherType ??
+                              defaultAppSettingsData[group].matcherType),
+                    frameMemoryBudget:
+                        typeof newSettings?.frameMemoryBudget === 'number'
+                            ? Math.min(Math.max(newSettings.frameMemoryBudget, 0), 4096)
+                            : (prevSettings?.frameMemoryBudget ??
+                              defaultAppSettingsData[group].frameMemoryBudget),
+                    trimScrollbar:
+                        typeof newSettings?.trimScrollbar === 'boolean'
+                            ? newSettings.trimScrollbar
+                            : (prevSettings?.trimScrollbar ??
+                              defaultAppSettingsData[group].trimScrollbar),
                 };
             } else if (group === AppSettingsGroup.FunctionTrayIcon) {
                 newSettings = newSettings as AppSettingsData[typeof group];
@@ -3,14 +3,18 @@ import { DrawContext } from '@/app/draw/types';
 import { ElementRect } from '@/commands';
 import { clickThrough, scrollThrough } from '@/commands/core';
 import {
-    SCROLL_SCREENSHOT_CAPTURE_RESULT_EXTRA_DATA_SIZE,
+    parseScrollScreenshotHandleImageEvent,
     ScrollImageList,
+    ScrollImagePushResult,
     ScrollDirection,
+    SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY,
     scrollScreenshotCapture,
     ScrollScreenshotCaptureResult,
     scrollScreenshotClear,
-    scrollScreenshotHandleImage,
+    ScrollScreenshotHandleImageEvent,
+    ScrollScreenshotHandleImageStatus,
     scrollScreenshotInit,
+    scrollScreenshotStartWorker,
 } from '@/commands/scrollScreenshot';
 import { useStateRef } from '@/hooks/useStateRef';
 import { useStateSubscriber } from '@/hooks/useStateSubscriber';
@@ -37,6 +41,7 @@ import { DrawStatePublisher } from '@/app/fullScreenDraw/components/drawCore/ext
 import { MessageType } from 'antd/es/message/interface';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { appError } from '@/utils/log';
+import { EventListenerContext } from '@/components/eventListener';
 
 const THUMBNAIL_WIDTH = 128;
 
@@ -53,6 +58,7 @@ export const ScrollScreenshot: React.FC<{
     actionRef: React.RefObject<ScrollScreenshotActionType | undefined>;
 }> = ({ actionRef }) => {
     const { message } = useContext(AntdContext);
+    const { addListener, removeListener } = useContext(EventListenerContext);
     const intl = useIntl();
     const { token } = theme.useToken();
 
@@ -111,16 +117,16 @@ export const ScrollScreenshot: React.FC<{
 
     const updateImageUrlList = useCallback(
         (captureResult: ScrollScreenshotCaptureResult) => {
-            if (captureResult.thumbnail_buffer === undefined) {
+            if (captureResult.edge_position === null) {
                 return;
             }
 
             const currentScrollSize = {
-                top_image_size: captureResult.top_image_size!,
-                bottom_image_size: captureResult.bottom_image_size!,
+                top_image_size: captureResult.top_image_size,
+                bottom_image_size: captureResult.bottom_image_size,
             };
 
-            const edgePosition = captureResult.edge_position!;
+            const edgePosition = captureResult.edge_position;
 
             let positionScale: number;
             if (scrollDirectionRef.current === ScrollDirection.Horizontal) {
@@ -150,17 +156,14 @@ export const ScrollScreenshot: React.FC<{
 
             setCaptuerEdgePosition(captuerEdge);
 
-            if (
-                captureResult.thumbnail_buffer.byteLength <=
-                SCROLL_SCREENSHOT_CAPTURE_RESULT_EXTRA_DATA_SIZE
-            ) {
+            if (captureResult.thumbnail_buffer === undefined) {
                 scrollTo(Math.max(captuerEdge, 0));
                 return;
             }
 
             const blobUrl = URL.createObjectURL(new Blob([captureResult.thumbnail_buffer]));
 
-            const overlaySize = captureResult.overlay_size!;
+            const overlaySize = captureResult.overlay_size ?? 0;
             if (captureResult.current_direction === ScrollImageList.Top) {
                 setTopImageUrlList((prev) => [{ url: blobUrl, overlaySize }, ...prev]);
                 setTimeout(() => {
@@ -199,69 +202,50 @@ export const ScrollScreenshot: React.FC<{
         );
     }, [intl, message]);
 
-    /**
-     * @returns 是否需要继续处理
-     */
-    const handleCaptureImage = useCallback(async (): Promise<boolean> => {
-        setLoading(true);
-        let captureResult: ScrollScreenshotCaptureResult;
-
-        let needContinue = false;
-
-        try {
-            captureResult = await scrollScreenshotHandleImage(
-                Math.round(THUMBNAIL_WIDTH * window.devicePixelRatio),
-            );
-        } catch (error) {
-            appError('[handleCaptureImage] scrollScreenshotHandleImage error', error);
-            message.error(intl.formatMessage({ id: 'draw.scrollScreenshot.captureError' }));
-            return needContinue;
-        }
-
-        needContinue = captureResult.type !== 'no_image';
-
-        setLoading(false);
-
-        if (captureResult.type === 'no_image') {
-            return needContinue;
-        } else if (
-            captureResult.edge_position === 0 &&
-            captureResult.thumbnail_buffer === undefined
-        ) {
-            return needContinue;
-        } else if (captureResult.edge_position === undefined) {
-            showCaptureMissMessage();
-            return needContinue;
-        }
-
-        updateImageUrlList(captureResult);
+    const handleCaptureResult = useCallback(
+        (captureResult: ScrollScreenshotCaptureResult) => {
+            // 截图队列处理完后结束加载
+            if (!captureResult.has_pending_image) {
+                setLoading(false);
+            }
 
-        return needContinue;
-    }, [setLoading, updateImageUrlList, message, intl, showCaptureMissMessage]);
+            if (captureResult.status === ScrollScreenshotHandleImageStatus.NoImage) {
+                return;
+            } else if (captureResult.status === ScrollScreenshotHandleImageStatus.NotMatched) {
+                // 还有剩余图片时不提示用户未识别到
+                if (!captureResult.has_pending_image) {
+                    showCaptureMissMessage();
+                }
+                return;
+            }
 
-    const pendingCaptureImageListRef = useRef<boolean>(false);
-    const handleCaptureImageList = useCallback(async () => {
-        if (pendingCaptureImageListRef.current) {
-            return;
-        }
-        pendingCaptureImageListRef.current = true;
+            updateImageUrlList(captureResult);
+        },
+        [setLoading, updateImageUrlList, showCaptureMissMessage],
+    );
 
-        let needContinue = true;
-        while (needContinue) {
+    useEffect(() => {
+        // 截图由后台拼接线程处理，处理结果通过事件发送
+        const listenerId = addListener(SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY, (args) => {
+            let captureResult: ScrollScreenshotCaptureResult;
             try {
-                needContinue = await handleCaptureImage();
+                captureResult = parseScrollScreenshotHandleImageEvent(
+                    (args as { payload: ScrollScreenshotHandleImageEvent }).payload,
+                );
             } catch (error) {
-                appError('[handleCaptureImageList] error', error);
-                break;
+                appError('[ScrollScreenshot] parseScrollScreenshotHandleImageEvent error', error);
+                setLoading(false);
+                message.error(intl.formatMessage({ id: 'draw.scrollScreenshot.captureError' }));
+                return;
             }
-        }
 
-        pendingCaptureImageListRef.current = false;
-    }, [handleCaptureImage]);
+            handleCaptureResult(captureResult);
+        });
 
-    const handleCaptureImageListDebounce = useMemo(() => {
-        return debounce(handleCaptureImageList, 128);
-    }, [handleCaptureImageList]);
+        return () => {
+            removeListener(listenerId);
+        };
+    }, [addListener, removeListener, handleCaptureResult, message, intl]);
 
     const pendingCaptureRef = useRef<boolean>(false);
     const captureImageCore = useCallback(
@@ -285,24 +269,30 @@ export const ScrollScreenshot: React.FC<{
 
             pendingCaptureRef.current = true;
 
-            await scrollScreenshotCapture(
-                scrollImageList,
-                rect.min_x,
-                rect.min_y,
-                rect.max_x,
-                rect.max_y,
-            );
+            // 在加入队列前开始加载，避免处理结果先于命令返回时加载状态无法结束
+            setLoading(true);
+
+            let pushResult: ScrollImagePushResult | undefined;
+            try {
+                pushResult = await scrollScreenshotCapture(
+                    scrollImageList,
+                    rect.min_x,
+                    rect.min_y,
+                    rect.max_x,
+                    rect.max_y,
+                );
+            } catch (error) {
+                appError('[captureImageCore] scrollScreenshotCapture error', error);
+            }
 
             pendingCaptureRef.current = false;
 
-            handleCaptureImageListDebounce();
+            // 丢弃的截图不会触发处理结果
+            if (pushResult !== ScrollImagePushResult.Queued) {
+                setLoading(false);
+            }
         },
-        [
-            captureBoundingBoxInfoRef,
-            selectLayerActionRef,
-            setDrawEvent,
-            handleCaptureImageListDebounce,
-        ],
+        [captureBoundingBoxInfoRef, selectLayerActionRef, setDrawEvent, setLoading],
     );
 
     const captureImageDebounce = useMemo(() => {
@@ -346,6 +336,14 @@ export const ScrollScreenshot: React.FC<{
                         ? Math.ceil((rect.max_x - rect.min_x) * 0.8)
                         : Math.ceil((rect.max_y - rect.min_y) * 0.8),
                     scrollSettings.tryRollback,
+                    scrollSettings.crossAxisTolerance,
+                    scrollSettings.seamMode,
+                    scrollSettings.matcherType,
+                    scrollSettings.frameMemoryBudget,
+                    scrollSettings.trimScrollbar,
+                );
+                await scrollScreenshotStartWorker(
+                    Math.round(THUMBNAIL_WIDTH * window.devicePixelRatio),
                 );
             } catch (error) {
                 appError('[init] scrollScreenshotInit error', error);
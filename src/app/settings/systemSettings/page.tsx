@@ -33,6 +33,7 @@ import { AntdContext } from '@/components/globalLayoutExtra';
 import { clearAllAppStore } from '@/utils/appStore';
 import { relaunch } from '@tauri-apps/plugin-process';
 import { OcrModel } from '@/commands/ocr';
+import { ScrollMatcherType, ScrollSeamMode } from '@/commands/scrollScreenshot';
 import { CaptureHistory, HistoryValidDuration } from '@/utils/captureHistory';
 import { usePlatform } from '@/hooks/usePlatform';
 import { MacOSPermissionsSettings } from './components/macosPermissionsSettings';
@@ -180,6 +181,52 @@ export default function SystemSettings() {
         ];
     }, [intl]);
 
+    const seamModeOptions = useMemo(() => {
+        return [
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.seamMode.overwrite',
+                }),
+                value: ScrollSeamMode.Overwrite,
+            },
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.seamMode.feather',
+                }),
+                value: ScrollSeamMode.Feather,
+            },
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.seamMode.minDifference',
+                }),
+                value: ScrollSeamMode.MinDifference,
+            },
+        ];
+    }, [intl]);
+
+    const matcherTypeOptions = useMemo(() => {
+        return [
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.matcherType.corner',
+                }),
+                value: ScrollMatcherType.Corner,
+            },
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.matcherType.phaseCorrelation',
+                }),
+                value: ScrollMatcherType.PhaseCorrelation,
+            },
+            {
+                label: intl.formatMessage({
+                    id: 'settings.systemSettings.scrollScreenshotSettings.matcherType.rowHash',
+                }),
+                value: ScrollMatcherType.RowHash,
+            },
+        ];
+    }, [intl]);
+
     const [currentPlatform] = usePlatform();
 
     return (
@@ -463,6 +510,41 @@ export default function SystemSettings() {
                                 name="tryRollback"
                             />
                         </Col>
+                        <Col span={12}>
+                            <ProFormSelect
+                                label={
+                                    <IconLabel
+                                        label={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.seamMode" />
+                                        }
+                                        tooltipTitle={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.seamMode.tip" />
+                                        }
+                                    />
+                                }
+                                name="seamMode"
+                                options={seamModeOptions}
+                            />
+                        </Col>
+                    </Row>
+
+                    <Row gutter={token.margin}>
+                        <Col span={12}>
+                            <ProFormSelect
+                                label={
+                                    <IconLabel
+                                        label={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.matcherType" />
+                                        }
+                                        tooltipTitle={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.matcherType.tip" />
+                                        }
+                                    />
+                                }
+                                name="matcherType"
+                                options={matcherTypeOptions}
+                            />
+                        </Col>
                     </Row>
 
                     <Row gutter={token.margin}>
@@ -581,6 +663,70 @@ export default function SystemSettings() {
                                 layout="vertical"
                             />
                         </Col>
+                        <Col span={12}>
+                            <ProFormSlider
+                                label={
+                                    <IconLabel
+                                        label={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.crossAxisTolerance" />
+                                        }
+                                        tooltipTitle={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.crossAxisTolerance.tip" />
+                                        }
+                                    />
+                                }
+                                name="crossAxisTolerance"
+                                min={0}
+                                max={32}
+                                step={1}
+                                marks={{
+                                    0: '0',
+                                    32: '32',
+                                }}
+                                layout="vertical"
+                            />
+                        </Col>
+                    </Row>
+
+                    <Row gutter={token.margin}>
+                        <Col span={12}>
+                            <ProFormSlider
+                                label={
+                                    <IconLabel
+                                        label={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.frameMemoryBudget" />
+                                        }
+                                        tooltipTitle={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.frameMemoryBudget.tip" />
+                                        }
+                                    />
+                                }
+                                name="frameMemoryBudget"
+                                min={0}
+                                max={4096}
+                                step={64}
+                                marks={{
+                                    0: '0',
+                                    4096: '4096',
+                                }}
+                                layout="vertical"
+                            />
+                        </Col>
+                        <Col span={12}>
+                            <ProFormSwitch
+                                label={
+                                    <IconLabel
+                                        label={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.trimScrollbar" />
+                                        }
+                                        tooltipTitle={
+                                            <FormattedMessage id="settings.systemSettings.scrollScreenshotSettings.trimScrollbar.tip" />
+                                        }
+                                    />
+                                }
+                                name="trimScrollbar"
+                            />
+                        </Col>
                     </Row>
                 </ProForm>
             </Spin>
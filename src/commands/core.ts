@@ -73,10 +73,52 @@ export const startFreeDrag = async () => {
     return result;
 };
 
-export const sendNewVersionNotification = async (title: string, body: string) => {
+/**
+ * @param actionLabel 通知按钮文字，Windows 下通知不带按钮
+ */
+export const sendNewVersionNotification = async (
+    title: string,
+    body: string,
+    actionLabel: string,
+) => {
     const result = await invoke<void>('send_new_version_notification', {
         title,
         body,
+        actionLabel,
+    });
+    return result;
+};
+
+export const NOTIFICATION_ACTION_INVOKED_EMIT_KEY = 'notification:action-invoked';
+
+export type NotificationAction = {
+    /** 按钮 ID，点击后随事件返回 */
+    id: string;
+    /** 按钮文字 */
+    label: string;
+};
+
+export type NotificationActionInvokedEvent = {
+    notification_id: number;
+    action_id: string;
+};
+
+/**
+ * 发送系统通知，点击按钮后通过 NOTIFICATION_ACTION_INVOKED_EMIT_KEY 事件返回按钮 ID
+ * @param imagePath 通知中显示的图片，如截图文件
+ * @returns 通知 ID
+ */
+export const sendNotification = async (
+    title: string,
+    body: string,
+    imagePath?: string,
+    actionList: NotificationAction[] = [],
+) => {
+    const result = await invoke<number>('send_notification', {
+        title,
+        body,
+        imagePath,
+        actionList,
     });
     return result;
 };
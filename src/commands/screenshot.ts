@@ -1,8 +1,12 @@
 import { invoke } from '@tauri-apps/api/core';
 import { ImageBuffer, ImageEncoder } from '.';
 
+/**
+ * 切换窗口置顶状态
+ * @returns 切换后窗口是否置顶
+ */
 export const switchAlwaysOnTop = async (windowId: number) => {
-    const result = await invoke<string>('switch_always_on_top', {
+    const result = await invoke<boolean>('switch_always_on_top', {
         windowId,
     });
     return result;
@@ -1,5 +1,6 @@
 import { appError } from '@/utils/log';
 import { invoke } from '@tauri-apps/api/core';
+import { ElementRect } from '.';
 
 export enum ScrollDirection {
     /// 垂直滚动
@@ -15,6 +16,24 @@ export enum ScrollImageList {
     Bottom = 'Bottom',
 }
 
+export enum ScrollSeamMode {
+    /// 直接覆盖重叠区域
+    Overwrite = 'Overwrite',
+    /// 在重叠区域内渐变过渡
+    Feather = 'Feather',
+    /// 在重叠区域内选取差异最小的一行（列）作为接缝
+    MinDifference = 'MinDifference',
+}
+
+export enum ScrollMatcherType {
+    /// 特征点匹配，适合内容丰富的页面
+    Corner = 'Corner',
+    /// 相位相关，适合特征点较少的页面
+    PhaseCorrelation = 'PhaseCorrelation',
+    /// 逐行（列）哈希对齐，适合大面积留白或纯色的页面
+    RowHash = 'RowHash',
+}
+
 export const scrollScreenshotInit = async (
     direction: ScrollDirection,
     imageWidth: number,
@@ -26,6 +45,11 @@ export const scrollScreenshotInit = async (
     descriptorPatchSize: number,
     minSizeDelta: number,
     tryRollback: boolean,
+    crossAxisTolerance: number,
+    seamMode: ScrollSeamMode,
+    matcherType: ScrollMatcherType,
+    frameMemoryBudget: number,
+    trimScrollbar: boolean,
 ) => {
     const result = await invoke('scroll_screenshot_init', {
         direction,
@@ -38,21 +62,179 @@ export const scrollScreenshotInit = async (
         descriptorPatchSize,
         minSizeDelta,
         tryRollback,
+        crossAxisTolerance,
+        seamMode,
+        matcherType,
+        frameMemoryBudget,
+        trimScrollbar,
+    });
+    return result;
+};
+
+/**
+ * 设置排除区域，坐标相对截取到的图片，区域内的内容不参与匹配
+ * 需要在 scrollScreenshotInit 之后调用
+ */
+export const scrollScreenshotSetExclusionRects = async (exclusionRectList: ElementRect[]) => {
+    const result = await invoke('scroll_screenshot_set_exclusion_rects', {
+        exclusionRectList,
+    });
+    return result;
+};
+
+export type ScrollAutoScrollOptions = {
+    /** 每次发送的滚轮格数 */
+    scroll_length: number;
+    /** 滚动后等待页面稳定的时间（毫秒） */
+    settle_delay: number;
+    /** 连续多少帧没有变化时认为已经到达内容末尾 */
+    max_unchanged_count: number;
+    /** 连续多少帧没有匹配时停止 */
+    max_not_matched_count: number;
+    /** 拼接结果在滚动方向上的最大尺寸，为 0 时不限制 */
+    max_size: number;
+    /** 最长运行时间（毫秒），为 0 时不限制 */
+    max_duration: number;
+};
+
+export enum ScrollAutoScrollStopReason {
+    /// 连续多帧没有变化，已经滚动到内容末尾
+    EndOfContent = 'EndOfContent',
+    /// 连续多帧没有匹配
+    NotMatched = 'NotMatched',
+    /// 达到最大尺寸
+    MaxSize = 'MaxSize',
+    /// 达到最长运行时间
+    MaxDuration = 'MaxDuration',
+    /// 被用户停止
+    Stopped = 'Stopped',
+}
+
+export type ScrollAutoScrollProgress = {
+    step_count: number;
+    frame_status: 'Appended' | 'Unchanged' | 'NotMatched';
+    unchanged_count: number;
+    /** 已运行的时间（毫秒） */
+    elapsed: number;
+    top_image_size: number;
+    bottom_image_size: number;
+    /** 停止原因，仍在运行时为空 */
+    stop_reason: ScrollAutoScrollStopReason | null;
+};
+
+/** 自动滚动截图的进度事件，payload 为 ScrollAutoScrollProgress */
+export const SCROLL_SCREENSHOT_AUTO_SCROLL_PROGRESS_EMIT_KEY =
+    'scroll-screenshot:auto-scroll-progress';
+
+/**
+ * 自动滚动并截图，直到到达内容末尾或满足停止条件
+ * 截图由后台拼接线程处理，需要先调用 scrollScreenshotStartWorker
+ * @returns 最后一帧的进度
+ */
+export const scrollScreenshotAutoScroll = async (
+    scrollImageList: ScrollImageList,
+    minX: number,
+    minY: number,
+    maxX: number,
+    maxY: number,
+    options: ScrollAutoScrollOptions,
+) => {
+    const result = await invoke<ScrollAutoScrollProgress>('scroll_screenshot_auto_scroll', {
+        scrollImageList,
+        minX,
+        minY,
+        maxX,
+        maxY,
+        options,
+    });
+    return result;
+};
+
+export const scrollScreenshotStopAutoScroll = async () => {
+    const result = await invoke('scroll_screenshot_stop_auto_scroll');
+    return result;
+};
+
+export type ScrollAreaDetection = {
+    /** 滚动区域，与传入的选区使用相同的坐标 */
+    rect: ElementRect;
+    direction: ScrollDirection;
+    /** 滚动后内容在滚动方向上移动的像素，向前滚动时为正 */
+    offset: number;
+};
+
+/**
+ * 在滚动前后截取选区，检测实际滚动的区域和方向，检测后滚动回原位置
+ * @param scrollLength 检测时发送的滚轮格数
+ * @param settleDelay 滚动后等待页面稳定的时间（毫秒）
+ */
+export const scrollScreenshotDetectArea = async (
+    minX: number,
+    minY: number,
+    maxX: number,
+    maxY: number,
+    scrollLength: number,
+    settleDelay: number,
+) => {
+    const result = await invoke<ScrollAreaDetection | null>('scroll_screenshot_detect_area', {
+        minX,
+        minY,
+        maxX,
+        maxY,
+        scrollLength,
+        settleDelay,
     });
     return result;
 };
 
-export type ScrollScreenshotCaptureResult = {
-    type: 'no_data' | 'no_change' | 'success' | 'no_image';
+/** scroll_screenshot_handle_image 响应格式版本，需与后端保持一致 */
+export const SCROLL_SCREENSHOT_HANDLE_IMAGE_RESULT_VERSION = 1;
+
+export enum ScrollScreenshotHandleImageStatus {
+    /// 截图队列中没有图片
+    NoImage = 'NoImage',
+    /// 图片没有带来新的区域
+    Unchanged = 'Unchanged',
+    /// 没有匹配到已有内容
+    NotMatched = 'NotMatched',
+    /// 图片已追加到图片列表
+    Appended = 'Appended',
+}
+
+export type ScrollScreenshotHandleImageResult = {
+    version: number;
+    status: ScrollScreenshotHandleImageStatus;
+    /** 截图队列中是否还有待处理的图片 */
+    has_pending_image: boolean;
+    /** 匹配置信度，首帧或未匹配时为空 */
+    confidence: number | null;
+    /** 支持匹配偏移的特征数量，首帧或未匹配时为空 */
+    inlier_count: number | null;
+    /** 图片边缘相对首帧的位置（方向边），匹配成功时存在 */
+    edge_position: number | null;
+    /** 缩略图中与已有图片重叠的尺寸，追加图片时存在 */
+    overlay_size: number | null;
+    top_image_size: number;
+    bottom_image_size: number;
+    /** 图片匹配到的列表 */
+    current_direction: ScrollImageList;
+    /** 缩略图字节数 */
+    thumbnail_size: number;
+};
+
+export type ScrollScreenshotCaptureResult = ScrollScreenshotHandleImageResult & {
+    /** 缩略图 PNG，仅追加图片时存在 */
     thumbnail_buffer: ArrayBuffer | undefined;
-    edge_position: number | undefined;
-    overlay_size: number | undefined;
-    top_image_size?: number | undefined;
-    bottom_image_size?: number | undefined;
-    current_direction?: ScrollImageList | undefined;
 };
 
-export const SCROLL_SCREENSHOT_CAPTURE_RESULT_EXTRA_DATA_SIZE = 4 + 4 + 4 + 4 + 4;
+export enum ScrollImagePushResult {
+    /// 已加入队列
+    Queued = 'Queued',
+    /// 处理不及时，且和队尾图片几乎相同，已丢弃
+    DroppedDuplicate = 'DroppedDuplicate',
+    /// 队列已满，已丢弃
+    DroppedQueueFull = 'DroppedQueueFull',
+}
 
 export const scrollScreenshotCapture = async (
     scrollImageList: ScrollImageList,
@@ -61,7 +243,7 @@ export const scrollScreenshotCapture = async (
     maxX: number,
     maxY: number,
 ) => {
-    const result = await invoke<ArrayBuffer>('scroll_screenshot_capture', {
+    const result = await invoke<ScrollImagePushResult>('scroll_screenshot_capture', {
         scrollImageList,
         minX,
         minY,
@@ -73,83 +255,76 @@ export const scrollScreenshotCapture = async (
 };
 
 /**
- * @returns JPG 的 buffer 数据
+ * 响应依次为：结果长度（u32 小端）、结果（UTF-8 JSON）、缩略图（PNG，仅追加图片时存在）
+ * @throws 调用失败或结果版本不受支持时抛出错误，由调用方处理
  */
 export const scrollScreenshotHandleImage = async (
     thumbnailSize: number,
 ): Promise<ScrollScreenshotCaptureResult> => {
-    let result: ArrayBuffer | undefined;
-    try {
-        result = await invoke<ArrayBuffer>('scroll_screenshot_handle_image', {
-            thumbnailSize,
-        });
-    } catch (error) {
-        appError('[scrollScreenshotHandleImage] error', error);
-        result = new ArrayBuffer();
-    }
+    const result = await invoke<ArrayBuffer>('scroll_screenshot_handle_image', {
+        thumbnailSize,
+    });
 
-    if (result.byteLength === 0) {
-        return {
-            type: 'no_data',
-            thumbnail_buffer: result,
-            edge_position: undefined,
-            overlay_size: undefined,
-        };
-    }
+    const resultLength = new DataView(result, 0, 4).getUint32(0, true);
+    const handleImageResult = JSON.parse(
+        new TextDecoder().decode(new Uint8Array(result, 4, resultLength)),
+    ) as ScrollScreenshotHandleImageResult;
 
-    if (result.byteLength === 1) {
-        const array = new Uint8Array(result);
-        if (array[0] === 1) {
-            return {
-                type: 'no_change',
-                thumbnail_buffer: undefined,
-                edge_position: 0,
-                overlay_size: undefined,
-            };
-        } else if (array[0] === 2) {
-            return {
-                type: 'no_image',
-                thumbnail_buffer: undefined,
-                edge_position: 0,
-                overlay_size: undefined,
-            };
-        } else {
-            return {
-                type: 'no_data',
-                thumbnail_buffer: new ArrayBuffer(),
-                edge_position: undefined,
-                overlay_size: undefined,
-            };
-        }
+    if (handleImageResult.version !== SCROLL_SCREENSHOT_HANDLE_IMAGE_RESULT_VERSION) {
+        throw new Error(
+            `[scrollScreenshotHandleImage] unsupported result version: ${handleImageResult.version}`,
+        );
     }
 
-    // 将屏幕信息和图像数据分离
-    const imageDataLength = result.byteLength - SCROLL_SCREENSHOT_CAPTURE_RESULT_EXTRA_DATA_SIZE;
+    return {
+        ...handleImageResult,
+        thumbnail_buffer:
+            handleImageResult.thumbnail_size > 0 ? result.slice(4 + resultLength) : undefined,
+    };
+};
+
+/** 后台拼接线程处理完一张截图后发送的事件 */
+export const SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY = 'scroll-screenshot:handle-image';
 
-    // 提取屏幕信息
-    const screenInfoView = new DataView(
-        result,
-        imageDataLength,
-        SCROLL_SCREENSHOT_CAPTURE_RESULT_EXTRA_DATA_SIZE,
-    );
-    const edgePosition = screenInfoView.getInt32(0, true); // i32
-    const overlaySize = screenInfoView.getInt32(4, true); // i32
-    const topImageSize = screenInfoView.getInt32(8, true); // i32
-    const bottomImageSize = screenInfoView.getInt32(12, true); // i32
-    const currentDirection =
-        screenInfoView.getInt32(16, true) === 0 ? ScrollImageList.Top : ScrollImageList.Bottom; // i32
+export type ScrollScreenshotHandleImageEvent = ScrollScreenshotHandleImageResult & {
+    /** 缩略图 PNG 的 base64 编码，仅追加图片时存在 */
+    thumbnail: string | null;
+};
+
+export const parseScrollScreenshotHandleImageEvent = (
+    payload: ScrollScreenshotHandleImageEvent,
+): ScrollScreenshotCaptureResult => {
+    if (payload.version !== SCROLL_SCREENSHOT_HANDLE_IMAGE_RESULT_VERSION) {
+        throw new Error(
+            `[parseScrollScreenshotHandleImageEvent] unsupported result version: ${payload.version}`,
+        );
+    }
+
+    const { thumbnail, ...handleImageResult } = payload;
 
     return {
-        type: 'success',
-        thumbnail_buffer: result,
-        edge_position: edgePosition,
-        overlay_size: overlaySize,
-        top_image_size: topImageSize,
-        bottom_image_size: bottomImageSize,
-        current_direction: currentDirection,
+        ...handleImageResult,
+        thumbnail_buffer: thumbnail
+            ? Uint8Array.from(atob(thumbnail), (char) => char.charCodeAt(0)).buffer
+            : undefined,
     };
 };
 
+/**
+ * 启动后台拼接线程，截图加入队列后自动处理，结果通过 SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY 事件发送
+ */
+export const scrollScreenshotStartWorker = async (thumbnailSize: number) => {
+    const result = await invoke('scroll_screenshot_start_worker', {
+        thumbnailSize,
+    });
+    return result;
+};
+
+export const scrollScreenshotStopWorker = async () => {
+    const result = await invoke('scroll_screenshot_stop_worker');
+    return result;
+};
+
 export type ScrollScreenshotCaptureSize = {
     top_image_size: number;
     bottom_image_size: number;
@@ -167,6 +342,69 @@ export const scrollScreenshotSaveToFile = async (filePath: string) => {
     return result;
 };
 
+export enum ScrollExportFormat {
+    /// 单个 PNG 文件
+    Png = 'Png',
+    /// 按最大尺寸分割为多个编号的 PNG 文件
+    PngTiles = 'PngTiles',
+    /// 单个多页 TIFF 文件
+    MultiPageTiff = 'MultiPageTiff',
+    /// 在接近最大尺寸的留白处分页，输出多个编号的 PNG 文件
+    PngPages = 'PngPages',
+    /// 在接近最大尺寸的留白处分页，输出单个多页 PDF 文件
+    Pdf = 'Pdf',
+}
+
+/**
+ * 按指定格式导出滚动截图，返回写入的文件列表
+ * @param maxPageSize 分块或分页导出时每块在滚动方向上的最大尺寸，为 0 时不分块
+ */
+export const scrollScreenshotExportToFile = async (
+    filePath: string,
+    exportFormat: ScrollExportFormat,
+    maxPageSize: number,
+) => {
+    const result = await invoke<string[]>('scroll_screenshot_export_to_file', {
+        filePath,
+        exportFormat,
+        maxPageSize,
+    });
+    return result;
+};
+
+export enum ScrollVideoFormat {
+    Mp4 = 'Mp4',
+    WebM = 'WebM',
+    Gif = 'Gif',
+}
+
+export type ScrollVideoOptions = {
+    /** 视频宽度 */
+    viewport_width: number;
+    /** 视频高度 */
+    viewport_height: number;
+    /** 滚动速度（视频像素/秒） */
+    speed: number;
+    /** 帧率 */
+    frame_rate: number;
+};
+
+/**
+ * 将拼接结果渲染为滚动播放的视频或 GIF
+ */
+export const scrollScreenshotExportVideo = async (
+    filePath: string,
+    format: ScrollVideoFormat,
+    options: ScrollVideoOptions,
+) => {
+    const result = await invoke('scroll_screenshot_export_video', {
+        filePath,
+        format,
+        options,
+    });
+    return result;
+};
+
 export const scrollScreenshotSaveToClipboard = async () => {
     const result = await invoke('scroll_screenshot_save_to_clipboard');
     return result;
@@ -177,6 +415,34 @@ export const scrollScreenshotClear = async () => {
     return result;
 };
 
+/**
+ * 撤销最后追加的图片，返回撤销后的截图尺寸
+ */
+export const scrollScreenshotUndo = async () => {
+    const result = await invoke<ScrollScreenshotCaptureSize>('scroll_screenshot_undo');
+    return result;
+};
+
+/**
+ * 将当前滚动截图会话保存到目录
+ */
+export const scrollScreenshotSaveSession = async (directory: string) => {
+    const result = await invoke('scroll_screenshot_save_session', {
+        directory,
+    });
+    return result;
+};
+
+/**
+ * 从目录恢复滚动截图会话，返回恢复后的截图尺寸
+ */
+export const scrollScreenshotRestoreSession = async (directory: string) => {
+    const result = await invoke<ScrollScreenshotCaptureSize>('scroll_screenshot_restore_session', {
+        directory,
+    });
+    return result;
+};
+
 export const scrollScreenshotGetImageData = async (): Promise<Blob | undefined> => {
     let result: ArrayBuffer | undefined;
     try {
@@ -192,3 +458,73 @@ export const scrollScreenshotGetImageData = async (): Promise<Blob | undefined>
 
     return new Blob([result]);
 };
+
+export const scrollScreenshotPanoramaInit = async (
+    sampleRate: number,
+    minSampleSize: number,
+    maxSampleSize: number,
+    cornerThreshold: number,
+    descriptorPatchSize: number,
+    tileSize: number,
+    frameMemoryBudget: number,
+) => {
+    const result = await invoke('scroll_screenshot_panorama_init', {
+        sampleRate,
+        minSampleSize,
+        maxSampleSize,
+        cornerThreshold,
+        descriptorPatchSize,
+        tileSize,
+        frameMemoryBudget,
+    });
+    return result;
+};
+
+export enum ScrollFrameStatus {
+    /// 帧带来了新的区域
+    Appended = 'Appended',
+    /// 帧与已有内容一致
+    Unchanged = 'Unchanged',
+    /// 没有匹配到已有内容
+    NotMatched = 'NotMatched',
+}
+
+export type ScrollPanoramaHandleResult = {
+    /** 为空时表示没有待处理的图片 */
+    status: ScrollFrameStatus | null;
+    /** 帧在画布上的位置（相对首帧） */
+    position: { x: number; y: number } | null;
+    /** 画布外接矩形（相对首帧） */
+    bounds: ElementRect;
+    /** 尚未覆盖的网格，坐标相对画布左上角 */
+    uncovered_tile_list: ElementRect[];
+};
+
+/**
+ * 以二维全景的方式处理截图队列中的下一张图片，截图仍通过 scrollScreenshotCapture 截取
+ */
+export const scrollScreenshotPanoramaHandleImage = async () => {
+    const result = await invoke<ScrollPanoramaHandleResult>(
+        'scroll_screenshot_panorama_handle_image',
+    );
+    return result;
+};
+
+/**
+ * @param backgroundColor 未覆盖区域的 RGBA 颜色，为空时保持透明
+ */
+export const scrollScreenshotPanoramaSaveToFile = async (
+    filePath: string,
+    backgroundColor?: [number, number, number, number],
+) => {
+    const result = await invoke('scroll_screenshot_panorama_save_to_file', {
+        filePath,
+        backgroundColor,
+    });
+    return result;
+};
+
+export const scrollScreenshotPanoramaClear = async () => {
+    const result = await invoke('scroll_screenshot_panorama_clear');
+    return result;
+};
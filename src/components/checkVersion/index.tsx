@@ -96,6 +96,7 @@ export const CheckVersion: React.FC = () => {
                                 currentVersion,
                             },
                         ),
+                        intl.formatMessage({ id: 'common.newVersion.openWebsite' }),
                     ).then(() => {
                         hasSendRef.current = true;
                         clearIntervalRef();
@@ -23,6 +23,10 @@ import { AntdContext } from '../globalLayoutExtra';
 import { appError } from '@/utils/log';
 import { debounce } from 'es-toolkit';
 import { ocrRelease } from '@/commands/ocr';
+import {
+    SCROLL_SCREENSHOT_AUTO_SCROLL_PROGRESS_EMIT_KEY,
+    SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY,
+} from '@/commands/scrollScreenshot';
 
 type Listener = {
     event: string;
@@ -269,6 +273,14 @@ const EventListenerCore: React.FC<{ children: React.ReactNode }> = ({ children }
                     event: 'release-draw-page',
                     callback: async () => {},
                 });
+                defaultListener.push({
+                    event: SCROLL_SCREENSHOT_AUTO_SCROLL_PROGRESS_EMIT_KEY,
+                    callback: async () => {},
+                });
+                defaultListener.push({
+                    event: SCROLL_SCREENSHOT_HANDLE_IMAGE_EMIT_KEY,
+                    callback: async () => {},
+                });
             }
 
             if (isFullScreenDraw || isFullScreenDrawSwitchMouseThrough) {
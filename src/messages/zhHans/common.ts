@@ -4,6 +4,7 @@ export const common = {
     'common.delete.tip': '确定删除“{name}”吗？',
     'common.newVersion': 'Snow Shot 新版本 {latestVersion} 已发布 \n当前版本 {currentVersion}',
     'common.newVersion.title': '发现新版本 {latestVersion}',
+    'common.newVersion.openWebsite': '打开网站',
     'common.permission.error.title': '未授予应用关键权限',
     'common.permission.error.description':
         '请在开启应用正常运行所必要的“录屏与系统录音”和“辅助功能”权限',
@@ -187,6 +187,27 @@ export const settings = {
         '图片特征描述大小',
     'settings.systemSettings.scrollScreenshotSettings.imageFeatureDescriptionLength.tip':
         '如果是 8 则以特征点为中心选取 8x8 的区域进行比较',
+    'settings.systemSettings.scrollScreenshotSettings.seamMode': '接缝处理',
+    'settings.systemSettings.scrollScreenshotSettings.seamMode.tip':
+        '拼接时两张图片重叠区域的处理方式，渐变过渡可减弱抗锯齿文字和渐变背景上的接缝，差异最小则在重叠区域内选择最相似的位置切换图片',
+    'settings.systemSettings.scrollScreenshotSettings.seamMode.overwrite': '直接覆盖',
+    'settings.systemSettings.scrollScreenshotSettings.seamMode.feather': '渐变过渡',
+    'settings.systemSettings.scrollScreenshotSettings.seamMode.minDifference': '差异最小',
+    'settings.systemSettings.scrollScreenshotSettings.matcherType': '匹配方式',
+    'settings.systemSettings.scrollScreenshotSettings.matcherType.tip':
+        '计算相邻截图偏移的方式，特征点适合内容丰富的页面，相位相关和逐行对齐适合留白较多、特征点稀少的页面',
+    'settings.systemSettings.scrollScreenshotSettings.matcherType.corner': '特征点',
+    'settings.systemSettings.scrollScreenshotSettings.matcherType.phaseCorrelation': '相位相关',
+    'settings.systemSettings.scrollScreenshotSettings.matcherType.rowHash': '逐行对齐',
+    'settings.systemSettings.scrollScreenshotSettings.crossAxisTolerance': '允许的横向偏移',
+    'settings.systemSettings.scrollScreenshotSettings.crossAxisTolerance.tip':
+        '滚动时页面在滚动垂直方向上允许的最大偏移像素，拼接时会自动校正偏移并裁剪掉未对齐的边缘，为 0 时不允许偏移',
+    'settings.systemSettings.scrollScreenshotSettings.frameMemoryBudget': '内存占用上限（MB）',
+    'settings.systemSettings.scrollScreenshotSettings.frameMemoryBudget.tip':
+        '已拼接的图片会压缩保存在内存中，超出上限后较早的图片会暂存到临时目录，为 0 时不限制',
+    'settings.systemSettings.scrollScreenshotSettings.trimScrollbar': '裁剪滚动条',
+    'settings.systemSettings.scrollScreenshotSettings.trimScrollbar.tip':
+        '自动识别页面边缘与滚动不同步的滚动条、浮动按钮等区域，并在导出时从每张截图中裁剪掉',
     'settings.commonSettings.trayIconSettings': '托盘',
     'settings.commonSettings.trayIconSettings.defaultIcons': '默认图标',
     'settings.commonSettings.trayIconSettings.defaultIcons.default': '默认',